@@ -1,15 +1,116 @@
 use anyhow::Result;
 use std::fs::File;
+use std::io::{BufReader, Read, Seek};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
-use rodio::{OutputStream, OutputStreamBuilder, Sink, Source};
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
+use rodio::cpal::{BufferSize, Device};
+use rodio::source::UniformSourceIterator;
+use rodio::{ChannelCount, Decoder, OutputStream, OutputStreamBuilder, SampleRate, Sink, Source};
+
+/// Slowest and fastest music playback rates accepted by `set_playback_rate`.
+const MIN_PLAYBACK_RATE: f32 = 0.25;
+const MAX_PLAYBACK_RATE: f32 = 2.0;
+/// Extra music speed per level above 1 when the tempo follows the level.
+const PLAYBACK_RATE_PER_LEVEL: f32 = 0.05;
+/// Level-driven tempo tops out here so late levels stay listenable.
+const MAX_LEVEL_PLAYBACK_RATE: f32 = 1.5;
+/// Fade-in for a track that replaces one already playing, to soften the cut.
+const MUSIC_SWITCH_FADE: Duration = Duration::from_millis(750);
+
+/// Pitch and length of the level-up chime.
+const CHIME_FREQUENCY: f32 = 880.0;
+const CHIME_DURATION: Duration = Duration::from_millis(180);
+
+/// Line clear tones, rising from a single to a Tetris, and a T-Spin tone that
+/// stands apart from all of them.
+const CLEAR_TONE_FREQUENCIES: [f32; 4] = [262.0, 330.0, 392.0, 523.0];
+const TSPIN_TONE_FREQUENCY: f32 = 740.0;
+const CLEAR_TONE_DURATION: Duration = Duration::from_millis(120);
+const BIG_CLEAR_TONE_DURATION: Duration = Duration::from_millis(260);
+
+/// Smallest buffer `audio_latency_ms` may ask for, in frames; below this most
+/// backends underrun.
+const MIN_BUFFER_FRAMES: u32 = 64;
+
+/// Output format assumed until a stream reports its own.
+const DEFAULT_CHANNELS: ChannelCount = 2;
+const DEFAULT_SAMPLE_RATE: SampleRate = 44_100;
+
+/// Opens a replacement output stream, with the buffer sized for the given latency,
+/// that raises the given flag on stream errors.
+type StreamOpener = fn(Option<u32>, Arc<AtomicBool>) -> Result<OutputStream>;
+
+/// A decoded track, already converted to the output format.
+pub type MusicSource = Box<dyn Source + Send>;
+
+/// Where the music plays. `rodio::Sink` plays through the output stream; tests can
+/// put a recorder in its place to check what would have been played.
+pub trait MusicSink {
+    fn append(&self, source: MusicSource);
+    fn empty(&self) -> bool;
+    fn skip_one(&self);
+    fn stop(&self);
+    fn pause(&self);
+    fn play(&self);
+    fn set_volume(&self, volume: f32);
+    fn set_speed(&self, speed: f32);
+}
+
+impl MusicSink for Sink {
+    fn append(&self, source: MusicSource) {
+        Sink::append(self, source);
+    }
+
+    fn empty(&self) -> bool {
+        Sink::empty(self)
+    }
+
+    fn skip_one(&self) {
+        Sink::skip_one(self);
+    }
+
+    fn stop(&self) {
+        Sink::stop(self);
+    }
+
+    fn pause(&self) {
+        Sink::pause(self);
+    }
+
+    fn play(&self) {
+        Sink::play(self);
+    }
+
+    fn set_volume(&self, volume: f32) {
+        Sink::set_volume(self, volume);
+    }
+
+    fn set_speed(&self, speed: f32) {
+        Sink::set_speed(self, speed);
+    }
+}
 
 pub struct AudioPlayer {
     stream: Option<OutputStream>,
-    sink: Option<Sink>,
+    sink: Option<Box<dyn MusicSink>>,
+    /// Format music is converted to before it reaches the sink.
+    channels: ChannelCount,
+    sample_rate: SampleRate,
     playing: Arc<AtomicBool>,
+    stream_error: Arc<AtomicBool>,
+    recovery_attempted: bool,
+    reopen_stream: StreamOpener,
+    /// The `audio_latency_ms` the stream was opened with, kept for reopening it.
+    latency_ms: Option<u32>,
+    music: Option<PathBuf>,
+    master_volume: f32,
+    music_volume: f32,
+    sfx_volume: f32,
+    playback_rate: f32,
 }
 
 impl AudioPlayer {
@@ -17,31 +118,151 @@ impl AudioPlayer {
         Self {
             stream: None,
             sink: None,
+            channels: DEFAULT_CHANNELS,
+            sample_rate: DEFAULT_SAMPLE_RATE,
             playing: Arc::new(AtomicBool::new(false)),
+            stream_error: Arc::new(AtomicBool::new(false)),
+            recovery_attempted: false,
+            reopen_stream: open_default_stream,
+            latency_ms: None,
+            music: None,
+            master_volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            playback_rate: 1.0,
         }
     }
 
     pub fn new() -> Self {
-        Self::try_new().unwrap_or_else(|e| {
-            eprintln!(
-                "Warning: Failed to initialize audio player: {}. Running without sound.",
-                e
-            );
+        Self::open(None, None)
+    }
+
+    /// Plays through the output device called `name`, falling back to the default
+    /// device (with a warning) if no such device exists.
+    pub fn with_device(name: &str) -> Self {
+        Self::open(Some(name), None)
+    }
+
+    /// Plays through the named output device, or the default one, with a buffer sized
+    /// for `latency_ms` when given. Runs without sound if no stream can be opened.
+    pub fn open(device: Option<&str>, latency_ms: Option<u32>) -> Self {
+        let player = match device {
+            Some(name) => Self::try_with_device(name, latency_ms),
+            None => Self::try_new(latency_ms),
+        };
+        let mut player = player.unwrap_or_else(|e| {
+            match device {
+                Some(name) => eprintln!(
+                    "Warning: Failed to initialize audio device {}: {}. Running without sound.",
+                    name, e
+                ),
+                None => eprintln!(
+                    "Warning: Failed to initialize audio player: {}. Running without sound.",
+                    e
+                ),
+            }
             Self::no_sound()
-        })
+        });
+        player.latency_ms = latency_ms;
+        player
+    }
+
+    fn try_with_device(name: &str, latency_ms: Option<u32>) -> Result<Self> {
+        let mut devices: Vec<_> = rodio::cpal::default_host().output_devices()?.collect();
+        let names: Vec<String> = devices
+            .iter()
+            .map(|device| device.name().unwrap_or_default())
+            .collect();
+
+        match select_device(&names, name) {
+            Some(index) => {
+                let stream_error = Arc::new(AtomicBool::new(false));
+                let stream = open_device_stream(
+                    devices.swap_remove(index),
+                    latency_ms,
+                    Arc::clone(&stream_error),
+                )?;
+                Ok(Self::from_stream(stream, stream_error))
+            }
+            None => {
+                eprintln!(
+                    "Warning: Audio device {} not found. Using the default device.",
+                    name
+                );
+                Self::try_new(latency_ms)
+            }
+        }
     }
 
-    fn try_new() -> Result<Self> {
-        let stream = OutputStreamBuilder::open_default_stream()?;
+    fn try_new(latency_ms: Option<u32>) -> Result<Self> {
+        let stream_error = Arc::new(AtomicBool::new(false));
+        let stream = open_default_stream(latency_ms, Arc::clone(&stream_error))?;
+        Ok(Self::from_stream(stream, stream_error))
+    }
+
+    fn from_stream(stream: OutputStream, stream_error: Arc<AtomicBool>) -> Self {
         let sink = Sink::connect_new(stream.mixer());
+        let config = stream.config();
 
-        Ok(Self {
-            stream: Some(stream),
-            sink: Some(sink),
-            playing: Arc::new(AtomicBool::new(false)),
-        })
+        let mut player =
+            Self::with_sink(Box::new(sink), config.channel_count(), config.sample_rate());
+        player.stream = Some(stream);
+        player.stream_error = stream_error;
+        player
     }
 
+    /// Plays music into `sink` at the given output format, with no output stream, so
+    /// no device is needed. Sound effects are dropped.
+    pub fn with_sink(
+        sink: Box<dyn MusicSink>,
+        channels: ChannelCount,
+        sample_rate: SampleRate,
+    ) -> Self {
+        let mut player = Self::no_sound();
+        player.sink = Some(sink);
+        player.channels = channels;
+        player.sample_rate = sample_rate;
+        player
+    }
+
+    /// Checks for a stream error (e.g. the device was unplugged) and, the first time
+    /// one is seen, rebuilds the stream on the default device and restarts the music.
+    /// If that fails too, the player carries on silently.
+    pub fn poll_health(&mut self) {
+        if !self.stream_error.swap(false, Ordering::SeqCst) || self.recovery_attempted {
+            return;
+        }
+        self.recovery_attempted = true;
+
+        let was_playing = self.playing.load(Ordering::SeqCst);
+        if let Some(ref sink) = self.sink {
+            sink.stop();
+        }
+
+        match (self.reopen_stream)(self.latency_ms, Arc::clone(&self.stream_error)) {
+            Ok(stream) => {
+                let sink = Sink::connect_new(stream.mixer());
+                sink.set_volume(self.music_gain());
+                sink.set_speed(self.playback_rate);
+                self.channels = stream.config().channel_count();
+                self.sample_rate = stream.config().sample_rate();
+                self.stream = Some(stream);
+                self.sink = Some(Box::new(sink));
+
+                if was_playing && let Some(path) = self.music.clone() {
+                    self.play_background_music(path);
+                }
+            }
+            Err(_) => {
+                self.stream = None;
+                self.sink = None;
+                self.playing.store(false, Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// Loops the track at `path`. If music is already playing it is replaced, with the
+    /// new track fading in.
     pub fn play_background_music(&mut self, path: PathBuf) {
         if !self.use_audio() {
             return;
@@ -64,7 +285,7 @@ impl AudioPlayer {
             }
         };
 
-        let source = match rodio::Decoder::try_from(file) {
+        let source = match decode_track(BufReader::new(file)) {
             Ok(s) => s,
             Err(e) => {
                 eprintln!(
@@ -76,9 +297,49 @@ impl AudioPlayer {
             }
         };
 
-        if let Some(ref sink) = self.sink {
-            sink.append(source.repeat_infinite());
-            self.playing.store(true, Ordering::SeqCst);
+        if self.play_music_source(source) {
+            self.music = Some(path);
+        }
+    }
+
+    /// Converts `source` to the output format and loops it, replacing any music
+    /// already playing. Returns false if there is no sink to play into.
+    fn play_music_source<S>(&mut self, source: S) -> bool
+    where
+        S: Source + Send + 'static,
+    {
+        let Some(ref sink) = self.sink else {
+            return false;
+        };
+
+        let source = resample(source, self.channels, self.sample_rate).repeat_infinite();
+        if sink.empty() {
+            sink.append(Box::new(source));
+        } else {
+            // Queue the new track behind the looping one, then skip to it
+            sink.append(Box::new(source.fade_in(MUSIC_SWITCH_FADE)));
+            sink.skip_one();
+        }
+        self.playing.store(true, Ordering::SeqCst);
+        true
+    }
+
+    /// Plays a short tone over the music at the sound effect volume.
+    pub fn play_chime(&self) {
+        self.play_tone(Tone {
+            frequency: CHIME_FREQUENCY,
+            duration: CHIME_DURATION,
+        });
+    }
+
+    /// Plays `tone` over the music at the sound effect volume.
+    pub fn play_tone(&self, tone: Tone) {
+        if let Some(ref stream) = self.stream {
+            let source = rodio::source::SineWave::new(tone.frequency)
+                .take_duration(tone.duration)
+                .fade_in(Duration::from_millis(10))
+                .amplify(self.sfx_gain());
+            stream.mixer().add(source);
         }
     }
 
@@ -101,10 +362,59 @@ impl AudioPlayer {
         }
     }
 
-    pub fn set_volume(&mut self, volume: f32) {
-        let volume = volume.clamp(0.0, 1.0);
+    pub fn master_volume(&self) -> f32 {
+        self.master_volume
+    }
+
+    pub fn music_volume(&self) -> f32 {
+        self.music_volume
+    }
+
+    pub fn sfx_volume(&self) -> f32 {
+        self.sfx_volume
+    }
+
+    /// Scales every channel.
+    pub fn set_master_volume(&mut self, volume: f32) {
+        self.master_volume = volume.clamp(0.0, 1.0);
+        self.apply_music_gain();
+    }
+
+    pub fn set_music_volume(&mut self, volume: f32) {
+        self.music_volume = volume.clamp(0.0, 1.0);
+        self.apply_music_gain();
+    }
+
+    pub fn set_sfx_volume(&mut self, volume: f32) {
+        self.sfx_volume = volume.clamp(0.0, 1.0);
+    }
+
+    /// Gain applied to the background music sink.
+    pub fn music_gain(&self) -> f32 {
+        effective_gain(self.master_volume, self.music_volume)
+    }
+
+    /// Gain for sound effects.
+    pub fn sfx_gain(&self) -> f32 {
+        effective_gain(self.master_volume, self.sfx_volume)
+    }
+
+    pub fn playback_rate(&self) -> f32 {
+        self.playback_rate
+    }
+
+    /// Speeds up (> 1.0) or slows down (< 1.0) the music. The sink resamples on the
+    /// fly, so pitch shifts along with tempo.
+    pub fn set_playback_rate(&mut self, rate: f32) {
+        self.playback_rate = clamp_playback_rate(rate);
+        if let Some(ref sink) = self.sink {
+            sink.set_speed(self.playback_rate);
+        }
+    }
+
+    fn apply_music_gain(&self) {
         if let Some(ref sink) = self.sink {
-            sink.set_volume(volume);
+            sink.set_volume(self.music_gain());
         }
     }
 
@@ -113,11 +423,142 @@ impl AudioPlayer {
         self.use_audio() && self.playing.load(Ordering::SeqCst)
     }
 
-    fn use_audio(&self) -> bool {
-        self.stream.is_some()
+    /// Whether there is a stream (or sink) to play into.
+    pub fn use_audio(&self) -> bool {
+        self.sink.is_some()
     }
 }
 
+/// Decodes an audio file (WAV, OGG, MP3 or FLAC) from `reader`.
+pub fn decode_track<R>(reader: R) -> Result<Decoder<R>>
+where
+    R: Read + Seek + Send + Sync + 'static,
+{
+    Ok(Decoder::new(reader)?)
+}
+
+/// Converts `source` to `channels` channels at `sample_rate`, the format the output
+/// plays, so the mixer doesn't have to.
+pub fn resample<S: Source>(
+    source: S,
+    channels: ChannelCount,
+    sample_rate: SampleRate,
+) -> UniformSourceIterator<S> {
+    UniformSourceIterator::new(source, channels, sample_rate)
+}
+
+/// A sine tone played as a sound effect.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tone {
+    pub frequency: f32,
+    pub duration: Duration,
+}
+
+/// The tone for a clear of `lines` rows: higher the more rows went, with T-Spins
+/// on a tone of their own. `None` when nothing was cleared and no T-Spin was made.
+pub fn clear_tone(lines: u32, tspin: bool) -> Option<Tone> {
+    if tspin {
+        return Some(Tone {
+            frequency: TSPIN_TONE_FREQUENCY,
+            duration: BIG_CLEAR_TONE_DURATION,
+        });
+    }
+    let index = (lines as usize).checked_sub(1)?;
+    let frequency = CLEAR_TONE_FREQUENCIES[index.min(CLEAR_TONE_FREQUENCIES.len() - 1)];
+    let duration = if lines >= 4 {
+        BIG_CLEAR_TONE_DURATION
+    } else {
+        CLEAR_TONE_DURATION
+    };
+    Some(Tone {
+        frequency,
+        duration,
+    })
+}
+
+fn clamp_playback_rate(rate: f32) -> f32 {
+    if rate.is_finite() {
+        rate.clamp(MIN_PLAYBACK_RATE, MAX_PLAYBACK_RATE)
+    } else {
+        1.0
+    }
+}
+
+/// Music playback rate for `level` when the tempo follows the level.
+pub fn playback_rate_for_level(level: u32) -> f32 {
+    let steps = level.saturating_sub(1) as f32;
+    (1.0 + steps * PLAYBACK_RATE_PER_LEVEL).min(MAX_LEVEL_PLAYBACK_RATE)
+}
+
+/// Channel volume scaled by the master volume, each clamped to [0, 1].
+fn effective_gain(master: f32, channel: f32) -> f32 {
+    master.clamp(0.0, 1.0) * channel.clamp(0.0, 1.0)
+}
+
+/// Opens `builder`'s stream with an error callback that raises `stream_error`, which
+/// `AudioPlayer::poll_health` checks from the game loop.
+fn open_stream(
+    builder: OutputStreamBuilder,
+    stream_error: Arc<AtomicBool>,
+) -> Result<OutputStream> {
+    let stream = builder
+        .with_error_callback(move |_| stream_error.store(true, Ordering::SeqCst))
+        .open_stream_or_fallback()?;
+    Ok(stream)
+}
+
+fn open_default_stream(
+    latency_ms: Option<u32>,
+    stream_error: Arc<AtomicBool>,
+) -> Result<OutputStream> {
+    let device = rodio::cpal::default_host()
+        .default_output_device()
+        .ok_or_else(|| anyhow::anyhow!("no default output device"))?;
+    open_device_stream(device, latency_ms, stream_error)
+}
+
+/// Opens `device` at its default config, with the buffer sized for `latency_ms` when
+/// given. A buffer size the device rejects falls back to one of its supported configs.
+fn open_device_stream(
+    device: Device,
+    latency_ms: Option<u32>,
+    stream_error: Arc<AtomicBool>,
+) -> Result<OutputStream> {
+    let sample_rate = device.default_output_config()?.sample_rate().0;
+    let builder = OutputStreamBuilder::from_device(device)?;
+    let builder = match latency_ms {
+        Some(ms) => builder.with_buffer_size(buffer_size_for_latency(ms, sample_rate)),
+        None => builder,
+    };
+    open_stream(builder, stream_error)
+}
+
+/// Buffer holding about `latency_ms` of audio at `sample_rate`, in frames, but never
+/// fewer than `MIN_BUFFER_FRAMES`.
+fn buffer_size_for_latency(latency_ms: u32, sample_rate: u32) -> BufferSize {
+    let frames = (sample_rate as u64 * latency_ms as u64 / 1000).min(u32::MAX as u64) as u32;
+    BufferSize::Fixed(frames.max(MIN_BUFFER_FRAMES))
+}
+
+/// Index of the device in `names` that best matches `wanted`: an exact name first,
+/// then a case-insensitive match, then the first name containing `wanted`.
+fn select_device(names: &[String], wanted: &str) -> Option<usize> {
+    let wanted_lower = wanted.to_lowercase();
+    names
+        .iter()
+        .position(|name| name == wanted)
+        .or_else(|| {
+            names
+                .iter()
+                .position(|name| name.to_lowercase() == wanted_lower)
+        })
+        .or_else(|| {
+            names
+                .iter()
+                .position(|name| name.to_lowercase().contains(&wanted_lower))
+        })
+}
+
 impl Default for AudioPlayer {
     fn default() -> Self {
         Self::new()
@@ -134,6 +575,52 @@ impl Drop for AudioPlayer {
 mod tests {
     use super::*;
     use std::io::Write;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU32, AtomicUsize};
+
+    #[test]
+    fn test_buffer_size_for_latency() {
+        assert_eq!(buffer_size_for_latency(20, 48_000), BufferSize::Fixed(960));
+        assert_eq!(buffer_size_for_latency(10, 44_100), BufferSize::Fixed(441));
+        assert_eq!(buffer_size_for_latency(50, 44_100), BufferSize::Fixed(2205));
+    }
+
+    #[test]
+    fn test_buffer_size_for_latency_has_a_floor() {
+        assert_eq!(
+            buffer_size_for_latency(0, 48_000),
+            BufferSize::Fixed(MIN_BUFFER_FRAMES)
+        );
+        assert_eq!(
+            buffer_size_for_latency(1, 8_000),
+            BufferSize::Fixed(MIN_BUFFER_FRAMES)
+        );
+    }
+
+    #[test]
+    fn test_clear_tone_rises_with_lines() {
+        let pitches: Vec<f32> = (1..=4)
+            .map(|lines| clear_tone(lines, false).unwrap().frequency)
+            .collect();
+        assert_eq!(pitches, CLEAR_TONE_FREQUENCIES);
+        assert!(pitches.windows(2).all(|pair| pair[0] < pair[1]));
+
+        assert_eq!(clear_tone(1, false).unwrap().duration, CLEAR_TONE_DURATION);
+        assert_eq!(
+            clear_tone(4, false).unwrap().duration,
+            BIG_CLEAR_TONE_DURATION
+        );
+        assert_eq!(clear_tone(0, false), None);
+    }
+
+    #[test]
+    fn test_clear_tone_tspin_is_distinct() {
+        for lines in 0..=3 {
+            let tone = clear_tone(lines, true).unwrap();
+            assert_eq!(tone.frequency, TSPIN_TONE_FREQUENCY);
+            assert!(!CLEAR_TONE_FREQUENCIES.contains(&tone.frequency));
+        }
+    }
 
     #[test]
     fn test_audio_player_creation() {
@@ -141,6 +628,267 @@ mod tests {
         assert!(player.use_audio() || !player.use_audio());
     }
 
+    fn device_names() -> Vec<String> {
+        [
+            "default",
+            "HDA Intel PCH, ALC3246 Analog",
+            "USB Headset",
+            "usb headset",
+        ]
+        .iter()
+        .map(|name| name.to_string())
+        .collect()
+    }
+
+    #[test]
+    fn test_select_device_prefers_exact_name() {
+        assert_eq!(select_device(&device_names(), "usb headset"), Some(3));
+        assert_eq!(select_device(&device_names(), "USB Headset"), Some(2));
+    }
+
+    #[test]
+    fn test_select_device_case_insensitive_and_partial() {
+        assert_eq!(select_device(&device_names(), "DEFAULT"), Some(0));
+        assert_eq!(select_device(&device_names(), "alc3246"), Some(1));
+    }
+
+    #[test]
+    fn test_select_device_not_found() {
+        assert_eq!(select_device(&device_names(), "HDMI"), None);
+        assert_eq!(select_device(&[], "default"), None);
+    }
+
+    #[test]
+    fn test_effective_gain() {
+        assert_eq!(effective_gain(1.0, 0.5), 0.5);
+        assert_eq!(effective_gain(0.5, 0.5), 0.25);
+        assert_eq!(effective_gain(0.0, 1.0), 0.0);
+        assert_eq!(effective_gain(2.0, -1.0), 0.0);
+        assert_eq!(effective_gain(1.5, 1.5), 1.0);
+    }
+
+    #[test]
+    fn test_volume_setters_clamp_and_mix() {
+        let mut player = AudioPlayer::no_sound();
+        player.set_master_volume(0.5);
+        player.set_music_volume(0.8);
+        player.set_sfx_volume(3.0);
+
+        assert_eq!(player.sfx_volume(), 1.0);
+        assert_eq!(player.music_gain(), 0.4);
+        assert_eq!(player.sfx_gain(), 0.5);
+
+        player.set_master_volume(-1.0);
+        assert_eq!(player.master_volume(), 0.0);
+        assert_eq!(player.music_gain(), 0.0);
+    }
+
+    #[test]
+    fn test_playback_rate_for_level() {
+        assert_eq!(playback_rate_for_level(0), 1.0);
+        assert_eq!(playback_rate_for_level(1), 1.0);
+        assert!((playback_rate_for_level(5) - 1.2).abs() < f32::EPSILON);
+        assert_eq!(playback_rate_for_level(30), MAX_LEVEL_PLAYBACK_RATE);
+    }
+
+    #[test]
+    fn test_set_playback_rate_clamps() {
+        let mut player = AudioPlayer::no_sound();
+
+        player.set_playback_rate(1.25);
+        assert_eq!(player.playback_rate(), 1.25);
+
+        player.set_playback_rate(10.0);
+        assert_eq!(player.playback_rate(), MAX_PLAYBACK_RATE);
+
+        player.set_playback_rate(0.0);
+        assert_eq!(player.playback_rate(), MIN_PLAYBACK_RATE);
+
+        player.set_playback_rate(f32::NAN);
+        assert_eq!(player.playback_rate(), 1.0);
+    }
+
+    static REOPEN_ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+    fn failing_reopen(
+        _latency_ms: Option<u32>,
+        _stream_error: Arc<AtomicBool>,
+    ) -> Result<OutputStream> {
+        REOPEN_ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+        anyhow::bail!("no output device")
+    }
+
+    static REOPEN_LATENCY: AtomicU32 = AtomicU32::new(0);
+
+    fn latency_recording_reopen(
+        latency_ms: Option<u32>,
+        _stream_error: Arc<AtomicBool>,
+    ) -> Result<OutputStream> {
+        REOPEN_LATENCY.store(latency_ms.unwrap_or(0), Ordering::SeqCst);
+        anyhow::bail!("no output device")
+    }
+
+    #[test]
+    fn test_poll_health_reopens_with_configured_latency() {
+        let mut player = AudioPlayer::no_sound();
+        player.reopen_stream = latency_recording_reopen;
+        player.latency_ms = Some(20);
+
+        player.stream_error.store(true, Ordering::SeqCst);
+        player.poll_health();
+
+        assert_eq!(REOPEN_LATENCY.load(Ordering::SeqCst), 20);
+    }
+
+    #[test]
+    fn test_poll_health_rebuilds_once_after_stream_error() {
+        let mut player = AudioPlayer::no_sound();
+        player.reopen_stream = failing_reopen;
+
+        player.poll_health();
+        assert_eq!(REOPEN_ATTEMPTS.load(Ordering::SeqCst), 0);
+
+        player.stream_error.store(true, Ordering::SeqCst);
+        player.poll_health();
+        assert_eq!(REOPEN_ATTEMPTS.load(Ordering::SeqCst), 1);
+        assert!(!player.use_audio());
+
+        // Only one recovery attempt is made
+        player.stream_error.store(true, Ordering::SeqCst);
+        player.poll_health();
+        assert_eq!(REOPEN_ATTEMPTS.load(Ordering::SeqCst), 1);
+    }
+
+    /// A mono 16-bit PCM WAV file holding `samples` at `sample_rate`.
+    fn wav_bytes(sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+        let data_len = (samples.len() * 2) as u32;
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+        wav.extend_from_slice(&sample_rate.to_le_bytes());
+        wav.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for sample in samples {
+            wav.extend_from_slice(&sample.to_le_bytes());
+        }
+        wav
+    }
+
+    fn test_track() -> Decoder<std::io::Cursor<Vec<u8>>> {
+        let samples = [0, 8_000, 16_000, 8_000, 0, -8_000, -16_000, -8_000];
+        decode_track(std::io::Cursor::new(wav_bytes(8_000, &samples))).unwrap()
+    }
+
+    #[test]
+    fn test_decode_and_resample_without_device() {
+        let track = test_track();
+        assert_eq!(track.channels(), 1);
+        assert_eq!(track.sample_rate(), 8_000);
+
+        let resampled = resample(track, 2, 16_000);
+        assert_eq!(resampled.channels(), 2);
+        assert_eq!(resampled.sample_rate(), 16_000);
+
+        let samples: Vec<f32> = resampled.collect();
+        // Doubling the rate interpolates between each pair of the 8 input frames,
+        // giving 15 stereo frames
+        assert_eq!(samples.len(), 30);
+        // Mono is copied to both channels
+        assert!(samples.chunks(2).all(|frame| frame[0] == frame[1]));
+        assert_eq!(samples[0], 0.0);
+        assert!(samples.iter().any(|&sample| sample > 0.4));
+    }
+
+    #[test]
+    fn test_playback_rate_steps_through_samples() {
+        let peak = 16_000.0 / 32_768.0;
+
+        // At double speed the 8 kHz track plays as if it were 16 kHz, so converting
+        // back to 8 kHz keeps every other sample
+        let fast: Vec<f32> = resample(test_track().speed(2.0), 1, 8_000).collect();
+        assert_eq!(fast, [0.0, peak, 0.0, -peak]);
+
+        // At half speed it steps half a sample at a time, interpolating between the
+        // 8 input samples for 15 in all
+        let slow: Vec<f32> = resample(test_track().speed(0.5), 1, 8_000).collect();
+        assert_eq!(slow.len(), 15);
+        assert_eq!(slow[1], peak / 4.0);
+        assert_eq!(slow[4], peak);
+        assert_eq!(slow[12], -peak);
+        assert!(slow.iter().all(|sample| sample.abs() <= peak));
+    }
+
+    #[test]
+    fn test_decode_track_rejects_garbage() {
+        assert!(decode_track(std::io::Cursor::new(vec![0u8; 64])).is_err());
+    }
+
+    /// Channels, sample rate and opening samples of an appended source.
+    type Recorded = (ChannelCount, SampleRate, Vec<f32>);
+
+    /// Records the format and opening samples of each source appended to it.
+    #[derive(Clone, Default)]
+    struct RecordingSink {
+        appended: Arc<Mutex<Vec<Recorded>>>,
+        skips: Arc<AtomicUsize>,
+    }
+
+    impl MusicSink for RecordingSink {
+        fn append(&self, source: MusicSource) {
+            let format = (source.channels(), source.sample_rate());
+            let opening = source.take(8).collect();
+            self.appended
+                .lock()
+                .unwrap()
+                .push((format.0, format.1, opening));
+        }
+
+        fn empty(&self) -> bool {
+            self.appended.lock().unwrap().is_empty()
+        }
+
+        fn skip_one(&self) {
+            self.skips.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn stop(&self) {}
+        fn pause(&self) {}
+        fn play(&self) {}
+        fn set_volume(&self, _volume: f32) {}
+        fn set_speed(&self, _speed: f32) {}
+    }
+
+    #[test]
+    fn test_music_plays_into_injected_sink() {
+        let sink = RecordingSink::default();
+        let mut player = AudioPlayer::with_sink(Box::new(sink.clone()), 2, 16_000);
+        assert!(!player.is_playing());
+
+        assert!(player.play_music_source(test_track()));
+        assert!(player.is_playing());
+        {
+            let appended = sink.appended.lock().unwrap();
+            assert_eq!(appended.len(), 1);
+            let (channels, sample_rate, opening) = &appended[0];
+            assert_eq!((*channels, *sample_rate), (2, 16_000));
+            assert_eq!(opening.len(), 8);
+        }
+
+        // A second track replaces the first
+        assert!(player.play_music_source(test_track()));
+        assert_eq!(sink.appended.lock().unwrap().len(), 2);
+        assert_eq!(sink.skips.load(Ordering::SeqCst), 1);
+
+        assert!(!AudioPlayer::no_sound().play_music_source(test_track()));
+    }
+
     #[test]
     fn test_audio_player_with_test_file() {
         let test_wav_path = PathBuf::from("/tmp/test_tetris_audio.wav");
@@ -1,5 +1,62 @@
+use crate::config::ClearGravity;
 use crate::tetrimino::{Tetrimino, TetriminoType};
+use std::fmt;
 
+/// A cell coordinate outside the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfBounds {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cell ({}, {}) is outside the board", self.x, self.y)
+    }
+}
+
+impl std::error::Error for OutOfBounds {}
+
+/// A running FNV-1a hash. Unlike `std::hash`, its output is fixed across builds and
+/// platforms, so checksums from one run can be compared against another's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checksum(u64);
+
+impl Checksum {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(Self::PRIME);
+        }
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    /// Folds in a cell or piece kind, with empty distinct from every piece.
+    pub fn write_kind(&mut self, kind: Option<TetriminoType>) {
+        self.write(&[kind.map_or(b'.', |kind| kind.to_char() as u8)]);
+    }
+
+    pub fn finish(self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for Checksum {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Board {
     width: usize,
     height: usize,
@@ -15,30 +72,134 @@ impl Board {
         }
     }
 
+    /// True if every block is within the side walls and above the floor without
+    /// overlapping the stack. Blocks above the top row (y < 0) count as empty space.
     pub fn is_valid_position(&self, tetromino: &Tetrimino) -> bool {
-        for (dx, dy) in tetromino.get_blocks() {
-            let x = tetromino.x + dx;
-            let y = tetromino.y + dy;
+        tetromino
+            .get_blocks()
+            .into_iter()
+            .all(|(dx, dy)| self.is_cell_free(tetromino.x + dx, tetromino.y + dy))
+    }
 
-            if x < 0 || x >= self.width as i32 || y < 0 || y >= self.height as i32 {
-                return false;
-            }
+    /// The lowest row `tetromino` can fall to straight down from its current,
+    /// valid, position: how far its most obstructed block can drop.
+    pub fn drop_row(&self, tetromino: &Tetrimino) -> i32 {
+        let fall = tetromino
+            .get_blocks()
+            .into_iter()
+            .map(|(dx, dy)| {
+                let (x, y) = (tetromino.x + dx, tetromino.y + dy);
+                (0..)
+                    .take_while(|&step| self.is_cell_free(x, y + step + 1))
+                    .count() as i32
+            })
+            .min()
+            .unwrap_or(0);
+        tetromino.y + fall
+    }
 
-            if self.cells[y as usize][x as usize].is_some() {
-                return false;
-            }
+    /// Whether a block could sit at `(x, y)`: inside the walls and floor, and either
+    /// above the board or on an empty cell.
+    pub fn is_cell_free(&self, x: i32, y: i32) -> bool {
+        if x < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return false;
         }
-        true
+        y < 0 || self.cells[y as usize][x as usize].is_none()
     }
 
+    /// Height of each column's stack, measured from the floor to its highest filled cell.
+    pub fn column_heights(&self) -> Vec<usize> {
+        (0..self.width)
+            .map(|x| {
+                (0..self.height)
+                    .find(|&y| self.cells[y][x].is_some())
+                    .map_or(0, |top| self.height - top)
+            })
+            .collect()
+    }
+
+    /// Sum of the height differences between neighbouring columns; 0 for a flat stack.
+    pub fn bumpiness(&self) -> usize {
+        self.column_heights()
+            .windows(2)
+            .map(|pair| pair[0].abs_diff(pair[1]))
+            .sum()
+    }
+
+    /// Empty cells with a filled cell somewhere above them in the same column.
+    pub fn count_holes(&self) -> usize {
+        self.hole_positions().len()
+    }
+
+    /// Every hole, as `(x, y)`, column by column from the top.
+    pub fn hole_positions(&self) -> Vec<(usize, usize)> {
+        (0..self.width)
+            .flat_map(|x| {
+                (0..self.height)
+                    .skip_while(move |&y| self.cells[y][x].is_none())
+                    .filter(move |&y| self.cells[y][x].is_none())
+                    .map(move |y| (x, y))
+            })
+            .collect()
+    }
+
+    /// Holes on this board that weren't holes on `before`.
+    pub fn new_holes_since(&self, before: &Board) -> Vec<(usize, usize)> {
+        let old = before.hole_positions();
+        self.hole_positions()
+            .into_iter()
+            .filter(|hole| !old.contains(hole))
+            .collect()
+    }
+
+    /// Empties the top `count` rows (all rows if `count` exceeds the height).
+    pub fn clear_top_rows(&mut self, count: usize) {
+        for row in self.cells.iter_mut().take(count) {
+            row.fill(None);
+        }
+    }
+
+    /// Spawn-zone check used for top-out. Cells above the visible board act as an
+    /// empty buffer, so only real overlaps block a spawn, the same as any other move.
+    pub fn can_spawn(&self, tetromino: &Tetrimino) -> bool {
+        self.is_valid_position(tetromino)
+    }
+
+    /// Board cells, as `(x, y)`, that `tetromino` overlaps and that are already filled.
+    pub fn overlapping_cells(&self, tetromino: &Tetrimino) -> Vec<(usize, usize)> {
+        tetromino
+            .get_blocks()
+            .into_iter()
+            .filter_map(|(dx, dy)| {
+                let x = usize::try_from(tetromino.x + dx).ok()?;
+                let y = usize::try_from(tetromino.y + dy).ok()?;
+                self.get_cell(x, y).map(|_| (x, y))
+            })
+            .collect()
+    }
+
+    /// Writes the piece's blocks into the board. Blocks that fall outside it are dropped.
     pub fn lock_tetromino(&mut self, tetromino: &Tetrimino) {
         for (dx, dy) in tetromino.get_blocks() {
-            let x = (tetromino.x + dx) as usize;
-            let y = (tetromino.y + dy) as usize;
-            self.cells[y][x] = Some(tetromino.kind);
+            if let (Ok(x), Ok(y)) = (
+                usize::try_from(tetromino.x + dx),
+                usize::try_from(tetromino.y + dy),
+            ) {
+                let _ = self.set_cell(x, y, Some(tetromino.kind));
+            }
         }
     }
 
+    /// A copy of the board with the piece locked and full lines cleared, along with
+    /// how many lines went. Leaves `self` untouched, so placements can be tried
+    /// cheaply without cloning the whole game.
+    pub fn with_piece_locked(&self, tetromino: &Tetrimino) -> (Board, u32) {
+        let mut after = self.clone();
+        after.lock_tetromino(tetromino);
+        let lines = after.clear_lines();
+        (after, lines)
+    }
+
     pub fn get_full_lines(&self) -> Vec<usize> {
         let mut full_lines = Vec::new();
         for y in 0..self.height {
@@ -51,11 +212,13 @@ impl Board {
 
     pub fn clear_lines(&mut self) -> u32 {
         let mut lines_cleared = 0;
-        let mut y = self.height - 1;
+        // One past the row being checked, so row 0 is checked too
+        let mut y = self.height;
 
         while y > 0 {
-            if self.cells[y].iter().all(|cell| cell.is_some()) {
-                self.cells.remove(y);
+            // After a removal the rows above shift down into y - 1, so check it again
+            if self.cells[y - 1].iter().all(|cell| cell.is_some()) {
+                self.cells.remove(y - 1);
                 self.cells.insert(0, vec![None; self.width]);
                 lines_cleared += 1;
             } else {
@@ -66,6 +229,127 @@ impl Board {
         lines_cleared
     }
 
+    /// Clears full lines using the given gravity model and returns the total cleared,
+    /// including any chained clears from cascade gravity.
+    pub fn clear_lines_with(&mut self, gravity: ClearGravity) -> u32 {
+        match gravity {
+            ClearGravity::Naive => self.clear_lines(),
+            ClearGravity::Cascade => self.clear_lines_cascade(),
+        }
+    }
+
+    /// Clears the rows that are full right now and lets the rest fall, without
+    /// clearing any rows that fill as a result; cascade gravity may leave new full
+    /// rows behind for the next step.
+    pub fn clear_step_with(&mut self, gravity: ClearGravity) -> u32 {
+        match gravity {
+            ClearGravity::Naive => self.clear_lines(),
+            ClearGravity::Cascade => self.clear_cascade_step(),
+        }
+    }
+
+    fn clear_lines_cascade(&mut self) -> u32 {
+        let mut lines_cleared = 0;
+
+        loop {
+            let lines = self.clear_cascade_step();
+            if lines == 0 {
+                return lines_cleared;
+            }
+            lines_cleared += lines;
+        }
+    }
+
+    fn clear_cascade_step(&mut self) -> u32 {
+        let full_lines = self.get_full_lines();
+        for &y in &full_lines {
+            self.cells[y] = vec![None; self.width];
+        }
+        if !full_lines.is_empty() {
+            self.settle_groups();
+        }
+        full_lines.len() as u32
+    }
+
+    /// Drops every connected group of blocks as far as it will go, repeating until
+    /// nothing moves, since a group landing can free the one above it.
+    fn settle_groups(&mut self) {
+        loop {
+            let mut moved = false;
+
+            for group in self.connected_groups() {
+                let blocks: Vec<(usize, usize, Option<TetriminoType>)> = group
+                    .iter()
+                    .map(|&(x, y)| (x, y, self.cells[y][x].take()))
+                    .collect();
+
+                let mut drop = 0;
+                while blocks.iter().all(|&(x, y, _)| {
+                    let below = y + drop + 1;
+                    below < self.height && self.cells[below][x].is_none()
+                }) {
+                    drop += 1;
+                }
+
+                for (x, y, kind) in blocks {
+                    self.cells[y + drop][x] = kind;
+                }
+                moved |= drop > 0;
+            }
+
+            if !moved {
+                return;
+            }
+        }
+    }
+
+    /// Groups filled cells by 4-way connectivity, lowest groups first.
+    fn connected_groups(&self) -> Vec<Vec<(usize, usize)>> {
+        let mut visited = vec![vec![false; self.width]; self.height];
+        let mut groups = Vec::new();
+
+        for y in (0..self.height).rev() {
+            for x in 0..self.width {
+                if visited[y][x] || self.cells[y][x].is_none() {
+                    continue;
+                }
+
+                let mut group = Vec::new();
+                let mut stack = vec![(x, y)];
+                visited[y][x] = true;
+
+                while let Some((cx, cy)) = stack.pop() {
+                    group.push((cx, cy));
+
+                    let mut neighbors = Vec::with_capacity(4);
+                    if cx > 0 {
+                        neighbors.push((cx - 1, cy));
+                    }
+                    if cx + 1 < self.width {
+                        neighbors.push((cx + 1, cy));
+                    }
+                    if cy > 0 {
+                        neighbors.push((cx, cy - 1));
+                    }
+                    if cy + 1 < self.height {
+                        neighbors.push((cx, cy + 1));
+                    }
+
+                    for (nx, ny) in neighbors {
+                        if !visited[ny][nx] && self.cells[ny][nx].is_some() {
+                            visited[ny][nx] = true;
+                            stack.push((nx, ny));
+                        }
+                    }
+                }
+
+                groups.push(group);
+            }
+        }
+
+        groups
+    }
+
     pub fn get_cell(&self, x: usize, y: usize) -> Option<TetriminoType> {
         if y < self.height && x < self.width {
             self.cells[y][x]
@@ -74,6 +358,60 @@ impl Board {
         }
     }
 
+    /// Fills or empties the cell at `(x, y)`.
+    pub fn set_cell(
+        &mut self,
+        x: usize,
+        y: usize,
+        value: Option<TetriminoType>,
+    ) -> Result<(), OutOfBounds> {
+        let cell = self
+            .cells
+            .get_mut(y)
+            .and_then(|row| row.get_mut(x))
+            .ok_or(OutOfBounds { x, y })?;
+        *cell = value;
+        Ok(())
+    }
+
+    /// Sets every cell in the rectangle with corners `(x0, y0)` and `(x1, y1)`,
+    /// inclusive and in either order. The part outside the board is ignored.
+    pub fn fill_region(
+        &mut self,
+        x0: usize,
+        y0: usize,
+        x1: usize,
+        y1: usize,
+        value: Option<TetriminoType>,
+    ) {
+        let (left, right) = (x0.min(x1), x0.max(x1).min(self.width.saturating_sub(1)));
+        let (top, bottom) = (y0.min(y1), y0.max(y1).min(self.height.saturating_sub(1)));
+        if left >= self.width || top >= self.height {
+            return;
+        }
+        for row in &mut self.cells[top..=bottom] {
+            row[left..=right].fill(value);
+        }
+    }
+
+    /// Empties every cell.
+    pub fn clear_all(&mut self) {
+        for row in &mut self.cells {
+            row.fill(None);
+        }
+    }
+
+    /// A stable hash of the board's size and every cell, for spotting replay desyncs.
+    pub fn checksum(&self) -> u64 {
+        let mut checksum = Checksum::new();
+        checksum.write_u64(self.width as u64);
+        checksum.write_u64(self.height as u64);
+        for &cell in self.cells.iter().flatten() {
+            checksum.write_kind(cell);
+        }
+        checksum.finish()
+    }
+
     pub fn get_width(&self) -> usize {
         self.width
     }
@@ -176,7 +514,69 @@ mod tests {
     #[test]
     fn test_is_valid_position_top_boundary() {
         let board = Board::new(10, 20);
+        // Rows above the board are open space
         let piece = create_test_piece(TetriminoType::I, 0, -1, 0);
+        assert!(board.is_valid_position(&piece));
+    }
+
+    #[test]
+    fn test_is_valid_position_vertical_i_spanning_top_edge() {
+        let board = Board::new(10, 20);
+        // Rotation 1 fills column x + 2 from y to y + 3, so rows -2..=1
+        let piece = create_test_piece(TetriminoType::I, 0, -2, 1);
+        assert!(board.is_valid_position(&piece));
+    }
+
+    #[test]
+    fn test_is_valid_position_above_board_still_checks_walls() {
+        let board = Board::new(10, 20);
+        let past_left = create_test_piece(TetriminoType::I, -3, -2, 1);
+        let past_right = create_test_piece(TetriminoType::I, 8, -2, 1);
+        assert!(!board.is_valid_position(&past_left));
+        assert!(!board.is_valid_position(&past_right));
+    }
+
+    #[test]
+    fn test_is_valid_position_above_board_still_collides_in_board() {
+        let mut board = Board::new(10, 20);
+        board.cells[1][2] = Some(TetriminoType::O);
+        let piece = create_test_piece(TetriminoType::I, 0, -2, 1);
+        assert!(!board.is_valid_position(&piece));
+
+        // Filling a row it doesn't reach leaves it valid
+        board.cells[1][2] = None;
+        board.cells[2][2] = Some(TetriminoType::O);
+        assert!(board.is_valid_position(&piece));
+    }
+
+    #[test]
+    fn test_is_valid_position_far_above_board() {
+        let board = Board::new(10, 20);
+        let piece = create_test_piece(TetriminoType::O, 4, -10, 0);
+        assert!(board.is_valid_position(&piece));
+    }
+
+    #[test]
+    fn test_lock_tetromino_drops_blocks_above_board() {
+        let mut board = Board::new(10, 20);
+        let piece = create_test_piece(TetriminoType::I, 0, -2, 1);
+        board.lock_tetromino(&piece);
+
+        let filled: Vec<(usize, usize)> = (0..20)
+            .flat_map(|y| (0..10).map(move |x| (x, y)))
+            .filter(|&(x, y)| board.get_cell(x, y).is_some())
+            .collect();
+        assert_eq!(filled, vec![(2, 0), (2, 1)]);
+    }
+
+    #[test]
+    fn test_is_valid_position_partly_above_board() {
+        let mut board = Board::new(10, 20);
+        // A T at y = -1 has its nub above the board and its base on row 0
+        let piece = create_test_piece(TetriminoType::T, 0, -1, 0);
+        assert!(board.is_valid_position(&piece));
+
+        board.cells[0][1] = Some(TetriminoType::I);
         assert!(!board.is_valid_position(&piece));
     }
 
@@ -224,6 +624,43 @@ mod tests {
         assert!(board.is_valid_position(&above_piece));
     }
 
+    #[test]
+    fn test_can_spawn_with_stack_at_visible_top() {
+        let mut board = Board::new(10, 20);
+        for y in 0..20 {
+            board.cells[y][9] = Some(TetriminoType::I);
+        }
+        let piece = create_test_piece(TetriminoType::T, 0, 0, 0);
+        assert!(board.can_spawn(&piece));
+    }
+
+    #[test]
+    fn test_can_spawn_blocked_by_overlap() {
+        let mut board = Board::new(10, 20);
+        board.cells[1][1] = Some(TetriminoType::I);
+        let piece = create_test_piece(TetriminoType::T, 0, 0, 0);
+        assert!(!board.can_spawn(&piece));
+    }
+
+    #[test]
+    fn test_can_spawn_uses_space_above_board_as_buffer() {
+        let mut board = Board::new(10, 20);
+        for x in 0..10 {
+            board.cells[1][x] = Some(TetriminoType::I);
+        }
+        // Only the bottom row of the T reaches the board, at y = 0
+        let piece = create_test_piece(TetriminoType::T, 0, -1, 0);
+        assert!(board.can_spawn(&piece));
+        assert!(board.is_valid_position(&piece));
+    }
+
+    #[test]
+    fn test_can_spawn_rejects_out_of_bounds_columns() {
+        let board = Board::new(10, 20);
+        let piece = create_test_piece(TetriminoType::I, 7, 0, 0);
+        assert!(!board.can_spawn(&piece));
+    }
+
     #[test]
     fn test_lock_tetromino_o_piece() {
         let mut board = Board::new(10, 20);
@@ -260,6 +697,49 @@ mod tests {
         assert_eq!(board.get_cell(2, 6), Some(TetriminoType::L));
     }
 
+    #[test]
+    fn test_column_heights() {
+        let mut board = Board::new(4, 6);
+        board.cells_mut()[5][0] = Some(TetriminoType::I);
+        board.cells_mut()[2][1] = Some(TetriminoType::I);
+        board.cells_mut()[0][3] = Some(TetriminoType::I);
+
+        // Holes under the top cell don't lower a column's height
+        assert_eq!(board.column_heights(), vec![1, 4, 0, 6]);
+    }
+
+    #[test]
+    fn test_bumpiness() {
+        let mut board = Board::new(4, 6);
+        assert_eq!(board.bumpiness(), 0);
+
+        // Heights 1, 4, 0, 6: |1-4| + |4-0| + |0-6|
+        board.cells_mut()[5][0] = Some(TetriminoType::I);
+        board.cells_mut()[2][1] = Some(TetriminoType::I);
+        board.cells_mut()[0][3] = Some(TetriminoType::I);
+        assert_eq!(board.bumpiness(), 13);
+
+        // A flat floor is smooth however high it is
+        let flat = board_from_rows(&["....", "....", "....", "....", "####", "####"]);
+        assert_eq!(flat.bumpiness(), 0);
+    }
+
+    #[test]
+    fn test_clear_top_rows() {
+        let mut board = Board::new(4, 6);
+        for row in board.cells_mut().iter_mut() {
+            row.fill(Some(TetriminoType::O));
+        }
+
+        board.clear_top_rows(2);
+
+        assert!((0..4).all(|x| board.get_cell(x, 1).is_none()));
+        assert!((0..4).all(|x| board.get_cell(x, 2).is_some()));
+
+        board.clear_top_rows(10);
+        assert!(board.cells().iter().flatten().all(Option::is_none));
+    }
+
     #[test]
     fn test_get_full_lines_empty_board() {
         let board = Board::new(10, 20);
@@ -368,6 +848,159 @@ mod tests {
         assert!(board.get_full_lines().is_empty());
     }
 
+    /// Builds a board from rows of `#` or piece letters (filled) and `.` (empty), top
+    /// row first. `#` cells are I blocks.
+    fn board_from_rows(rows: &[&str]) -> Board {
+        let mut board = Board::new(rows[0].len(), rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                board.cells[y][x] = match c {
+                    '#' => Some(TetriminoType::I),
+                    _ => TetriminoType::from_char(c),
+                };
+            }
+        }
+        board
+    }
+
+    fn board_rows(board: &Board) -> Vec<String> {
+        board
+            .cells
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| if cell.is_some() { '#' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_clear_lines_two_adjacent_rows() {
+        let mut board = board_from_rows(&["....", "#...", ".#..", "####", "####", "..##"]);
+
+        assert_eq!(board.clear_lines(), 2);
+        assert_eq!(
+            board_rows(&board),
+            ["....", "....", "....", "#...", ".#..", "..##"]
+        );
+    }
+
+    #[test]
+    fn test_clear_lines_three_adjacent_rows() {
+        let mut board = board_from_rows(&["#...", "..#.", "####", "####", "####", "##.#"]);
+
+        assert_eq!(board.clear_lines(), 3);
+        assert_eq!(
+            board_rows(&board),
+            ["....", "....", "....", "#...", "..#.", "##.#"]
+        );
+    }
+
+    #[test]
+    fn test_clear_lines_four_adjacent_rows() {
+        let mut board = board_from_rows(&["...#", "#...", "####", "####", "####", "####"]);
+
+        assert_eq!(board.clear_lines(), 4);
+        assert_eq!(
+            board_rows(&board),
+            ["....", "....", "....", "....", "...#", "#..."]
+        );
+    }
+
+    #[test]
+    fn test_clear_lines_tetris_on_bottom_rows() {
+        let mut board = Board::new(10, 20);
+        board.cells[14][3] = Some(TetriminoType::T);
+        board.cells[15][0] = Some(TetriminoType::O);
+        board.cells[15][9] = Some(TetriminoType::S);
+        for y in 16..20 {
+            board.cells[y].fill(Some(TetriminoType::I));
+        }
+
+        assert_eq!(board.clear_lines(), 4);
+
+        let mut expected = Board::new(10, 20);
+        expected.cells[18][3] = Some(TetriminoType::T);
+        expected.cells[19][0] = Some(TetriminoType::O);
+        expected.cells[19][9] = Some(TetriminoType::S);
+        assert_eq!(board.cells, expected.cells);
+    }
+
+    #[test]
+    fn test_clear_lines_includes_top_row() {
+        let mut board = board_from_rows(&["####", "#...", "####"]);
+
+        assert_eq!(board.clear_lines(), 2);
+        assert_eq!(board_rows(&board), ["....", "....", "#..."]);
+    }
+
+    fn build_chain_board() -> Board {
+        // . . . .
+        // X . . .   <- falls into the gap below once row 2 clears
+        // X X X X
+        // . X X X
+        let mut board = Board::new(4, 4);
+        board.cells[1][0] = Some(TetriminoType::T);
+        for x in 0..4 {
+            board.cells[2][x] = Some(TetriminoType::I);
+        }
+        for x in 1..4 {
+            board.cells[3][x] = Some(TetriminoType::O);
+        }
+        board
+    }
+
+    #[test]
+    fn test_clear_lines_with_naive_no_chain() {
+        let mut board = build_chain_board();
+        assert_eq!(board.clear_lines_with(ClearGravity::Naive), 1);
+        assert_eq!(board.get_cell(0, 2), Some(TetriminoType::T));
+        assert_eq!(board.get_cell(0, 3), None);
+    }
+
+    #[test]
+    fn test_clear_lines_with_cascade_chains() {
+        let mut board = build_chain_board();
+        assert_eq!(board.clear_lines_with(ClearGravity::Cascade), 2);
+        assert!(board.cells.iter().flatten().all(|cell| cell.is_none()));
+    }
+
+    #[test]
+    fn test_clear_step_with_cascade_stops_after_one_step() {
+        let mut board = build_chain_board();
+        assert_eq!(board.clear_step_with(ClearGravity::Cascade), 1);
+        assert_eq!(board.get_full_lines(), vec![3]);
+        assert_eq!(board.clear_step_with(ClearGravity::Cascade), 1);
+        assert_eq!(board.clear_step_with(ClearGravity::Cascade), 0);
+    }
+
+    #[test]
+    fn test_clear_lines_with_cascade_drops_groups_independently() {
+        // X X . .
+        // . X . .
+        // X X X X
+        // X . . X
+        let mut board = Board::new(4, 4);
+        board.cells[0][0] = Some(TetriminoType::S);
+        board.cells[0][1] = Some(TetriminoType::S);
+        board.cells[1][1] = Some(TetriminoType::S);
+        for x in 0..4 {
+            board.cells[2][x] = Some(TetriminoType::I);
+        }
+        board.cells[3][0] = Some(TetriminoType::J);
+        board.cells[3][3] = Some(TetriminoType::L);
+
+        assert_eq!(board.clear_lines_with(ClearGravity::Cascade), 1);
+
+        // The S group hangs on column 1, which is open down to the floor
+        assert_eq!(board.get_cell(1, 3), Some(TetriminoType::S));
+        assert_eq!(board.get_cell(1, 2), Some(TetriminoType::S));
+        assert_eq!(board.get_cell(0, 2), Some(TetriminoType::S));
+        assert_eq!(board.get_cell(0, 3), Some(TetriminoType::J));
+        assert_eq!(board.get_cell(3, 3), Some(TetriminoType::L));
+    }
+
     #[test]
     fn test_get_cell_in_bounds() {
         let mut board = Board::new(10, 20);
@@ -482,6 +1115,185 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fill_region() {
+        let mut board = Board::new(10, 20);
+        board.fill_region(7, 19, 2, 17, Some(TetriminoType::J));
+
+        for y in 0..20 {
+            for x in 0..10 {
+                let inside = (2..=7).contains(&x) && (17..=19).contains(&y);
+                assert_eq!(board.get_cell(x, y).is_some(), inside, "({}, {})", x, y);
+            }
+        }
+
+        board.fill_region(3, 18, 3, 18, None);
+        assert_eq!(board.get_cell(3, 18), None);
+        assert_eq!(board.get_cell(4, 18), Some(TetriminoType::J));
+    }
+
+    #[test]
+    fn test_fill_region_clamps_to_board() {
+        let mut board = Board::new(10, 20);
+        board.fill_region(8, 15, 50, 99, Some(TetriminoType::T));
+
+        for y in 15..20 {
+            assert_eq!(board.get_cell(8, y), Some(TetriminoType::T));
+            assert_eq!(board.get_cell(9, y), Some(TetriminoType::T));
+        }
+        assert_eq!(board.get_cell(7, 19), None);
+        assert_eq!(board.get_cell(8, 14), None);
+
+        // Wholly off the board is a no-op
+        board.fill_region(10, 0, 20, 5, Some(TetriminoType::T));
+        board.fill_region(0, 20, 5, 30, Some(TetriminoType::T));
+        assert!(board.get_full_lines().is_empty());
+        assert_eq!(board.cells().iter().flatten().flatten().count(), 10);
+    }
+
+    #[test]
+    fn test_clear_all() {
+        let mut board = Board::new(10, 20);
+        board.fill_region(0, 0, 9, 19, Some(TetriminoType::S));
+        assert_eq!(board.get_full_lines().len(), 20);
+
+        board.clear_all();
+        assert!(board.cells().iter().flatten().all(|cell| cell.is_none()));
+    }
+
+    #[test]
+    fn test_drop_row_empty_board() {
+        let board = Board::new(10, 20);
+
+        // Flat I sits in row 0 of its box; vertical I spans four rows
+        assert_eq!(
+            board.drop_row(&Tetrimino::from_spec(TetriminoType::I, 3, 0, 0)),
+            19
+        );
+        assert_eq!(
+            board.drop_row(&Tetrimino::from_spec(TetriminoType::I, 3, 0, 1)),
+            16
+        );
+        assert_eq!(
+            board.drop_row(&Tetrimino::from_spec(TetriminoType::O, 0, -2, 0)),
+            18
+        );
+    }
+
+    #[test]
+    fn test_drop_row_over_flat_stack() {
+        let mut board = Board::new(10, 20);
+        board.fill_region(0, 17, 9, 19, Some(TetriminoType::J));
+
+        assert_eq!(
+            board.drop_row(&Tetrimino::from_spec(TetriminoType::T, 4, 0, 0)),
+            15
+        );
+        // Already resting on the stack
+        assert_eq!(
+            board.drop_row(&Tetrimino::from_spec(TetriminoType::T, 4, 15, 0)),
+            15
+        );
+    }
+
+    #[test]
+    fn test_drop_row_stops_under_overhang() {
+        let mut board = Board::new(10, 20);
+        // A ledge at row 10 over x=0..2, with open space below it
+        board.fill_region(0, 10, 1, 10, Some(TetriminoType::L));
+
+        // An O above the ledge lands on it, not on the floor beneath
+        assert_eq!(
+            board.drop_row(&Tetrimino::from_spec(TetriminoType::O, 0, 0, 0)),
+            8
+        );
+        // Half over the ledge is still caught by it
+        assert_eq!(
+            board.drop_row(&Tetrimino::from_spec(TetriminoType::O, 1, 0, 0)),
+            8
+        );
+        // Beside it falls to the floor
+        assert_eq!(
+            board.drop_row(&Tetrimino::from_spec(TetriminoType::O, 2, 0, 0)),
+            18
+        );
+        // Already under the ledge, it falls to the floor as well
+        assert_eq!(
+            board.drop_row(&Tetrimino::from_spec(TetriminoType::O, 0, 11, 0)),
+            18
+        );
+    }
+
+    #[test]
+    fn test_is_cell_free() {
+        let mut board = Board::new(10, 20);
+        board.cells[19][4] = Some(TetriminoType::Z);
+
+        assert!(board.is_cell_free(0, 0));
+        assert!(board.is_cell_free(9, -3));
+        assert!(!board.is_cell_free(4, 19));
+        assert!(!board.is_cell_free(-1, 5));
+        assert!(!board.is_cell_free(10, 5));
+        assert!(!board.is_cell_free(3, 20));
+    }
+
+    #[test]
+    fn test_count_holes() {
+        let mut board = Board::new(10, 20);
+        assert_eq!(board.count_holes(), 0);
+
+        // A roof over two empty cells in column 3, and a solid column 5
+        board.cells[17][3] = Some(TetriminoType::I);
+        for y in 17..20 {
+            board.cells[y][5] = Some(TetriminoType::I);
+        }
+        assert_eq!(board.count_holes(), 2);
+        assert_eq!(board.hole_positions(), vec![(3, 18), (3, 19)]);
+    }
+
+    #[test]
+    fn test_new_holes_since() {
+        let mut before = Board::new(10, 20);
+        // An existing hole under column 0
+        before.cells[18][0] = Some(TetriminoType::O);
+
+        let mut after = before.clone();
+        // An overhang makes a new hole at x=1; x=2 is filled underneath
+        for x in 1..3 {
+            after.cells[18][x] = Some(TetriminoType::L);
+        }
+        after.cells[19][2] = Some(TetriminoType::L);
+
+        assert_eq!(after.new_holes_since(&before), vec![(1, 19)]);
+        assert!(before.new_holes_since(&before).is_empty());
+    }
+
+    #[test]
+    fn test_set_cell_in_bounds() {
+        let mut board = Board::new(10, 20);
+
+        assert_eq!(board.set_cell(9, 19, Some(TetriminoType::S)), Ok(()));
+        assert_eq!(board.get_cell(9, 19), Some(TetriminoType::S));
+
+        assert_eq!(board.set_cell(9, 19, None), Ok(()));
+        assert_eq!(board.get_cell(9, 19), None);
+    }
+
+    #[test]
+    fn test_set_cell_out_of_bounds() {
+        let mut board = Board::new(10, 20);
+
+        assert_eq!(
+            board.set_cell(10, 0, Some(TetriminoType::I)),
+            Err(OutOfBounds { x: 10, y: 0 })
+        );
+        assert_eq!(
+            board.set_cell(0, 20, Some(TetriminoType::I)),
+            Err(OutOfBounds { x: 0, y: 20 })
+        );
+        assert!(board.cells().iter().flatten().all(Option::is_none));
+    }
+
     #[test]
     fn test_board_dimensions_consistency() {
         let board = Board::new(10, 20);
@@ -492,6 +1304,65 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_with_piece_locked_matches_manual_placement() {
+        let board = board_from_rows(&["....", "....", "#...", "##.#"]);
+        let mut piece = Tetrimino::from_spec(TetriminoType::O, 1, 0, 0);
+        piece.y = board.drop_row(&piece);
+
+        let (after, lines) = board.with_piece_locked(&piece);
+        let mut manual = board.clone();
+        manual.lock_tetromino(&piece);
+
+        assert_eq!(lines, 0);
+        assert_eq!(after.cells(), manual.cells());
+        assert_eq!(after.count_holes(), manual.count_holes());
+        assert_eq!(after.column_heights(), manual.column_heights());
+        assert_eq!(board.count_holes(), 0);
+    }
+
+    #[test]
+    fn test_with_piece_locked_reports_cleared_lines() {
+        let board = board_from_rows(&["....", "....", "#.##", "#.##"]);
+        let mut piece = Tetrimino::from_spec(TetriminoType::I, -1, 0, 1);
+        piece.y = board.drop_row(&piece);
+
+        let (after, lines) = board.with_piece_locked(&piece);
+
+        assert_eq!(lines, 2);
+        assert_eq!(after.column_heights(), vec![0, 2, 0, 0]);
+        assert_eq!(after.count_holes(), 0);
+        assert!(board.get_full_lines().is_empty());
+    }
+
+    #[test]
+    fn test_checksum_matches_for_identical_boards() {
+        let rows = ["....", ".T..", "TTT.", "IIII"];
+        assert_eq!(
+            board_from_rows(&rows).checksum(),
+            board_from_rows(&rows).checksum()
+        );
+        assert_eq!(Board::new(10, 20).checksum(), Board::new(10, 20).checksum());
+    }
+
+    #[test]
+    fn test_checksum_changes_with_a_single_cell() {
+        let board = board_from_rows(&["....", ".T..", "TTT.", "IIII"]);
+
+        let mut filled = board.clone();
+        filled.set_cell(3, 2, Some(TetriminoType::T)).unwrap();
+        assert_ne!(filled.checksum(), board.checksum());
+
+        let mut recolored = board.clone();
+        recolored.set_cell(1, 1, Some(TetriminoType::S)).unwrap();
+        assert_ne!(recolored.checksum(), board.checksum());
+    }
+
+    #[test]
+    fn test_checksum_depends_on_board_size() {
+        assert_ne!(Board::new(10, 20).checksum(), Board::new(20, 10).checksum());
+    }
+
     #[test]
     fn test_clear_lines_skips_non_full_lines() {
         let mut board = Board::new(10, 20);
@@ -0,0 +1,172 @@
+use crate::board::Board;
+use crate::game_state::GameState;
+use crate::tetrimino::{Tetrimino, TetriminoType};
+
+/// Heuristic weights, after the well-known four-feature evaluation: reward cleared
+/// lines, punish stack height, holes, and uneven columns.
+const LINES_WEIGHT: f64 = 0.76;
+const HEIGHT_WEIGHT: f64 = 0.51;
+const HOLES_WEIGHT: f64 = 0.36;
+const BUMPINESS_WEIGHT: f64 = 0.18;
+
+/// Where the bot wants a piece: its column and rotation before a hard drop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub x: i32,
+    pub rotation: usize,
+}
+
+/// Picks the hard-drop placement for `kind` that leaves `board` in the best shape,
+/// trying every rotation and column from `spawn_y`. `None` if nothing fits.
+pub fn choose_placement(board: &Board, kind: TetriminoType, spawn_y: i32) -> Option<Placement> {
+    let mut best: Option<(f64, Placement)> = None;
+
+    for rotation in 0..4 {
+        for x in -2..board.get_width() as i32 {
+            let mut piece = Tetrimino {
+                kind,
+                x,
+                y: spawn_y,
+                rotation,
+            };
+            if !board.is_valid_position(&piece) {
+                continue;
+            }
+            piece.y = board.drop_row(&piece);
+
+            let score = evaluate(board, &piece);
+            // Strictly better only, so ties keep the first (leftmost, least rotated) option
+            if best.is_none_or(|(best_score, _)| score > best_score) {
+                best = Some((score, Placement { x, rotation }));
+            }
+        }
+    }
+
+    best.map(|(_, placement)| placement)
+}
+
+/// Places the current piece where `choose_placement` wants it, locks it, and resolves
+/// any line clear at once so the next piece is ready. Tops out if nothing fits.
+pub fn play_move(state: &mut GameState) {
+    let Some(piece) = state.current_piece else {
+        return;
+    };
+    let Some(placement) = choose_placement(&state.board, piece.kind, piece.y) else {
+        state.end_game();
+        return;
+    };
+
+    state.current_piece = Some(Tetrimino {
+        x: placement.x,
+        rotation: placement.rotation,
+        ..piece
+    });
+    while state.move_piece(0, 1) {}
+    state.lock_current_piece();
+    // Cascade gravity can chain several clear steps
+    while state.is_line_clear_pending() {
+        state.complete_line_clear();
+    }
+}
+
+/// Scores the board after locking `piece` and clearing any full lines.
+fn evaluate(board: &Board, piece: &Tetrimino) -> f64 {
+    let (after, lines) = board.with_piece_locked(piece);
+
+    let aggregate_height: usize = after.column_heights().iter().sum();
+
+    LINES_WEIGHT * lines as f64
+        - HEIGHT_WEIGHT * aggregate_height as f64
+        - HOLES_WEIGHT * after.count_holes() as f64
+        - BUMPINESS_WEIGHT * after.bumpiness() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a board whose columns are filled solid up to the given heights.
+    fn board_with_heights(heights: &[usize]) -> Board {
+        let mut board = Board::new(heights.len(), 20);
+        for (x, &height) in heights.iter().enumerate() {
+            for y in 20 - height..20 {
+                board.set_cell(x, y, Some(TetriminoType::J)).unwrap();
+            }
+        }
+        board
+    }
+
+    fn blocks_after(board: &Board, kind: TetriminoType, placement: Placement) -> Board {
+        let mut piece = Tetrimino {
+            kind,
+            x: placement.x,
+            y: 0,
+            rotation: placement.rotation,
+        };
+        piece.y = board.drop_row(&piece);
+        let mut after = board.clone();
+        after.lock_tetromino(&piece);
+        after
+    }
+
+    #[test]
+    fn test_o_piece_takes_the_only_flat_spot() {
+        let board = board_with_heights(&[0, 0, 1, 2, 1, 2, 1, 2, 1, 2]);
+
+        let placement = choose_placement(&board, TetriminoType::O, 0).unwrap();
+
+        assert_eq!(placement.x, 0);
+        assert_eq!(
+            blocks_after(&board, TetriminoType::O, placement).count_holes(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_avoids_holes_when_possible() {
+        let board = board_with_heights(&[2, 2, 1, 2, 2, 2, 2, 2, 2, 2]);
+
+        for kind in [TetriminoType::T, TetriminoType::L, TetriminoType::S] {
+            let placement = choose_placement(&board, kind, 0).unwrap();
+            assert_eq!(
+                blocks_after(&board, kind, placement).count_holes(),
+                0,
+                "{:?} at {:?}",
+                kind,
+                placement
+            );
+        }
+    }
+
+    #[test]
+    fn test_i_piece_fills_well_for_a_tetris() {
+        let board = board_with_heights(&[4, 4, 4, 4, 4, 4, 4, 4, 4, 0]);
+
+        let placement = choose_placement(&board, TetriminoType::I, 0).unwrap();
+        let mut after = blocks_after(&board, TetriminoType::I, placement);
+
+        assert_eq!(after.clear_lines(), 4);
+    }
+
+    #[test]
+    fn test_play_move_survives_a_seeded_game() {
+        let mut state = GameState::new(crate::config::GameConfig {
+            seed: Some(7),
+            ..crate::config::GameConfig::default()
+        });
+        state.spawn_piece();
+
+        for _ in 0..100 {
+            play_move(&mut state);
+        }
+
+        assert!(!state.is_game_over());
+        assert!(state.lines_cleared > 0);
+    }
+
+    #[test]
+    fn test_no_placement_on_blocked_board() {
+        let board = board_with_heights(&[20; 10]);
+        assert_eq!(choose_placement(&board, TetriminoType::T, 0), None);
+    }
+}
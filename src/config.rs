@@ -1,9 +1,216 @@
+use crate::tetrimino::TetriminoType;
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::{fmt, fs, io};
+
+/// Config schema version written by this build. Bump it alongside a new step in
+/// `migrate` whenever a change needs more than new fields falling back to defaults.
+pub const CONFIG_VERSION: u32 = 2;
+
+/// Smallest board side a config may ask for; every piece has to fit across it.
+const MIN_BOARD_SIZE: usize = 4;
+
+/// Why a config file couldn't be loaded or saved.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file couldn't be read or written.
+    Io(io::Error),
+    /// The file isn't valid config JSON.
+    Parse(serde_json::Error),
+    /// The file parsed but holds settings the game can't run with.
+    Validation(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "config file error: {}", e),
+            ConfigError::Parse(e) => write!(f, "invalid config: {}", e),
+            ConfigError::Validation(reason) => write!(f, "invalid config: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(e) => Some(e),
+            ConfigError::Parse(e) => Some(e),
+            ConfigError::Validation(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ConfigError {
+    fn from(e: serde_json::Error) -> Self {
+        ConfigError::Parse(e)
+    }
+}
+
+/// Glyph set used for the playfield border.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BorderStyle {
+    Rounded,
+    #[default]
+    Square,
+    Double,
+    None,
+}
+
+/// How the ghost (landing preview) piece is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GhostStyle {
+    /// The piece's solid block, dimmed.
+    #[default]
+    Dimmed,
+    /// A shaded block that stands out against a busy stack.
+    Outline,
+    /// Bracket glyphs that leave the cell mostly empty.
+    Hollow,
+}
+
+/// How blocks above a cleared line fall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ClearGravity {
+    /// Everything above a cleared row shifts down by one row.
+    #[default]
+    Naive,
+    /// Each connected group of blocks falls on its own, which can chain further clears.
+    Cascade,
+}
+
+/// Which rotation rules pieces follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RotationSystem {
+    /// Guideline Super Rotation System: pieces spawn flat side up and kick in every
+    /// direction, including up off the floor.
+    #[default]
+    Srs,
+    /// Arika Rotation System from TGM: T, J and L spawn flat side down, kicks only
+    /// try one cell right then left, the I never kicks, and there are no floor kicks.
+    Ars,
+}
+
+/// Strategy for generating the piece sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Randomizer {
+    /// Guideline 7-bag: every run of seven pieces contains each type once.
+    #[default]
+    SevenBag,
+    /// Uniform random with a single NES-style re-roll on an immediate repeat.
+    TrueRandom,
+    /// TGM-style: re-roll up to four times to avoid the last four pieces dealt.
+    TgmHistory,
+    /// Shuffled bags of the pieces listed in `custom_bag`.
+    Custom,
+}
+
+/// How the fixed-goal line requirement changes as the level rises.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum LevelGrowth {
+    /// Every level needs `lines_per_level` lines.
+    #[default]
+    None,
+    /// Each level needs this many more lines than the one before.
+    Add(u32),
+    /// Each level needs this many times the lines of the one before, rounded.
+    Multiply(f64),
+}
+
+impl LevelGrowth {
+    /// Lines needed to advance past `level`, given the level 1 requirement `base`.
+    pub fn lines_for_level(self, base: u32, level: u32) -> u32 {
+        let steps = level.saturating_sub(1);
+        match self {
+            LevelGrowth::None => base,
+            LevelGrowth::Add(extra) => base.saturating_add(extra.saturating_mul(steps)),
+            LevelGrowth::Multiply(factor) => {
+                let lines = base as f64 * factor.powi(steps.min(i32::MAX as u32) as i32);
+                (lines.round() as u32).max(1)
+            }
+        }
+    }
+}
+
+/// Rank titles from lowest to highest. `rank_thresholds` sets the bar for each one
+/// after the first.
+pub const RANK_TITLES: [&str; 5] = ["Novice", "Apprentice", "Skilled", "Expert", "Master"];
+
+/// What it takes to reach a rank: this score or this many lines, whichever comes first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RankThreshold {
+    pub score: u64,
+    pub lines: u32,
+}
+
+impl RankThreshold {
+    pub const fn new(score: u64, lines: u32) -> Self {
+        Self { score, lines }
+    }
+
+    fn reached(self, score: u64, lines: u32) -> bool {
+        score >= self.score || lines >= self.lines
+    }
+}
+
+/// Point values used by scoring, before the level multiplier is applied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoreConfig {
+    pub single: u64,
+    pub double: u64,
+    pub triple: u64,
+    pub tetris: u64,
+    /// Replaces the line clear value for a full T-Spin.
+    pub tspin_single_bonus: u64,
+    pub tspin_double_bonus: u64,
+    pub tspin_triple_bonus: u64,
+    pub tspin_quad_bonus: u64,
+    /// T-Spin Minis replace the line clear value with their own, smaller table.
+    pub mini: u64,
+    pub mini_single: u64,
+    pub mini_double: u64,
+    /// Points per consecutive line-clearing piece in a combo.
+    pub combo_step: u64,
+    /// Points per row a sonic drop moves the piece, not scaled by level.
+    pub soft_drop: u64,
+    /// Scales the line clear and T-Spin value of a back-to-back Tetris or T-Spin.
+    pub back_to_back_multiplier: f64,
+}
+
+impl Default for ScoreConfig {
+    fn default() -> Self {
+        Self {
+            single: 100,
+            double: 300,
+            triple: 500,
+            tetris: 800,
+            tspin_single_bonus: 800,
+            tspin_double_bonus: 1200,
+            tspin_triple_bonus: 1600,
+            tspin_quad_bonus: 2000,
+            mini: 100,
+            mini_single: 200,
+            mini_double: 400,
+            combo_step: 50,
+            soft_drop: 1,
+            back_to_back_multiplier: 1.5,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GameConfig {
+    /// Schema version the file was written with; files from before versioning count as 1.
+    pub version: u32,
     pub board_width: usize,
     pub board_height: usize,
     pub starting_level: u32,
@@ -15,11 +222,135 @@ pub struct GameConfig {
     pub preview_count: usize,
     pub das_delay: u64,  // Delayed Auto Shift in ms
     pub das_repeat: u64, // Auto-repeat rate in ms
+    pub show_grid: bool, // Draw empty cells as a faint dotted grid
+    pub border_style: BorderStyle,
+    pub ghost_style: GhostStyle,
+    pub clear_gravity: ClearGravity,
+    pub randomizer: Randomizer,
+    pub seed: Option<u64>, // Fixed RNG seed for reproducible games; random when unset
+    pub practice_mode: bool, // Allows picking the next piece with the number keys
+    pub spawn_kick: bool,  // Nudge a blocked spawn sideways before topping out
+    /// Speed up gravity while the board is mostly empty.
+    pub adaptive_gravity: bool,
+    /// How long a grounded piece can still be moved before it locks, in ms.
+    pub lock_delay_ms: u64,
+    /// Hard drop locks instantly; when false it lands the piece and starts lock delay.
+    pub hard_drop_locks: bool,
+    /// NES-style rotation: clockwise only, no wall kicks.
+    pub classic_rotation: bool,
+    /// Point values; defaults follow the guideline tables.
+    pub scoring: ScoreConfig,
+    /// Beginner assist: faintly show where the held piece would land if swapped in.
+    pub assist_hold_ghost: bool,
+    /// Show frame rate and frame times (min/avg/max) in the top-right corner.
+    pub show_fps: bool,
+    /// Top-outs allowed before game over; each extra life clears the top of the stack.
+    pub lives: u32,
+    /// Ascending cumulative line counts at which level 1, 2, 3, ... begin. When set, this
+    /// replaces the `lines_per_level`/variable goal formulas; levels stop at the end of the table.
+    pub level_thresholds: Vec<u32>,
+    /// Keep the level (and so the score multiplier) fixed at `starting_level`.
+    pub freeze_level: bool,
+    /// Show each column's stack height in a row above the board.
+    pub show_heights: bool,
+    /// Output device name to play through; the system default when unset or not found.
+    pub audio_device: Option<String>,
+    /// Scales both the music and sound effect volumes, from 0.0 to 1.0.
+    pub master_volume: f32,
+    /// Volume of the background music, from 0.0 to 1.0, before the master volume.
+    pub music_volume: f32,
+    /// Volume of the sound effects, from 0.0 to 1.0, before the master volume.
+    pub sfx_volume: f32,
+    /// Speed up the music as the level rises.
+    pub music_follows_level: bool,
+    /// Music to switch to once the level reaches each key; the bundled theme plays below
+    /// the lowest key.
+    pub level_music: BTreeMap<u32, PathBuf>,
+    /// Pieces dealt first, in order, before the randomizer takes over.
+    pub initial_queue: Vec<TetriminoType>,
+    /// Soft drop repeat rate in ms while Down is held, separate from `das_repeat`.
+    pub soft_drop_repeat: u64,
+    /// Show only the board; stats appear briefly on pause and level-up.
+    pub minimal_ui: bool,
+    /// Copies of each piece type shuffled into one bag; 2 gives a 14-piece double bag.
+    pub bag_repeats: u32,
+    /// How the fixed goal grows from one level to the next; constant by default.
+    pub lines_per_level_growth: LevelGrowth,
+    /// Allow N to toggle faint landing previews for the next pieces in the queue.
+    pub planning_mode: bool,
+    /// Play a short chime along with the level-up banner.
+    pub level_up_chime: bool,
+    /// Board row new pieces spawn at; negative values start them partly above the board.
+    pub spawn_row: i32,
+    /// Color locked blocks by row, cool at the bottom to warm at the top, instead of by piece.
+    pub height_gradient: bool,
+    /// Rows per second of fall speed gained for each second spent on a level, on top of the
+    /// level's base gravity; resets on level up. 0 disables the ramp.
+    pub gravity_ramp: f64,
+    /// Briefly flash cells that the last locked piece turned into holes.
+    pub highlight_holes: bool,
+    /// Beginner assist: when a rotation and all its kicks fail, shift the piece one cell away
+    /// from whatever blocks it and try once more.
+    pub rotation_assist: bool,
+    /// Times cleared rows flash before they disappear; each blink lasts 500 ms whatever the
+    /// number of lines. 0 clears them immediately.
+    pub clear_blinks: u32,
+    /// Target output latency; sizes the audio buffer to about this many ms. Smaller values
+    /// make sound effects feel tighter but may crackle. The system default buffer when unset.
+    pub audio_latency_ms: Option<u32>,
+    /// Score or lines needed for each rank title after Novice, in `RANK_TITLES` order.
+    pub rank_thresholds: [RankThreshold; 4],
+    /// Only show the ghost once the piece is within this many rows of landing; always when unset.
+    pub ghost_proximity: Option<usize>,
+    /// Garble the stack's colors once the level reaches `kill_screen_level`; play carries on.
+    pub kill_screen: bool,
+    /// Level at which `kill_screen` kicks in; 29 is the NES kill screen.
+    pub kill_screen_level: u32,
+    /// Wait out the DAS delay after the one-cell move a direction press makes, so a
+    /// tap moves exactly one cell. When off, auto-repeat starts right after the
+    /// press at the DAS repeat rate. A press always moves once either way.
+    pub das_initial_tap: bool,
+    /// Play a tone on line clears, pitched higher for bigger clears and distinct for T-Spins.
+    pub clear_tones: bool,
+    /// Highest combo count that earns a combo bonus; longer combos score as this.
+    /// `None` leaves the bonus uncapped. The combo shown in play is not capped.
+    pub max_combo: Option<u32>,
+    /// Reshuffle the opening 7-bag until the first piece is an I, J, L or T, never an
+    /// awkward S, Z or O. Has no effect when `initial_queue` sets the opener.
+    pub first_piece_guaranteed_good: bool,
+    /// When a hard drop would top out, the first press only flashes a warning; a second
+    /// press within a second commits it.
+    pub safe_hard_drop: bool,
+    /// Terminal columns per cell: 2 draws roughly square blocks, 1 narrow ones that fit
+    /// smaller terminals. Anything other than 1 is drawn as 2.
+    pub cell_width: u8,
+    /// Rotation rules: spawn orientations and wall kicks. `classic_rotation` still turns
+    /// kicks off entirely.
+    pub rotation_system: RotationSystem,
+    /// How long menus, pause and other idle screens wait for a key per check, in ms
+    /// (1-250). Longer waits use less CPU but redraw less often.
+    pub poll_timeout_ms: u64,
+    /// Entry delay (ARE) between a piece locking and the next one spawning, in ms.
+    pub spawn_delay_ms: u64,
+    /// Fade a newly spawned piece in from dim over its first 100ms.
+    pub spawn_fade_in: bool,
+    /// Blank lines between pieces in the NEXT panel; 0 packs them tight.
+    pub preview_spacing: u8,
+    /// Count line clear animations and spawn delays toward the game clock. When off,
+    /// Sprint and Ultra time only counts while a piece is in play.
+    pub clock_counts_delays: bool,
+    /// Pieces in each bag for the `Custom` randomizer; types may repeat or be left out.
+    pub custom_bag: Vec<TetriminoType>,
+    /// Initial Hold System: a hold pressed while no piece is in play (before the first
+    /// piece, or during a line clear or spawn delay) holds the next piece as it spawns.
+    /// When off, such a press does nothing.
+    pub initial_hold: bool,
 }
 
 impl Default for GameConfig {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             board_width: 10,
             board_height: 20,
             starting_level: 1,
@@ -31,20 +362,303 @@ impl Default for GameConfig {
             preview_count: 3,
             das_delay: 250,
             das_repeat: 50,
+            show_grid: false,
+            border_style: BorderStyle::default(),
+            ghost_style: GhostStyle::default(),
+            clear_gravity: ClearGravity::default(),
+            randomizer: Randomizer::default(),
+            seed: None,
+            practice_mode: false,
+            spawn_kick: false,
+            adaptive_gravity: false,
+            lock_delay_ms: 500,
+            hard_drop_locks: true,
+            classic_rotation: false,
+            scoring: ScoreConfig::default(),
+            assist_hold_ghost: false,
+            show_fps: false,
+            lives: 1,
+            level_thresholds: Vec::new(),
+            freeze_level: false,
+            show_heights: false,
+            audio_device: None,
+            master_volume: 1.0,
+            music_volume: 0.5,
+            sfx_volume: 1.0,
+            music_follows_level: false,
+            level_music: BTreeMap::new(),
+            initial_queue: Vec::new(),
+            soft_drop_repeat: 20,
+            minimal_ui: false,
+            bag_repeats: 1,
+            lines_per_level_growth: LevelGrowth::default(),
+            planning_mode: false,
+            level_up_chime: false,
+            spawn_row: 0,
+            height_gradient: false,
+            gravity_ramp: 0.0,
+            highlight_holes: false,
+            rotation_assist: false,
+            clear_blinks: 2,
+            audio_latency_ms: None,
+            rank_thresholds: [
+                RankThreshold::new(10_000, 20),
+                RankThreshold::new(50_000, 60),
+                RankThreshold::new(150_000, 120),
+                RankThreshold::new(400_000, 200),
+            ],
+            ghost_proximity: None,
+            kill_screen: false,
+            kill_screen_level: 29,
+            das_initial_tap: true,
+            clear_tones: false,
+            max_combo: None,
+            first_piece_guaranteed_good: false,
+            safe_hard_drop: false,
+            cell_width: 2,
+            rotation_system: RotationSystem::default(),
+            poll_timeout_ms: 16,
+            spawn_delay_ms: 0,
+            spawn_fade_in: false,
+            preview_spacing: 1,
+            clock_counts_delays: true,
+            custom_bag: Vec::new(),
+            initial_hold: false,
         }
     }
 }
 
 impl GameConfig {
-    pub fn load_from_file(path: &PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+    /// Loads a config file, upgrading it from older schema versions first.
+    pub fn load_from_file(path: &PathBuf) -> Result<Self, ConfigError> {
         let contents = fs::read_to_string(path)?;
-        let config = serde_json::from_str(&contents)?;
+        let mut value: serde_json::Value = serde_json::from_str(&contents)?;
+        migrate(&mut value);
+        let config: Self = serde_json::from_value(value)?;
+        config.validate()?;
         Ok(config)
     }
 
-    pub fn _save_to_file(&self, path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-        let contents = serde_json::to_string_pretty(self)?;
+    /// Rejects settings that parse but can't produce a playable game.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.board_width < MIN_BOARD_SIZE || self.board_height < MIN_BOARD_SIZE {
+            return Err(ConfigError::Validation(format!(
+                "board is {}x{}, but must be at least {}x{}",
+                self.board_width, self.board_height, MIN_BOARD_SIZE, MIN_BOARD_SIZE
+            )));
+        }
+        self.validate_piece_source()
+    }
+
+    /// Checks that the randomizer can always deal another piece.
+    pub fn validate_piece_source(&self) -> Result<(), ConfigError> {
+        if self.randomizer == Randomizer::Custom && self.custom_bag.is_empty() {
+            return Err(ConfigError::Validation(
+                "the custom randomizer needs at least one piece in custom_bag".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The highest rank title whose threshold `score` or `lines` reaches.
+    pub fn rank_for(&self, score: u64, lines: u32) -> &'static str {
+        let reached = self
+            .rank_thresholds
+            .iter()
+            .take_while(|threshold| threshold.reached(score, lines))
+            .count();
+        RANK_TITLES[reached]
+    }
+
+    /// Saves the config, always stamped with the current `CONFIG_VERSION`.
+    pub fn save_to_file(&self, path: &PathBuf) -> Result<(), ConfigError> {
+        let config = Self {
+            version: CONFIG_VERSION,
+            ..self.clone()
+        };
+        let contents = serde_json::to_string_pretty(&config)?;
         fs::write(path, contents)?;
         Ok(())
     }
 }
+
+/// Upgrades raw config JSON to `CONFIG_VERSION` in place, one version at a time.
+/// Fields added since the file was written are left out and so take their defaults.
+/// A file from a newer build is loaded as far as this build understands it.
+fn migrate(value: &mut serde_json::Value) {
+    let Some(fields) = value.as_object_mut() else {
+        // Not an object; let deserialization report it
+        return;
+    };
+    let version = fields
+        .get("version")
+        .and_then(serde_json::Value::as_u64)
+        .unwrap_or(1);
+
+    if version > CONFIG_VERSION as u64 {
+        eprintln!(
+            "Warning: config version {} is newer than supported version {}; unknown settings are ignored",
+            version, CONFIG_VERSION
+        );
+        return;
+    }
+
+    // v1 -> v2: v1 predates the version field. Nothing was renamed or removed,
+    // so the fields added since simply take their defaults. Later steps that
+    // rename or reshape fields go here, guarded by `if version < N`.
+
+    fields.insert("version".to_string(), CONFIG_VERSION.into());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("tetris-rs-{}-{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_rank_for_score_boundaries() {
+        let config = GameConfig::default();
+        assert_eq!(config.rank_for(0, 0), "Novice");
+        assert_eq!(config.rank_for(9_999, 0), "Novice");
+        assert_eq!(config.rank_for(10_000, 0), "Apprentice");
+        assert_eq!(config.rank_for(149_999, 0), "Skilled");
+        assert_eq!(config.rank_for(150_000, 0), "Expert");
+        assert_eq!(config.rank_for(400_000, 0), "Master");
+        assert_eq!(config.rank_for(u64::MAX, 0), "Master");
+    }
+
+    #[test]
+    fn test_rank_for_lines_count_too() {
+        let config = GameConfig::default();
+        assert_eq!(config.rank_for(0, 19), "Novice");
+        assert_eq!(config.rank_for(0, 20), "Apprentice");
+        assert_eq!(config.rank_for(0, 60), "Skilled");
+        // Whichever of score and lines reaches further counts
+        assert_eq!(config.rank_for(60_000, 130), "Expert");
+        assert_eq!(config.rank_for(500_000, 5), "Master");
+    }
+
+    #[test]
+    fn test_rank_for_custom_thresholds() {
+        let config = GameConfig {
+            rank_thresholds: [
+                RankThreshold::new(100, 1),
+                RankThreshold::new(200, 2),
+                RankThreshold::new(300, 3),
+                RankThreshold::new(400, 4),
+            ],
+            ..GameConfig::default()
+        };
+        assert_eq!(config.rank_for(99, 0), "Novice");
+        assert_eq!(config.rank_for(250, 0), "Skilled");
+        assert_eq!(config.rank_for(0, 4), "Master");
+    }
+
+    #[test]
+    fn test_migrate_v1_fills_defaults_and_bumps_version() {
+        // A v1 file: no version field and only the settings that existed then
+        let mut value = serde_json::json!({
+            "board_width": 12,
+            "starting_level": 5,
+            "enable_sound": false,
+        });
+        migrate(&mut value);
+        let config: GameConfig = serde_json::from_value(value).unwrap();
+
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.board_width, 12);
+        assert_eq!(config.starting_level, 5);
+        assert!(!config.enable_sound);
+        assert_eq!(config.lock_delay_ms, 500);
+        assert_eq!(config.bag_repeats, 1);
+        assert_eq!(config.spawn_row, 0);
+    }
+
+    #[test]
+    fn test_migrate_future_version_still_loads() {
+        let mut value = serde_json::json!({
+            "version": CONFIG_VERSION + 1,
+            "board_height": 24,
+            "some_future_setting": true,
+        });
+        migrate(&mut value);
+        let config: GameConfig = serde_json::from_value(value).unwrap();
+
+        assert_eq!(config.version, CONFIG_VERSION + 1);
+        assert_eq!(config.board_height, 24);
+    }
+
+    #[test]
+    fn test_load_missing_file_is_io_error() {
+        let path = temp_path("missing");
+        let _ = fs::remove_file(&path);
+
+        match GameConfig::load_from_file(&path) {
+            Err(ConfigError::Io(e)) => assert_eq!(e.kind(), io::ErrorKind::NotFound),
+            other => panic!("expected an Io error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_load_invalid_json_is_parse_error() {
+        let path = temp_path("invalid-json");
+        fs::write(&path, "{ \"board_width\": ").unwrap();
+        let result = GameConfig::load_from_file(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(ConfigError::Parse(_))));
+    }
+
+    #[test]
+    fn test_load_tiny_board_is_validation_error() {
+        let path = temp_path("tiny-board");
+        fs::write(&path, r#"{"board_width": 2}"#).unwrap();
+        let result = GameConfig::load_from_file(&path);
+        fs::remove_file(&path).unwrap();
+
+        match result {
+            Err(ConfigError::Validation(reason)) => assert!(reason.contains("2x20")),
+            other => panic!("expected a Validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_custom_bag() {
+        let config = GameConfig {
+            randomizer: Randomizer::Custom,
+            ..GameConfig::default()
+        };
+        assert!(matches!(config.validate(), Err(ConfigError::Validation(_))));
+
+        let config = GameConfig {
+            custom_bag: vec![TetriminoType::I],
+            ..config
+        };
+        assert!(config.validate().is_ok());
+        // The bag only matters to the custom randomizer
+        assert!(GameConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_v1_file_round_trips_as_current_version() {
+        let path = temp_path("v1-round-trip");
+        fs::write(&path, r#"{"board_width": 8, "preview_count": 5}"#).unwrap();
+
+        let loaded = GameConfig::load_from_file(&path).unwrap();
+        assert_eq!(loaded.version, CONFIG_VERSION);
+        loaded.save_to_file(&path).unwrap();
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["version"], CONFIG_VERSION);
+
+        let reloaded = GameConfig::load_from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(reloaded.board_width, 8);
+        assert_eq!(reloaded.preview_count, 5);
+        assert_eq!(reloaded.version, CONFIG_VERSION);
+    }
+}
@@ -1,90 +1,272 @@
-use crate::audio::AudioPlayer;
+use crate::audio::{AudioPlayer, clear_tone, playback_rate_for_level};
+use crate::bot;
 use crate::config::GameConfig;
-use crate::game_state::GameState;
-use crate::input::{InputAction, InputHandler};
-use crate::ui::Renderer;
+use crate::game_state::{GameEvent, GameMode, GameState};
+use crate::input::{
+    AutoRepeat, GameOverAction, InputAction, InputEvent, InputHandler, MenuInput, ReplayInput,
+};
+use crate::replay::ReplayViewer;
+use crate::settings::SettingsMenu;
+use crate::stats::LifetimeStats;
+use crate::title::{TitleChoice, TitleMenu};
+use crate::ui::{FrameStats, Renderer};
 use anyhow::Result;
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
+    event::{
+        DisableMouseCapture, EnableMouseCapture, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
 };
+use std::collections::BTreeMap;
 use std::io::{stdout, Write};
 use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
+/// Fraction of the level's gravity interval used on an empty board with adaptive gravity.
+const ADAPTIVE_FASTEST_FACTOR: f64 = 0.5;
+/// Board coverage at which adaptive gravity returns to the level's base speed.
+const ADAPTIVE_FULL_SPEED_COVERAGE: f64 = 0.5;
+/// Shortest gravity interval the ramp can reach: 20 rows per 60 Hz frame.
+const TWENTY_G_INTERVAL: Duration = Duration::from_micros(833);
+/// Number of recent frames the FPS overlay summarizes.
+const FRAME_STATS_WINDOW: usize = 60;
+/// Time between the bot's placements in the attract-mode demo.
+const ATTRACT_MOVE_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Where a game is in its lifecycle. A new game waits for a key press before its
+/// clock and gravity start, so one launched in a background terminal doesn't play
+/// out unseen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlayState {
+    WaitingToStart,
+    Playing,
+}
+
+impl PlayState {
+    /// The state after a frame in which a key was, or wasn't, pressed.
+    fn after_input(self, key_pressed: bool) -> Self {
+        match self {
+            PlayState::WaitingToStart if key_pressed => PlayState::Playing,
+            state => state,
+        }
+    }
+}
+
+/// Callback invoked for each `GameEvent` the game emits.
+pub type EventHandler = Box<dyn FnMut(&GameEvent)>;
+
 pub struct Game {
     state: GameState,
     renderer: Renderer,
     input: InputHandler,
     audio: AudioPlayer,
+    event_handler: Option<EventHandler>,
+    export_path: Option<PathBuf>,
+    result_path: Option<PathBuf>,
+    frame_stats: FrameStats,
+    current_track: Option<PathBuf>,
+    horizontal_repeat: AutoRepeat,
+    soft_drop_repeat: AutoRepeat,
+    held_direction: Option<InputAction>,
+    config_path: Option<PathBuf>,
+    lifetime_stats: LifetimeStats,
+    stats_path: Option<PathBuf>,
+    attract_mode: bool,
+    play_state: PlayState,
 }
 
 impl Game {
     pub fn new(config: GameConfig) -> Result<Self> {
         let renderer = Renderer::new()?;
         let audio = if config.enable_sound {
-            AudioPlayer::new()
+            open_audio(&config)
         } else {
             AudioPlayer::no_sound()
         };
+        let horizontal_repeat = AutoRepeat::new(
+            Duration::from_millis(config.das_delay),
+            Duration::from_millis(config.das_repeat),
+        )
+        .with_initial_tap(config.das_initial_tap);
+        let soft_drop_repeat = AutoRepeat::new(
+            Duration::from_millis(config.soft_drop_repeat),
+            Duration::from_millis(config.soft_drop_repeat),
+        );
+        let input = InputHandler::new().with_poll_timeout(config.poll_timeout_ms);
         let state = GameState::new(config);
-        let input = InputHandler::new();
 
         Ok(Self {
             state,
             renderer,
             input,
             audio,
+            event_handler: None,
+            export_path: None,
+            result_path: None,
+            frame_stats: FrameStats::new(FRAME_STATS_WINDOW),
+            current_track: None,
+            horizontal_repeat,
+            soft_drop_repeat,
+            held_direction: None,
+            config_path: None,
+            lifetime_stats: LifetimeStats::default(),
+            stats_path: None,
+            attract_mode: false,
+            play_state: PlayState::WaitingToStart,
         })
     }
 
+    /// Writes `GameState::export_summary` to `path` when the game ends.
+    pub fn set_export_path(&mut self, path: PathBuf) {
+        self.export_path = Some(path);
+    }
+
+    /// Writes `GameState::result` as JSON to `path` when the game ends.
+    pub fn set_result_path(&mut self, path: PathBuf) {
+        self.result_path = Some(path);
+    }
+
+    /// Shows a bot-played demo until a key is pressed, before the real game starts.
+    pub fn set_attract_mode(&mut self, enabled: bool) {
+        self.attract_mode = enabled;
+    }
+
+    /// Where changes made on the settings screen are saved when the game exits.
+    pub fn set_config_path(&mut self, path: PathBuf) {
+        self.config_path = Some(path);
+    }
+
+    /// Lifetime totals to show on the title screen, updated and saved to `path`
+    /// after every game.
+    pub fn set_lifetime_stats(&mut self, stats: LifetimeStats, path: PathBuf) {
+        self.lifetime_stats = stats;
+        self.stats_path = Some(path);
+    }
+
+    /// Registers a callback invoked once per frame for each queued `GameEvent`.
+    pub fn set_event_handler(&mut self, handler: EventHandler) {
+        self.event_handler = Some(handler);
+    }
+
     pub fn run(&mut self) -> Result<()> {
-        let _cleanup = setup_terminal();
+        let cleanup = setup_terminal();
+        // Held-key repeat needs release events; otherwise rely on the terminal's own
+        let auto_repeat = cleanup.keyboard_enhanced;
 
         self.start_music();
 
-        self.state.spawn_piece();
+        if self.attract_mode {
+            self.run_attract()?;
+        }
 
+        while let Some(mode) = self.run_title()? {
+            self.start_game(mode);
+            self.play(auto_repeat)?;
+        }
+
+        Ok(())
+    }
+
+    /// Runs one game until the player quits from the game-over screen; restarting
+    /// from there plays the same mode again.
+    fn play(&mut self, auto_repeat: bool) -> Result<()> {
         let mut last_update = Instant::now();
         let mut last_gravity = Instant::now();
-        let gravity_duration = self.get_gravity_duration();
         let frame_duration = Duration::from_millis(16);
 
         loop {
             let now = Instant::now();
             let frame_time = now.duration_since(last_update);
 
-            if let Some(action) = self.input.poll_input() {
-                self.handle_input(action)?;
+            if self.play_state == PlayState::WaitingToStart {
+                self.renderer.render_ready(&self.state)?;
+                // The key that starts the game isn't played as a move
+                self.play_state = self.play_state.after_input(self.input.wait_key_press());
+                if self.play_state == PlayState::Playing {
+                    let now = Instant::now();
+                    self.state.start_clock(now);
+                    last_gravity = now;
+                }
+                last_update = now;
+                continue;
             }
 
-            if !self.state.show_help {
-                if self.state.pending_line_clear {
-                    if !self.state.is_line_clear_animation_active() {
-                        self.state.complete_line_clear();
-                    }
-                } else if now.duration_since(last_gravity) >= gravity_duration {
-                    if !self.state.move_piece(0, 1) {
-                        self.state.lock_current_piece();
-                    }
-                    last_gravity = now;
+            self.frame_stats.record(frame_time);
+            let gravity_duration = self.get_gravity_duration();
+
+            let paused_before = self.state.paused_time(now);
+            if auto_repeat {
+                if let Some(event) = self.input.poll_event() {
+                    self.handle_input_event(event, now)?;
                 }
+                self.apply_auto_repeat(now);
+            } else if let Some(action) = self.input.poll_input() {
+                self.handle_input(action)?;
+            }
+            // Time spent paused doesn't count toward the next gravity step
+            last_gravity += self
+                .state
+                .paused_time(Instant::now())
+                .saturating_sub(paused_before);
+
+            if self
+                .state
+                .tick(now, now.duration_since(last_gravity) >= gravity_duration)
+            {
+                last_gravity = now;
             }
 
-            self.renderer.render(&self.state)?;
+            self.dispatch_events();
+            self.audio.poll_health();
+
+            let show_fps =
+                self.state.config.show_fps || (cfg!(debug_assertions) && self.state.show_debug);
+            self.renderer
+                .render(&self.state, show_fps.then_some(&self.frame_stats))?;
 
-            if self.state.game_over {
+            if self.state.is_game_over() {
                 self.renderer.render_game_over(&self.state)?;
 
                 self.audio.stop();
 
+                if let Some(ref path) = self.export_path {
+                    std::fs::write(path, self.state.export_summary())?;
+                }
+
+                if let Some(ref path) = self.result_path {
+                    std::fs::write(path, serde_json::to_string_pretty(&self.state.result())?)?;
+                }
+
+                self.lifetime_stats
+                    .update(self.state.score, self.state.lines_cleared);
+                if let Some(ref path) = self.stats_path {
+                    self.lifetime_stats.save(path)?;
+                }
+
                 stdout().flush()?;
 
-                while !self.input.has_input() {
-                    std::thread::sleep(Duration::from_millis(16));
+                let action = loop {
+                    match self.input.wait_game_over_action() {
+                        GameOverAction::Review => {
+                            self.run_replay_viewer()?;
+                            self.renderer.render_game_over(&self.state)?;
+                        }
+                        action => break action,
+                    }
+                };
+                match action {
+                    GameOverAction::Restart => self.start_game(self.state.mode),
+                    GameOverAction::Replay => self.begin(self.state.replay()),
+                    GameOverAction::Review | GameOverAction::Quit => break,
                 }
-                break;
+                last_update = Instant::now();
+                last_gravity = last_update;
+                continue;
             }
 
             if frame_time < frame_duration {
@@ -97,21 +279,223 @@ impl Game {
         Ok(())
     }
 
+    /// Lets the bot play a throwaway game, starting over whenever it tops out, until
+    /// a key is pressed.
+    fn run_attract(&mut self) -> Result<()> {
+        let demo_config = GameConfig {
+            seed: None,
+            ..self.state.config.clone()
+        };
+        let mut demo = GameState::new(demo_config.clone());
+        demo.spawn_piece();
+        let mut last_move = Instant::now();
+
+        while !self.input.wait_key_press() {
+            let now = Instant::now();
+            if now.duration_since(last_move) >= ATTRACT_MOVE_INTERVAL {
+                if demo.is_game_over() {
+                    demo = GameState::new(demo_config.clone());
+                    demo.spawn_piece();
+                } else {
+                    bot::play_move(&mut demo);
+                }
+                // The demo's events shouldn't reach integrations or the music
+                demo.drain_events();
+                last_move = now;
+            }
+
+            self.renderer.render_attract(&demo)?;
+        }
+        Ok(())
+    }
+
+    /// Steps through the game that just ended until the viewer is closed.
+    fn run_replay_viewer(&mut self) -> Result<()> {
+        let mut viewer = ReplayViewer::new(&self.state);
+
+        loop {
+            self.renderer.render_replay(&viewer)?;
+
+            match self.input.wait_replay_input() {
+                Some(ReplayInput::TogglePlay) => viewer.toggle_play(),
+                Some(ReplayInput::Step) => {
+                    viewer.step();
+                }
+                Some(ReplayInput::NextClear) => viewer.seek_next_clear(),
+                Some(ReplayInput::Close) => return Ok(()),
+                None => {}
+            }
+            viewer.advance(Instant::now());
+        }
+    }
+
+    /// Shows the title screen until a mode is picked, or `None` if the player quits.
+    fn run_title(&mut self) -> Result<Option<GameMode>> {
+        let mut menu = TitleMenu::new();
+
+        loop {
+            self.renderer.render_title(&menu, &self.lifetime_stats)?;
+
+            if let Some(input) = self.input.wait_menu_input() {
+                match menu.handle(input) {
+                    Some(TitleChoice::Start(mode)) => return Ok(Some(mode)),
+                    Some(TitleChoice::Quit) => return Ok(None),
+                    None => {}
+                }
+            }
+        }
+    }
+
+    /// Starts a fresh game of `mode` with the current config, keeping the audio and
+    /// terminal.
+    fn start_game(&mut self, mode: GameMode) {
+        self.begin(GameState::with_mode(self.state.config.clone(), mode));
+    }
+
+    /// Puts `state` in play from its first piece, resetting held keys and the music.
+    fn begin(&mut self, state: GameState) {
+        self.state = state;
+        self.state.spawn_piece();
+        self.play_state = PlayState::WaitingToStart;
+        self.horizontal_repeat.release();
+        self.soft_drop_repeat.release();
+        self.held_direction = None;
+
+        self.current_track = None;
+        self.audio.set_playback_rate(1.0);
+        self.start_music();
+    }
+
+    fn dispatch_events(&mut self) {
+        let events = self.state.drain_events();
+        let tspin = events
+            .iter()
+            .any(|event| matches!(event, GameEvent::TSpin { .. }));
+
+        for event in &events {
+            if let GameEvent::LinesCleared { lines, .. } = *event
+                && self.state.config.enable_sound
+                && self.state.config.clear_tones
+                && let Some(tone) = clear_tone(lines, tspin)
+            {
+                self.audio.play_tone(tone);
+            }
+            if let GameEvent::LevelUp { level } = *event {
+                if self.state.config.music_follows_level {
+                    self.audio.set_playback_rate(playback_rate_for_level(level));
+                }
+                self.play_track_for_level();
+                if self.state.config.enable_sound && self.state.config.level_up_chime {
+                    self.audio.play_chime();
+                }
+            }
+        }
+
+        if let Some(ref mut handler) = self.event_handler {
+            for event in &events {
+                handler(event);
+            }
+        }
+    }
+
     fn start_music(&mut self) {
-        let mut audio_path = PathBuf::from(env!("OUT_DIR"));
-        audio_path.push("tetris_theme.wav");
+        if self.play_track_for_level() && self.state.config.music_follows_level {
+            self.audio
+                .set_playback_rate(playback_rate_for_level(self.state.level));
+        }
+    }
 
-        if audio_path.exists() {
-            self.audio.play_background_music(audio_path);
-            self.audio.set_volume(0.5);
+    /// Starts the track configured for the current level unless it is already
+    /// playing. Returns whether that track is (now) playing.
+    fn play_track_for_level(&mut self) -> bool {
+        let track = track_for_level(&self.state.config.level_music, self.state.level)
+            .cloned()
+            .unwrap_or_else(|| {
+                let mut audio_path = PathBuf::from(env!("OUT_DIR"));
+                audio_path.push("tetris_theme.wav");
+                audio_path
+            });
+
+        if self.current_track.as_ref() == Some(&track) {
+            return true;
+        }
+        if !track.exists() {
+            return false;
         }
+
+        self.audio.play_background_music(track.clone());
+        self.current_track = Some(track);
+        true
     }
 
     fn get_gravity_duration(&self) -> Duration {
         let base_gravity_ms = 800;
         let level = self.state.level.max(1);
         let gravity_ms = (base_gravity_ms / (2_u32.pow((level - 1).min(10)))).max(50);
-        Duration::from_millis(gravity_ms as u64)
+        let base = Duration::from_millis(gravity_ms as u64);
+
+        let base = if self.state.config.adaptive_gravity {
+            adaptive_gravity(base, self.state.board_coverage())
+        } else {
+            base
+        };
+        ramped_gravity(
+            base,
+            self.state.config.gravity_ramp,
+            self.state.level_started_at.elapsed(),
+        )
+    }
+
+    /// Tracks held movement keys for auto-repeat, then handles presses as usual.
+    fn handle_input_event(&mut self, event: InputEvent, now: Instant) -> Result<()> {
+        match event {
+            InputEvent::Pressed(action) => {
+                match action {
+                    InputAction::MoveLeft | InputAction::MoveRight => {
+                        self.held_direction = Some(action);
+                        self.horizontal_repeat.press(now);
+                    }
+                    InputAction::MoveDown => {
+                        self.soft_drop_repeat.press(now);
+                    }
+                    _ => {}
+                }
+                self.handle_input(action)
+            }
+            InputEvent::Released(action) => {
+                match action {
+                    InputAction::MoveLeft | InputAction::MoveRight
+                        if self.held_direction == Some(action) =>
+                    {
+                        self.held_direction = None;
+                        self.horizontal_repeat.release();
+                    }
+                    InputAction::MoveDown => self.soft_drop_repeat.release(),
+                    _ => {}
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Moves the piece for any repeats due on held keys; horizontal keys use the DAS
+    /// timing and soft drop its own, usually faster, rate.
+    fn apply_auto_repeat(&mut self, now: Instant) {
+        let dx = match self.held_direction {
+            Some(InputAction::MoveLeft) => -1,
+            Some(InputAction::MoveRight) => 1,
+            _ => 0,
+        };
+        for _ in 0..self.horizontal_repeat.poll(now) {
+            if dx == 0 || !self.state.move_piece(dx, 0) {
+                break;
+            }
+        }
+        for _ in 0..self.soft_drop_repeat.poll(now) {
+            if !self.state.move_piece(0, 1) {
+                break;
+            }
+        }
     }
 
     fn handle_input(&mut self, action: InputAction) -> Result<()> {
@@ -125,8 +509,17 @@ impl Game {
             InputAction::MoveDown => {
                 self.state.move_piece(0, 1);
             }
+            InputAction::MoveToLeftWall => {
+                self.state.move_to_wall(-1);
+            }
+            InputAction::MoveToRightWall => {
+                self.state.move_to_wall(1);
+            }
             InputAction::HardDrop => {
-                self.state.hard_drop();
+                self.state.request_hard_drop(Instant::now());
+            }
+            InputAction::SonicDrop => {
+                self.state.sonic_drop();
             }
             InputAction::RotateClockwise => {
                 self.state.rotate_piece(true);
@@ -143,8 +536,25 @@ impl Game {
             InputAction::ToggleHelp => {
                 self.state.toggle_help();
             }
+            InputAction::ToggleGhost => {
+                self.state.toggle_ghost();
+            }
+            InputAction::TogglePlanning => {
+                self.state.toggle_planning();
+            }
+            InputAction::OpenSettings => {
+                self.handle_settings()?;
+            }
+            InputAction::ToggleDebug => {
+                self.state.toggle_debug();
+            }
+            InputAction::SelectNext(kind) => {
+                if self.state.config.practice_mode {
+                    self.state.queue_piece_front(kind);
+                }
+            }
             InputAction::Quit => {
-                self.state.game_over = true;
+                self.state.end_game();
 
                 self.audio.stop();
             }
@@ -153,13 +563,72 @@ impl Game {
         Ok(())
     }
 
+    fn handle_settings(&mut self) -> Result<()> {
+        let mut menu = SettingsMenu::new();
+        self.state.set_settings_open(true);
+
+        loop {
+            self.renderer.render_settings(&self.state, &menu)?;
+
+            match self.input.wait_menu_input() {
+                Some(MenuInput::Up) => menu.move_selection(-1),
+                Some(MenuInput::Down) => menu.move_selection(1),
+                Some(MenuInput::Decrease) => menu.adjust(&mut self.state.config, -1),
+                Some(MenuInput::Increase) => menu.adjust(&mut self.state.config, 1),
+                Some(MenuInput::Close) => break,
+                None => {}
+            }
+        }
+        self.state.set_settings_open(false);
+
+        if menu.changed() {
+            self.apply_settings();
+            if let Some(ref path) = self.config_path {
+                self.state
+                    .config
+                    .save_to_file(path)
+                    .map_err(|e| anyhow::anyhow!("Failed to save settings: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Pushes config values that are cached outside `GameState` back into effect.
+    fn apply_settings(&mut self) {
+        let config = &self.state.config;
+        self.horizontal_repeat = AutoRepeat::new(
+            Duration::from_millis(config.das_delay),
+            Duration::from_millis(config.das_repeat),
+        )
+        .with_initial_tap(config.das_initial_tap);
+        self.held_direction = None;
+
+        if !config.enable_sound {
+            self.audio.pause();
+        } else if self.audio.use_audio() {
+            self.audio.resume();
+        } else {
+            // The game started silent, so there is no stream to resume yet
+            self.audio = open_audio(config);
+            self.current_track = None;
+            self.start_music();
+        }
+        self.state.populate_next_pieces();
+    }
+
     fn handle_pause(&mut self) -> Result<()> {
+        self.state.toggle_pause();
         self.audio.pause();
+        self.state.reveal_stats(Instant::now());
+        // Releases during the pause go unseen, so don't resume with keys "held"
+        self.held_direction = None;
+        self.horizontal_repeat.release();
+        self.soft_drop_repeat.release();
 
         self.renderer.render_pause(&self.state)?;
 
         loop {
-            if let Some(action) = self.input.poll_input() {
+            if let Some(action) = self.input.wait_input() {
                 match action {
                     InputAction::Pause => {
                         self.audio.resume();
@@ -168,23 +637,64 @@ impl Game {
                     }
                     InputAction::Quit => {
                         self.audio.stop();
-                        self.state.game_over = true;
+                        self.state.end_game();
                         break;
                     }
                     _ => {}
                 }
             }
-            std::thread::sleep(Duration::from_millis(16));
         }
 
+        self.state.toggle_pause();
         Ok(())
     }
 }
 
-struct TerminalCleanup;
+/// Opens the configured audio device at the configured volumes.
+fn open_audio(config: &GameConfig) -> AudioPlayer {
+    let mut audio = AudioPlayer::open(config.audio_device.as_deref(), config.audio_latency_ms);
+    audio.set_master_volume(config.master_volume);
+    audio.set_music_volume(config.music_volume);
+    audio.set_sfx_volume(config.sfx_volume);
+    audio
+}
+
+/// The track for the highest level threshold in `level_music` that `level` has
+/// reached, or `None` below the first threshold.
+fn track_for_level(level_music: &BTreeMap<u32, PathBuf>, level: u32) -> Option<&PathBuf> {
+    level_music
+        .range(..=level)
+        .next_back()
+        .map(|(_, track)| track)
+}
+
+/// Scales the level's gravity interval by board coverage: an empty board falls at
+/// `ADAPTIVE_FASTEST_FACTOR` of the base interval, easing back to the base as it fills.
+fn adaptive_gravity(base: Duration, coverage: f64) -> Duration {
+    let crowding = (coverage / ADAPTIVE_FULL_SPEED_COVERAGE).clamp(0.0, 1.0);
+    let factor = ADAPTIVE_FASTEST_FACTOR + (1.0 - ADAPTIVE_FASTEST_FACTOR) * crowding;
+    base.mul_f64(factor)
+}
+
+/// Speeds up a gravity interval by `ramp` rows per second for each second of
+/// `elapsed`, never going faster than 20G.
+fn ramped_gravity(base: Duration, ramp: f64, elapsed: Duration) -> Duration {
+    if ramp <= 0.0 || base.is_zero() {
+        return base;
+    }
+    let rows_per_sec = 1.0 / base.as_secs_f64() + ramp * elapsed.as_secs_f64();
+    Duration::from_secs_f64(1.0 / rows_per_sec).max(TWENTY_G_INTERVAL)
+}
+
+struct TerminalCleanup {
+    keyboard_enhanced: bool,
+}
 
 impl Drop for TerminalCleanup {
     fn drop(&mut self) {
+        if self.keyboard_enhanced {
+            let _ = execute!(stdout(), PopKeyboardEnhancementFlags);
+        }
         let _ = disable_raw_mode();
         let _ = execute!(stdout(), LeaveAlternateScreen, DisableMouseCapture);
         let _ = stdout().flush();
@@ -196,7 +706,15 @@ fn setup_terminal() -> TerminalCleanup {
     execute!(stdout(), EnterAlternateScreen, EnableMouseCapture)
         .expect("Failed to enter alternate screen");
 
-    TerminalCleanup
+    // Ask for key release events where the terminal supports them
+    let keyboard_enhanced = supports_keyboard_enhancement().unwrap_or(false)
+        && execute!(
+            stdout(),
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+        )
+        .is_ok();
+
+    TerminalCleanup { keyboard_enhanced }
 }
 
 #[cfg(test)]
@@ -253,6 +771,83 @@ mod tests {
         assert_eq!(gravity.as_millis(), 800);
     }
 
+    #[test]
+    fn test_adaptive_gravity_empty_board_is_fastest() {
+        let gravity = adaptive_gravity(Duration::from_millis(800), 0.0);
+        assert_eq!(gravity.as_millis(), 400);
+    }
+
+    #[test]
+    fn test_adaptive_gravity_scales_with_coverage() {
+        let gravity = adaptive_gravity(Duration::from_millis(800), 0.25);
+        assert_eq!(gravity.as_millis(), 600);
+    }
+
+    #[test]
+    fn test_adaptive_gravity_crowded_board_uses_base() {
+        let base = Duration::from_millis(800);
+        assert_eq!(adaptive_gravity(base, 0.5), base);
+        assert_eq!(adaptive_gravity(base, 1.0), base);
+    }
+
+    #[test]
+    fn test_ramped_gravity_disabled_keeps_base() {
+        let base = Duration::from_millis(800);
+        assert_eq!(ramped_gravity(base, 0.0, Duration::from_secs(60)), base);
+    }
+
+    #[test]
+    fn test_ramped_gravity_speeds_up_over_time() {
+        // 800 ms is 1.25 rows/s; each second adds 0.25 rows/s
+        let base = Duration::from_millis(800);
+        let at = |secs| ramped_gravity(base, 0.25, Duration::from_secs(secs)).as_millis();
+
+        assert_eq!(at(0), 800);
+        assert_eq!(at(3), 500);
+        assert_eq!(at(11), 250);
+        assert_eq!(at(35), 100);
+    }
+
+    #[test]
+    fn test_ramped_gravity_clamps_to_20g() {
+        let gravity = ramped_gravity(Duration::from_millis(50), 100.0, Duration::from_secs(600));
+        assert_eq!(gravity, TWENTY_G_INTERVAL);
+    }
+
+    #[test]
+    fn test_play_state_starts_on_first_key_press() {
+        let waiting = PlayState::WaitingToStart;
+        assert_eq!(waiting.after_input(false), PlayState::WaitingToStart);
+        assert_eq!(waiting.after_input(true), PlayState::Playing);
+
+        // Later input leaves a running game running
+        assert_eq!(PlayState::Playing.after_input(true), PlayState::Playing);
+        assert_eq!(PlayState::Playing.after_input(false), PlayState::Playing);
+    }
+
+    #[test]
+    fn test_track_for_level() {
+        let level_music = BTreeMap::from([
+            (5, PathBuf::from("mid.wav")),
+            (10, PathBuf::from("fast.wav")),
+        ]);
+
+        assert_eq!(track_for_level(&level_music, 1), None);
+        assert_eq!(
+            track_for_level(&level_music, 5),
+            Some(&PathBuf::from("mid.wav"))
+        );
+        assert_eq!(
+            track_for_level(&level_music, 9),
+            Some(&PathBuf::from("mid.wav"))
+        );
+        assert_eq!(
+            track_for_level(&level_music, 15),
+            Some(&PathBuf::from("fast.wav"))
+        );
+        assert_eq!(track_for_level(&BTreeMap::new(), 15), None);
+    }
+
     #[test]
     fn test_handle_input_action_types() {
         let actions = [
@@ -272,7 +867,9 @@ mod tests {
 
     #[test]
     fn test_terminal_cleanup_drop() {
-        let cleanup = TerminalCleanup;
+        let cleanup = TerminalCleanup {
+            keyboard_enhanced: false,
+        };
         drop(cleanup);
     }
 }
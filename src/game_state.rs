@@ -1,29 +1,377 @@
-use crate::board::Board;
-use crate::config::GameConfig;
+use crate::board::{Board, Checksum};
+use crate::config::{ConfigError, GameConfig, Randomizer};
+use crate::rotation;
 use crate::tetrimino::{Tetrimino, TetriminoType};
-use rand::rng;
-use rand::seq::SliceRandom;
-use std::time::Instant;
+use rand::rngs::ChaCha12Rng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::{Rng, SeedableRng, rng};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// How many recent pieces the TGM randomizer tries to avoid.
+const TGM_HISTORY_LEN: usize = 4;
+/// Re-roll attempts the TGM randomizer makes before accepting a repeat.
+const TGM_ROLLS: usize = 4;
+/// Garbage sent by a combo, indexed by clears in a row before this one; longer
+/// combos send the last entry.
+const COMBO_ATTACK: [u32; 11] = [0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5];
+/// Extra garbage for a back-to-back Tetris or T-Spin.
+const BACK_TO_BACK_ATTACK: u32 = 1;
+/// Extra garbage for clearing the whole board.
+const PERFECT_CLEAR_ATTACK: u32 = 10;
+/// Pieces `first_piece_guaranteed_good` allows to open a game, and the only ones the
+/// TGM randomizer deals first.
+const GOOD_OPENERS: [TetriminoType; 4] = [
+    TetriminoType::I,
+    TetriminoType::J,
+    TetriminoType::L,
+    TetriminoType::T,
+];
+/// Horizontal offsets tried, in order, when `spawn_kick` rescues a blocked spawn.
+const SPAWN_KICK_OFFSETS: [i32; 4] = [-1, 1, -2, 2];
+/// Rows wiped from the top of the stack when a life is spent on a top-out.
+const LIFE_CLEAR_ROWS: usize = 4;
+/// How long `minimal_ui` shows the side panels after a pause or level-up.
+const STATS_REVEAL_DURATION: Duration = Duration::from_secs(3);
+/// How long the "LEVEL N" banner stays up after a level-up.
+const LEVEL_UP_BANNER_DURATION: Duration = Duration::from_millis(700);
+/// How long `safe_hard_drop` waits for the confirming second press.
+const HARD_DROP_CONFIRM_WINDOW: Duration = Duration::from_secs(1);
+/// Half of one line clear blink: cleared rows are shown, then hidden, for this long each.
+const CLEAR_BLINK_INTERVAL: Duration = Duration::from_millis(250);
+/// How long `highlight_holes` flashes the holes a lock created.
+const HOLE_HIGHLIGHT_DURATION: Duration = Duration::from_millis(600);
+/// On/off period of the hole highlight flash.
+const HOLE_FLASH_INTERVAL: Duration = Duration::from_millis(150);
+/// How long a new piece is drawn dim after spawning, with `spawn_fade_in` on.
+const SPAWN_FADE_DURATION: Duration = Duration::from_millis(100);
+/// Successful moves or rotations that may restart the lock delay for one piece.
+const MAX_LOCK_RESETS: u32 = 15;
+/// How many queued pieces the planning overlay previews.
+const PLANNING_DEPTH: usize = 2;
+/// Lines a Sprint game has to clear.
+const SPRINT_LINES: u32 = 40;
+/// How long an Ultra game lasts.
+const ULTRA_TIME_LIMIT: Duration = Duration::from_secs(120);
+
+/// Rule set chosen on the title screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GameMode {
+    /// Play until topping out.
+    #[default]
+    Marathon,
+    /// Clear 40 lines as fast as possible.
+    Sprint,
+    /// Score as much as possible in two minutes.
+    Ultra,
+    /// No top-outs: a full stack is cleared away and play goes on.
+    Zen,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 4] = [
+        GameMode::Marathon,
+        GameMode::Sprint,
+        GameMode::Ultra,
+        GameMode::Zen,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GameMode::Marathon => "Marathon",
+            GameMode::Sprint => "Sprint",
+            GameMode::Ultra => "Ultra",
+            GameMode::Zen => "Zen",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            GameMode::Marathon => "Play until you top out",
+            GameMode::Sprint => "Clear 40 lines",
+            GameMode::Ultra => "Two minutes to score",
+            GameMode::Zen => "Endless, no game over",
+        }
+    }
+}
+
+/// The randomizer state behind the piece queue. It lives apart from the rest of
+/// the game so it can be forked to look ahead without dealing anything.
+struct PieceGenerator {
+    rng: ChaCha12Rng,
+    bag: Vec<TetriminoType>,
+    history: Vec<TetriminoType>,
+    /// Whether the TGM randomizer has dealt its opening piece yet.
+    tgm_opened: bool,
+}
+
+impl Clone for PieceGenerator {
+    fn clone(&self) -> Self {
+        // The rng isn't Clone, but a copy with the same seed, stream and word
+        // position draws the same numbers from here on
+        let mut rng = ChaCha12Rng::from_seed(self.rng.get_seed());
+        rng.set_stream(self.rng.get_stream());
+        rng.set_word_pos(self.rng.get_word_pos());
+        Self {
+            rng,
+            bag: self.bag.clone(),
+            history: self.history.clone(),
+            tgm_opened: self.tgm_opened,
+        }
+    }
+}
+
+impl PieceGenerator {
+    fn new(seed: u64) -> Self {
+        Self {
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            bag: Vec::new(),
+            // TGM seeds its history with S/Z to bias the early pieces away from them
+            history: vec![
+                TetriminoType::Z,
+                TetriminoType::S,
+                TetriminoType::Z,
+                TetriminoType::S,
+            ],
+            tgm_opened: false,
+        }
+    }
+
+    fn refill_bag(&mut self, config: &GameConfig) {
+        // Create a new bag with `bag_repeats` copies of all 7 tetrimino types, or of
+        // the custom bag
+        let repeats = config.bag_repeats.max(1) as usize;
+        let mut new_bag = match config.randomizer {
+            Randomizer::Custom => config.custom_bag.repeat(repeats),
+            _ => TetriminoType::ALL.repeat(repeats),
+        };
+
+        // Shuffle the bag randomly
+        new_bag.shuffle(&mut self.rng);
+        self.bag = new_bag;
+    }
+
+    /// Reshuffles the opening bag until it deals one of `GOOD_OPENERS` first.
+    fn reshuffle_for_good_opener(&mut self, config: &GameConfig) {
+        if config.randomizer != Randomizer::SevenBag {
+            return;
+        }
+        // The bag is dealt from the back
+        while !self
+            .bag
+            .last()
+            .is_some_and(|kind| GOOD_OPENERS.contains(kind))
+        {
+            self.bag.shuffle(&mut self.rng);
+        }
+    }
+
+    fn next_piece(&mut self, config: &GameConfig) -> TetriminoType {
+        match config.randomizer {
+            Randomizer::SevenBag | Randomizer::Custom => self.next_from_bag(config),
+            Randomizer::TrueRandom => self.next_true_random(),
+            Randomizer::TgmHistory => self.next_tgm_history(),
+        }
+    }
+
+    fn next_from_bag(&mut self, config: &GameConfig) -> TetriminoType {
+        if self.bag.is_empty() {
+            self.refill_bag(config);
+        }
+        // refill_bag always yields a piece; `with_seed` ruled out an empty custom bag
+        self.bag.pop().unwrap_or(TetriminoType::I)
+    }
+
+    fn random_type(&mut self) -> TetriminoType {
+        *TetriminoType::ALL
+            .choose(&mut self.rng)
+            .unwrap_or(&TetriminoType::I)
+    }
+
+    fn next_true_random(&mut self) -> TetriminoType {
+        let mut piece = self.random_type();
+        if self.history.last() == Some(&piece) {
+            piece = self.random_type();
+        }
+        self.remember(piece);
+        piece
+    }
+
+    fn next_tgm_history(&mut self) -> TetriminoType {
+        // TGM never opens with an S, Z or O
+        if !self.tgm_opened {
+            self.tgm_opened = true;
+            let piece = *GOOD_OPENERS
+                .choose(&mut self.rng)
+                .unwrap_or(&TetriminoType::I);
+            self.remember(piece);
+            return piece;
+        }
+
+        let mut piece = self.random_type();
+        for _ in 1..TGM_ROLLS {
+            if !self.history.contains(&piece) {
+                break;
+            }
+            piece = self.random_type();
+        }
+        self.remember(piece);
+        piece
+    }
+
+    fn remember(&mut self, piece: TetriminoType) {
+        self.history.push(piece);
+        if self.history.len() > TGM_HISTORY_LEN {
+            self.history.remove(0);
+        }
+    }
+}
 
 pub struct GameState {
     pub board: Board,
+    pub mode: GameMode,
     pub current_piece: Option<Tetrimino>,
     pub held_piece: Option<TetriminoType>,
     pub next_pieces: Vec<TetriminoType>,
     pub score: u64,
     pub level: u32,
     pub lines_cleared: u32,
-    pub game_over: bool,
+    pub lives: u32,
     pub config: GameConfig,
-    bag: Vec<TetriminoType>,
+    pub seed: u64,
+    generator: PieceGenerator,
     pub lines_until_next_level: u32,
+    /// Lines cleared since the current level began.
+    pub lines_this_level: u32,
     pieces_placed: u32,
+    /// Every piece locked this game, in order, for the replay viewer.
+    locked_pieces: Vec<LockedPiece>,
     combo_count: u32,
+    /// Longest run of consecutive line-clearing pieces this game.
+    max_combo: u32,
+    /// Garbage the last locked piece would send in versus play.
+    last_attack: u32,
+    total_attack_sent: u32,
     back_to_back_active: bool,
+    /// Back-to-back bonuses awarded in a row, and the longest such run this game.
+    b2b_chain: u32,
+    max_b2b_chain: u32,
     last_was_special: bool,
     pub line_clear_animation: Option<LineClearAnimation>,
-    pub pending_line_clear: bool,
+    phase: Phase,
     pub show_help: bool,
+    /// When `safe_hard_drop` last held back a hard drop that would top out.
+    hard_drop_warned_at: Option<Instant>,
+    /// Play is paused; `tick` does nothing until it is resumed.
+    pub paused: bool,
+    /// The settings menu is up, which freezes play like a pause.
+    settings_open: bool,
+    /// Planning overlay visibility; only has an effect with `planning_mode` on.
+    pub show_planning: bool,
+    pub show_debug: bool,
+    pub started_at: Instant,
+    /// When the current level began, for the in-level gravity ramp.
+    pub level_started_at: Instant,
+    finished_at: Option<Instant>,
+    /// Time spent in clear animations and spawn delays, for `clock_counts_delays`,
+    /// and when the current such window opened.
+    delay_time: Duration,
+    delay_started: Option<Instant>,
+    /// Time spent paused, showing help or in settings, and when the current such
+    /// stop began.
+    paused_time: Duration,
+    paused_at: Option<Instant>,
+    lock_resets: u32,
+    /// When the current piece came into play, for `spawn_fade_in`.
+    spawned_at: Option<Instant>,
+    events: Vec<GameEvent>,
+    last_move_was_rotation: bool,
+    pending_tspin: TSpinKind,
+    stats_revealed_at: Option<Instant>,
+    level_up_at: Option<(u32, Instant)>,
+    buffered_inputs: Vec<BufferedInput>,
+    /// Filled cells, as `(x, y)`, that the last piece overlapped when its spawn topped out.
+    pub blocking_cells: Vec<(usize, usize)>,
+    /// Holes, as `(x, y)`, created by the last lock, and when, for `highlight_holes`.
+    new_holes: Vec<(usize, usize)>,
+    new_holes_at: Option<Instant>,
+}
+
+/// A move or rotation pressed during the line clear animation, or a hold pressed with
+/// no piece in play under `initial_hold`, replayed on the next piece once it spawns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BufferedInput {
+    Shift(i32),
+    Rotate { clockwise: bool },
+    Hold,
+}
+
+/// Where play is between one piece and the next. `tick` moves through the phases;
+/// everything time-based hangs off the one that is current.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// A piece is in play and hasn't come to rest.
+    Falling,
+    /// The piece is grounded and locks `lock_delay_ms` after `started`, unless it
+    /// falls again first.
+    LockDelay {
+        started: Instant,
+    },
+    /// Full rows are flashing; no piece is in play and gravity waits.
+    ClearAnimation,
+    /// No piece is in play; the next spawns `spawn_delay_ms` after `started`.
+    SpawnDelay {
+        started: Instant,
+    },
+    GameOver,
+}
+
+/// T-Spin classification of a locked T piece, per the guideline 3-corner rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TSpinKind {
+    #[default]
+    None,
+    /// Both back corners and only one front corner are filled.
+    Mini,
+    /// Both front corners and at least one back corner are filled.
+    Full,
+}
+
+/// Notable things that happened during play, queued for integrations to consume.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameEvent {
+    LinesCleared { lines: u32, score_delta: u64 },
+    TSpin { kind: TSpinKind, lines: u32 },
+    LevelUp { level: u32 },
+    GameOver { score: u64 },
+}
+
+/// A piece where it locked, how many lines that cleared, and any top-out rescues
+/// that wiped the board before the next piece locked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockedPiece {
+    pub piece: Tetrimino,
+    pub lines: u32,
+    pub wipes_after: Vec<BoardWipe>,
+}
+
+/// A change to the board other than a piece locking or lines clearing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardWipe {
+    /// A life was spent, emptying this many rows from the top.
+    TopRows(usize),
+    /// Zen mode emptied the whole board.
+    All,
+}
+
+impl BoardWipe {
+    pub fn apply(self, board: &mut Board) {
+        match self {
+            BoardWipe::TopRows(count) => board.clear_top_rows(count),
+            BoardWipe::All => board.clear_all(),
+        }
+    }
 }
 
 pub struct LineClearAnimation {
@@ -32,34 +380,158 @@ pub struct LineClearAnimation {
     pub total_lines: u32,
 }
 
+/// Outcome of [`GameState::try_move`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveResult {
+    Moved,
+    /// A sideways move ran into a wall or the stack.
+    BlockedHorizontal,
+    /// A downward move ran into the floor or the stack; the piece should lock.
+    BlockedByFloor,
+    /// There is no active piece to move.
+    NoPiece,
+}
+
+/// Machine-readable end-of-game stats, written by `--result-json`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GameResult {
+    pub mode: &'static str,
+    pub seed: u64,
+    pub score: u64,
+    pub level: u32,
+    pub lines: u32,
+    pub duration_ms: u64,
+    /// Pieces placed per second.
+    pub pps: f64,
+    /// Always `None` until finesse is tracked.
+    pub finesse_faults: Option<u32>,
+    pub max_combo: u32,
+}
+
+/// The piece queue at one moment, for state and replay export. Pieces serialize as
+/// their letters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueSnapshot {
+    pub next_pieces: Vec<TetriminoType>,
+    pub held_piece: Option<TetriminoType>,
+    /// Pieces left in the current bag, in the order they will be dealt.
+    pub bag: Vec<TetriminoType>,
+}
+
+/// Aggregate stats from a headless run of [`GameState::simulate_placements`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimResult {
+    pub score: u64,
+    pub lines_cleared: u32,
+    pub game_over: bool,
+}
+
 impl GameState {
     pub fn new(config: GameConfig) -> Self {
+        Self::with_mode(config, GameMode::default())
+    }
+
+    pub fn with_mode(config: GameConfig, mode: GameMode) -> Self {
+        let seed = config.seed.unwrap_or_else(|| rng().random());
+        Self::with_seed(config, mode, seed)
+    }
+
+    /// A fresh game of the same mode and seed, so the same pieces are dealt again.
+    /// The config, including an unset `seed`, carries over unchanged.
+    pub fn replay(&self) -> Self {
+        Self::with_seed(self.config.clone(), self.mode, self.seed)
+    }
+
+    /// Like [`GameState::new`], but rejects a config whose randomizer could run out
+    /// of pieces instead of falling back to the 7-bag.
+    pub fn try_new(config: GameConfig) -> Result<Self, ConfigError> {
+        config.validate_piece_source()?;
+        Ok(Self::new(config))
+    }
+
+    fn with_seed(config: GameConfig, mode: GameMode, seed: u64) -> Self {
+        // A piece source that runs dry would leave `spawn_piece` with nothing to deal
+        let config = match config.validate_piece_source() {
+            Ok(()) => config,
+            Err(e) => {
+                eprintln!("Warning: {}. Using the 7-bag instead.", e);
+                GameConfig {
+                    randomizer: Randomizer::SevenBag,
+                    ..config
+                }
+            }
+        };
         let starting_level = config.starting_level;
-        let lines_until_next_level = config.lines_per_level;
+        let lines_until_next_level = config
+            .lines_per_level_growth
+            .lines_for_level(config.lines_per_level, starting_level);
+        let lives = config.lives.max(1);
 
         let mut game_state = Self {
             board: Board::new(config.board_width, config.board_height),
+            mode,
             current_piece: None,
             held_piece: None,
             next_pieces: Vec::new(),
             score: 0,
             level: starting_level,
             lines_cleared: 0,
-            game_over: false,
+            lives,
             config,
-            bag: Vec::new(),
+            seed,
+            generator: PieceGenerator::new(seed),
             lines_until_next_level,
+            lines_this_level: 0,
             pieces_placed: 0,
+            locked_pieces: Vec::new(),
             combo_count: 0,
+            max_combo: 0,
             back_to_back_active: false,
+            b2b_chain: 0,
+            max_b2b_chain: 0,
+            last_attack: 0,
+            total_attack_sent: 0,
             last_was_special: false,
             line_clear_animation: None,
-            pending_line_clear: false,
+            phase: Phase::Falling,
             show_help: false,
+            hard_drop_warned_at: None,
+            paused: false,
+            show_planning: true,
+            show_debug: false,
+            started_at: Instant::now(),
+            level_started_at: Instant::now(),
+            finished_at: None,
+            delay_time: Duration::ZERO,
+            delay_started: None,
+            paused_time: Duration::ZERO,
+            paused_at: None,
+            settings_open: false,
+            lock_resets: 0,
+            spawned_at: None,
+            events: Vec::new(),
+            last_move_was_rotation: false,
+            pending_tspin: TSpinKind::None,
+            stats_revealed_at: None,
+            level_up_at: None,
+            buffered_inputs: Vec::new(),
+            blocking_cells: Vec::new(),
+            new_holes: Vec::new(),
+            new_holes_at: None,
         };
 
-        // Initialize the first bag and next pieces
-        game_state.refill_bag();
+        if !game_state.config.level_thresholds.is_empty() {
+            game_state.update_level_from_thresholds();
+        }
+
+        // Initialize the first bag and next pieces, after any configured opening queue
+        game_state.next_pieces = game_state.config.initial_queue.clone();
+        game_state.generator.refill_bag(&game_state.config);
+        if game_state.config.first_piece_guaranteed_good && game_state.next_pieces.is_empty() {
+            game_state
+                .generator
+                .reshuffle_for_good_opener(&game_state.config);
+        }
         game_state.populate_next_pieces();
         game_state
     }
@@ -67,8 +539,13 @@ impl GameState {
     pub fn spawn_piece(&mut self) {
         // Get the next piece from the queue
         if let Some(piece_type) = self.next_pieces.first() {
-            let piece = Tetrimino::new(*piece_type);
+            let piece = self.spawn_tetrimino(*piece_type);
             self.current_piece = Some(piece);
+            self.set_phase(Phase::Falling);
+            self.spawned_at = Some(Instant::now());
+            self.hard_drop_warned_at = None;
+            self.last_move_was_rotation = false;
+            self.lock_resets = 0;
 
             // Remove the piece from next_pieces and refill if needed
             self.next_pieces.remove(0);
@@ -76,30 +553,108 @@ impl GameState {
 
             // Check if the spawned piece can be placed
             if let Some(ref current) = self.current_piece
-                && !self.board.is_valid_position(current)
+                && !self.board.can_spawn(current)
+                && !(self.config.spawn_kick && self.try_spawn_kick())
+                && !self.rescue_top_out()
             {
-                self.game_over = true;
+                if let Some(piece) = self.current_piece {
+                    self.blocking_cells = self.board.overlapping_cells(&piece);
+                }
+                self.end_game();
+            } else {
+                self.replay_buffered_inputs();
+            }
+        }
+    }
+
+    /// A new `kind` piece at the spawn position.
+    fn spawn_tetrimino(&self, kind: TetriminoType) -> Tetrimino {
+        Tetrimino {
+            y: self.config.spawn_row,
+            rotation: rotation::spawn_rotation(self.config.rotation_system, kind),
+            ..Tetrimino::new(kind)
+        }
+    }
+
+    /// Shifts a blocked spawn sideways to the first open offset, if any.
+    fn try_spawn_kick(&mut self) -> bool {
+        let Some(piece) = self.current_piece else {
+            return false;
+        };
+
+        for dx in SPAWN_KICK_OFFSETS {
+            let candidate = Tetrimino {
+                x: piece.x + dx,
+                ..piece
+            };
+            if self.board.can_spawn(&candidate) {
+                self.current_piece = Some(candidate);
+                return true;
             }
         }
+        false
+    }
+
+    /// Slides the current piece sideways in `dir` (negative for left) until a wall
+    /// or the stack stops it.
+    pub fn move_to_wall(&mut self, dir: i32) {
+        let dx = dir.signum();
+        if dx == 0 {
+            return;
+        }
+        while self.move_piece(dx, 0) {}
     }
 
     pub fn move_piece(&mut self, dx: i32, dy: i32) -> bool {
-        if let Some(ref mut piece) = self.current_piece {
-            piece.x += dx;
-            piece.y += dy;
-
-            if !self.board.is_valid_position(piece) {
-                piece.x -= dx;
-                piece.y -= dy;
-                return false;
+        self.try_move(dx, dy) == MoveResult::Moved
+    }
+
+    /// Moves the current piece by `(dx, dy)`, reporting why a move was refused.
+    /// Any blocked move with a downward component counts as hitting the floor.
+    pub fn try_move(&mut self, dx: i32, dy: i32) -> MoveResult {
+        let Some(ref mut piece) = self.current_piece else {
+            if self.is_between_pieces() && dx != 0 && dy == 0 {
+                self.buffered_inputs.push(BufferedInput::Shift(dx));
             }
-            true
+            return MoveResult::NoPiece;
+        };
+
+        piece.x += dx;
+        piece.y += dy;
+
+        if !self.board.is_valid_position(piece) {
+            piece.x -= dx;
+            piece.y -= dy;
+            return if dy > 0 {
+                MoveResult::BlockedByFloor
+            } else {
+                MoveResult::BlockedHorizontal
+            };
+        }
+
+        // Falling again (e.g. slid off a ledge) cancels a pending lock; a sideways
+        // step on the ground buys more time
+        if dy > 0 {
+            self.cancel_lock_delay();
         } else {
-            false
+            self.reset_lock_delay();
         }
+        self.last_move_was_rotation = false;
+        MoveResult::Moved
     }
 
-    pub fn rotate_piece(&mut self, clockwise: bool) {
+    /// Rotates the current piece, trying wall kicks if needed. Returns whether the
+    /// piece actually turned.
+    pub fn rotate_piece(&mut self, clockwise: bool) -> bool {
+        if self.config.classic_rotation && !clockwise {
+            return false;
+        }
+        if self.current_piece.is_none() && self.is_between_pieces() {
+            self.buffered_inputs
+                .push(BufferedInput::Rotate { clockwise });
+            return false;
+        }
+
         if self.current_piece.as_ref().map(|p| p.kind).is_some() {
             let old_rotation;
             let old_x;
@@ -114,10 +669,12 @@ impl GameState {
                 piece_type = piece.kind;
             }
 
+            // Keep rotation in 0..4 so a counter-clockwise turn from 0 doesn't wrap to
+            // usize::MAX and overflow on the next clockwise turn.
             let new_rotation = if clockwise {
-                old_rotation + 1
+                (old_rotation + 1) % 4
             } else {
-                old_rotation.wrapping_sub(1)
+                (old_rotation + 3) % 4
             };
 
             // First try the basic rotation
@@ -131,7 +688,11 @@ impl GameState {
                 .board
                 .is_valid_position(self.current_piece.as_ref().unwrap())
             {
-                let kicks = self.get_wall_kicks(piece_type, old_rotation, new_rotation);
+                let kicks = if self.config.classic_rotation {
+                    Vec::new()
+                } else {
+                    self.get_wall_kicks(piece_type, old_rotation, new_rotation)
+                };
                 let mut kicked = false;
 
                 for (dx, dy) in kicks {
@@ -150,29 +711,227 @@ impl GameState {
                     }
                 }
 
+                if !kicked && self.config.rotation_assist {
+                    kicked = self.assist_nudge(old_x, old_y);
+                }
+
                 // If no wall kick worked, revert to original position
                 if !kicked {
                     let piece = self.current_piece.as_mut().unwrap();
                     piece.rotation = old_rotation;
                     piece.x = old_x;
                     piece.y = old_y;
+                    return false;
                 }
             }
+
+            self.last_move_was_rotation = true;
+            self.reset_lock_delay();
+            return true;
         }
+        false
+    }
+
+    /// `rotation_assist` fallback: puts the rotated piece back at `(x, y)` and shifts
+    /// it one column away from the side its blocked cells are on. Returns whether the
+    /// shifted piece fits; blocks on both sides, or only below, leave nowhere to go.
+    fn assist_nudge(&mut self, x: i32, y: i32) -> bool {
+        let Some(piece) = self.current_piece.as_mut() else {
+            return false;
+        };
+        piece.x = x;
+        piece.y = y;
+
+        let blocks = piece.get_blocks();
+        let center = blocks.iter().map(|&(dx, _)| dx).sum::<i32>() as f64 / blocks.len() as f64;
+        let blocked: Vec<f64> = blocks
+            .iter()
+            .filter(|&&(dx, dy)| !self.board.is_cell_free(x + dx, y + dy))
+            .map(|&(dx, _)| dx as f64)
+            .collect();
+
+        let dx = if blocked.iter().all(|&bx| bx < center) {
+            1
+        } else if blocked.iter().all(|&bx| bx > center) {
+            -1
+        } else {
+            return false;
+        };
+
+        piece.x = x + dx;
+        self.board.is_valid_position(piece)
+    }
+
+    /// Drops the current piece to its landing row like a hard drop but leaves it in
+    /// play, scoring each row as a soft drop. Returns the number of rows dropped.
+    pub fn sonic_drop(&mut self) -> u32 {
+        let rows = self.drop_current_piece();
+        self.score += rows as u64 * self.config.scoring.soft_drop;
+        rows
     }
 
     pub fn hard_drop(&mut self) {
-        while self.move_piece(0, 1) {}
-        self.lock_current_piece();
+        self.drop_current_piece();
+        if self.config.hard_drop_locks {
+            self.lock_current_piece();
+        } else {
+            self.start_lock_delay();
+        }
+    }
+
+    /// Hard drops, except that with `safe_hard_drop` on a drop that would top out
+    /// needs a second press within `HARD_DROP_CONFIRM_WINDOW`; the first only raises
+    /// the warning. Returns whether the piece was dropped.
+    pub fn request_hard_drop(&mut self, now: Instant) -> bool {
+        if self.config.safe_hard_drop && self.hard_drop_tops_out() && !self.hard_drop_warning(now) {
+            self.hard_drop_warned_at = Some(now);
+            return false;
+        }
+        self.hard_drop_warned_at = None;
+        self.hard_drop();
+        true
+    }
+
+    /// Whether a held-back hard drop is still waiting for its confirming press.
+    pub fn hard_drop_warning(&self, now: Instant) -> bool {
+        self.hard_drop_warned_at
+            .is_some_and(|at| now.saturating_duration_since(at) < HARD_DROP_CONFIRM_WINDOW)
+    }
+
+    /// Whether hard dropping the current piece would end the game: either it locks
+    /// entirely above the board, or the next piece then has nowhere to spawn. Lives
+    /// and Zen's rescue aren't counted.
+    pub fn hard_drop_tops_out(&self) -> bool {
+        let Some(piece) = self.current_piece else {
+            return false;
+        };
+        let landed = self.drop_position(piece);
+        if landed.get_blocks().iter().all(|&(_, dy)| landed.y + dy < 0) {
+            return true;
+        }
+
+        let mut after = self.board.clone();
+        after.lock_tetromino(&landed);
+        after.clear_lines_with(self.config.clear_gravity);
+        let Some(&next) = self.next_pieces.first() else {
+            return false;
+        };
+        let spawn = self.spawn_tetrimino(next);
+        let kicks: &[i32] = if self.config.spawn_kick {
+            &SPAWN_KICK_OFFSETS
+        } else {
+            &[]
+        };
+        !std::iter::once(&0).chain(kicks).any(|dx| {
+            after.can_spawn(&Tetrimino {
+                x: spawn.x + dx,
+                ..spawn
+            })
+        })
+    }
+
+    /// Starts the lock delay timer for a grounded piece, if not already running.
+    pub fn start_lock_delay(&mut self) {
+        if self.current_piece.is_some() && self.phase == Phase::Falling {
+            self.set_phase(Phase::LockDelay {
+                started: Instant::now(),
+            });
+        }
+    }
+
+    /// Drops a running lock delay, for a piece that is falling again or has left play.
+    fn cancel_lock_delay(&mut self) {
+        if let Phase::LockDelay { .. } = self.phase {
+            self.set_phase(Phase::Falling);
+        }
+    }
+
+    /// Restarts a running lock delay after a successful move or rotation, at most
+    /// `MAX_LOCK_RESETS` times per piece so a grounded piece can't stall forever.
+    fn reset_lock_delay(&mut self) {
+        if let Phase::LockDelay { started } = &mut self.phase
+            && self.lock_resets < MAX_LOCK_RESETS
+        {
+            *started = Instant::now();
+            self.lock_resets += 1;
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Moves to `phase`, timing the stretches with no piece in play.
+    fn set_phase(&mut self, phase: Phase) {
+        let was_between_pieces = self.is_between_pieces();
+        self.phase = phase;
+        match (was_between_pieces, self.is_between_pieces()) {
+            (false, true) => self.delay_started = Some(Instant::now()),
+            (true, false) => {
+                if let Some(started) = self.delay_started.take() {
+                    self.delay_time += started.elapsed();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.phase == Phase::GameOver
+    }
+
+    /// Whether rows are flashing before they are cleared.
+    pub fn is_line_clear_pending(&self) -> bool {
+        self.phase == Phase::ClearAnimation
+    }
+
+    /// No piece in play yet, during the clear animation or the spawn delay; moves
+    /// pressed now are buffered for the next piece.
+    fn is_between_pieces(&self) -> bool {
+        matches!(self.phase, Phase::ClearAnimation | Phase::SpawnDelay { .. })
+    }
+
+    pub fn is_lock_delay_active(&self) -> bool {
+        matches!(self.phase, Phase::LockDelay { .. })
+    }
+
+    /// True once a running lock delay has lasted `lock_delay_ms` as of `now`.
+    pub fn lock_delay_expired(&self, now: Instant) -> bool {
+        match self.phase {
+            Phase::LockDelay { started } => {
+                now.saturating_duration_since(started)
+                    >= Duration::from_millis(self.config.lock_delay_ms)
+            }
+            _ => false,
+        }
     }
 
     pub fn lock_current_piece(&mut self) {
+        self.cancel_lock_delay();
         if let Some(piece) = self.current_piece.take() {
+            // Lock out: nothing of the piece made it onto the board
+            let locked_out = piece.get_blocks().iter().all(|&(_, dy)| piece.y + dy < 0);
+            if locked_out && !self.rescue_top_out() {
+                self.end_game();
+                return;
+            }
+
+            self.pending_tspin = self.check_tspin(&piece);
+            let before = self.config.highlight_holes.then(|| self.board.clone());
             self.board.lock_tetromino(&piece);
+            if let Some(before) = before {
+                self.new_holes = self.board.new_holes_since(&before);
+                self.new_holes_at = Some(Instant::now());
+            }
             self.pieces_placed += 1;
 
             let cleared_rows = self.board.get_full_lines();
             let lines = cleared_rows.len() as u32;
+            self.locked_pieces.push(LockedPiece {
+                piece,
+                lines,
+                wipes_after: Vec::new(),
+            });
 
             if lines > 0 {
                 self.line_clear_animation = Some(LineClearAnimation {
@@ -180,60 +939,118 @@ impl GameState {
                     start_time: Instant::now(),
                     total_lines: lines,
                 });
-                self.pending_line_clear = true;
+                self.set_phase(Phase::ClearAnimation);
             } else {
                 self.update_score(0, false);
-                self.spawn_piece();
+                self.begin_spawn();
             }
         }
     }
 
     pub fn complete_line_clear(&mut self) {
-        if !self.pending_line_clear {
+        if self.phase != Phase::ClearAnimation {
             return;
         }
-        self.pending_line_clear = false;
 
-        let lines = self.board.clear_lines();
+        // Cascade gravity clears one step at a time; each chained step flashes and
+        // scores as a clear of its own, continuing the combo
+        let lines = self.board.clear_step_with(self.config.clear_gravity);
         self.lines_cleared += lines;
+        self.lines_this_level += lines;
+        // The rows the holes were in have moved
+        self.new_holes.clear();
 
         if lines > 0 {
             self.update_score(lines, true);
         }
 
         self.line_clear_animation = None;
-        self.spawn_piece();
+        if self.mode == GameMode::Sprint && self.lines_cleared >= SPRINT_LINES {
+            self.end_game();
+            return;
+        }
+
+        let chained_rows = self.board.get_full_lines();
+        if !chained_rows.is_empty() {
+            self.line_clear_animation = Some(LineClearAnimation {
+                total_lines: chained_rows.len() as u32,
+                cleared_rows: chained_rows,
+                start_time: Instant::now(),
+            });
+            return;
+        }
+        self.begin_spawn();
+    }
+
+    /// Spawns the next piece, or starts the spawn delay if `spawn_delay_ms` is set.
+    fn begin_spawn(&mut self) {
+        if self.config.spawn_delay_ms > 0 {
+            self.set_phase(Phase::SpawnDelay {
+                started: Instant::now(),
+            });
+        } else {
+            self.spawn_piece();
+        }
+    }
+
+    /// Applies input buffered while no piece was in play to the new piece. A buffered
+    /// hold goes first, so moves and rotations apply to the piece that stays in play.
+    fn replay_buffered_inputs(&mut self) {
+        let inputs = std::mem::take(&mut self.buffered_inputs);
+        if inputs.contains(&BufferedInput::Hold) {
+            self.hold_piece();
+        }
+        for input in inputs {
+            match input {
+                BufferedInput::Shift(dx) => {
+                    self.move_piece(dx, 0);
+                }
+                BufferedInput::Rotate { clockwise } => {
+                    self.rotate_piece(clockwise);
+                }
+                BufferedInput::Hold => {}
+            }
+        }
     }
 
     fn update_score(&mut self, lines: u32, _lines_cleared: bool) {
+        let tspin = std::mem::take(&mut self.pending_tspin);
+        self.last_attack = 0;
         if lines == 0 {
             self.combo_count = 0;
             self.last_was_special = false;
             return;
         }
 
-        // Check for T-Spin detection
-        let is_tspin = self.check_tspin();
+        let is_tspin = tspin != TSpinKind::None;
 
         // Calculate awarded line clears
         let awarded_lines = self.compute_awarded_lines(lines, is_tspin);
 
-        // Apply score based on awarded lines and T-Spin
-        let base_score: u64 = match awarded_lines {
-            1 => 100,
-            2 => 300,
-            3 => 500,
-            4 => 800,
+        let scoring = &self.config.scoring;
+
+        // Apply score based on awarded lines and T-Spin; a Mini replaces the line
+        // clear value with its own, smaller table, and a full T-Spin with the
+        // T-Spin table below
+        let base_score: u64 = match (tspin, awarded_lines) {
+            (TSpinKind::Mini, 1) => scoring.mini_single,
+            (TSpinKind::Mini, 2) => scoring.mini_double,
+            (TSpinKind::Mini, _) => scoring.mini,
+            (TSpinKind::Full, _) => 0,
+            (_, 1) => scoring.single,
+            (_, 2) => scoring.double,
+            (_, 3) => scoring.triple,
+            (_, 4) => scoring.tetris,
             _ => 0,
         };
 
-        // Apply T-Spin bonus multiplier
-        let tspin_bonus: u64 = if is_tspin {
+        // Apply T-Spin bonus
+        let tspin_bonus: u64 = if tspin == TSpinKind::Full {
             match awarded_lines {
-                1 => 800,
-                2 => 1200,
-                3 => 1600,
-                4 => 2000,
+                1 => scoring.tspin_single_bonus,
+                2 => scoring.tspin_double_bonus,
+                3 => scoring.tspin_triple_bonus,
+                4 => scoring.tspin_quad_bonus,
                 _ => 0,
             }
         } else {
@@ -241,38 +1058,189 @@ impl GameState {
         };
 
         // Calculate combo bonus
-        let combo_bonus: u64 = if self.combo_count > 0 {
-            (self.combo_count * 50) as u64
-        } else {
-            0
-        };
+        let scoring_combo = self
+            .config
+            .max_combo
+            .map_or(self.combo_count, |cap| self.combo_count.min(cap));
+        let combo_bonus: u64 = scoring_combo as u64 * scoring.combo_step;
 
         // Calculate back-to-back bonus
         let is_special = awarded_lines == 4 || is_tspin;
-        let back_to_back_bonus: u64 = if self.back_to_back_active && is_special {
-            (base_score + tspin_bonus) / 2
+        let is_back_to_back = self.back_to_back_active && is_special;
+        let back_to_back_bonus: u64 = if is_back_to_back {
+            ((base_score + tspin_bonus) as f64 * (scoring.back_to_back_multiplier - 1.0)) as u64
         } else {
             0
         };
 
-        self.score +=
+        let score_delta =
             (base_score + tspin_bonus + combo_bonus + back_to_back_bonus) * self.level as u64;
+        self.score += score_delta;
+
+        let perfect_clear = self
+            .board
+            .column_heights()
+            .iter()
+            .all(|&height| height == 0);
+        self.last_attack = attack_for(
+            lines,
+            tspin,
+            is_back_to_back,
+            self.combo_count,
+            perfect_clear,
+        );
+        self.total_attack_sent += self.last_attack;
+
+        self.events
+            .push(GameEvent::LinesCleared { lines, score_delta });
+        if is_tspin {
+            self.events.push(GameEvent::TSpin { kind: tspin, lines });
+        }
 
         // Update back-to-back state
+        if is_back_to_back {
+            self.b2b_chain += 1;
+            self.max_b2b_chain = self.max_b2b_chain.max(self.b2b_chain);
+        } else {
+            self.b2b_chain = 0;
+        }
         self.back_to_back_active = is_special;
         self.last_was_special = is_special;
 
         // Increment combo if lines were cleared
         if lines > 0 {
             self.combo_count += 1;
+            self.max_combo = self.max_combo.max(self.combo_count);
         }
 
         // Update level based on selected goal system
-        if self.config.enable_variable_goal {
-            self.update_level_variable_goal(lines, is_tspin);
-        } else {
-            self.update_level_fixed_goal(lines);
+        let previous_level = self.level;
+        if !self.config.freeze_level {
+            if !self.config.level_thresholds.is_empty() {
+                self.update_level_from_thresholds();
+            } else if self.config.enable_variable_goal {
+                self.update_level_variable_goal(lines, is_tspin);
+            } else {
+                self.update_level_fixed_goal(lines);
+            }
+        }
+        if self.level != previous_level {
+            let now = Instant::now();
+            self.events.push(GameEvent::LevelUp { level: self.level });
+            self.level_up_at = Some((self.level, now));
+            self.level_started_at = now;
+            self.reveal_stats(now);
+        }
+    }
+
+    /// Briefly shows the side panels when `minimal_ui` hides them.
+    pub fn reveal_stats(&mut self, now: Instant) {
+        self.stats_revealed_at = Some(now);
+    }
+
+    /// How far through the current level's goal the player is, from 0.0 to 1.0. Works
+    /// for every goal system, since it only compares lines cleared this level with
+    /// lines still needed.
+    pub fn level_progress(&self) -> f64 {
+        let goal = self.lines_this_level + self.lines_until_next_level;
+        if goal == 0 {
+            return 1.0;
+        }
+        (self.lines_this_level as f64 / goal as f64).min(1.0)
+    }
+
+    /// Restarts the game and level clocks from `now`, for a game that sat waiting to
+    /// start after it was set up.
+    pub fn start_clock(&mut self, now: Instant) {
+        self.started_at = now;
+        self.level_started_at = now;
+        self.delay_time = Duration::ZERO;
+        self.paused_time = Duration::ZERO;
+    }
+
+    /// Game time as of `now`, stopping at game over. Time spent paused or showing
+    /// help never counts; with `clock_counts_delays` off, line clear animations and
+    /// spawn delays don't either.
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        let end = self.finished_at.unwrap_or(now);
+        let current_pause = self.paused_at.map_or(Duration::ZERO, |paused_at| {
+            end.saturating_duration_since(paused_at)
+        });
+        let wall = end
+            .saturating_duration_since(self.started_at)
+            .saturating_sub(self.paused_time + current_pause);
+        if self.config.clock_counts_delays {
+            return wall;
         }
+        let current_delay = self.delay_started.map_or(Duration::ZERO, |started| {
+            end.saturating_duration_since(started)
+        });
+        wall.saturating_sub(self.delay_time + current_delay)
+    }
+
+    /// Time left on Ultra's clock as of `now`; `None` in untimed modes.
+    pub fn time_remaining(&self, now: Instant) -> Option<Duration> {
+        (self.mode == GameMode::Ultra).then(|| ULTRA_TIME_LIMIT.saturating_sub(self.elapsed(now)))
+    }
+
+    /// Holes the last lock created, while their highlight is flashing on as of `now`.
+    pub fn highlighted_holes(&self, now: Instant) -> &[(usize, usize)] {
+        let flashing_on = self.new_holes_at.is_some_and(|at| {
+            let elapsed = now.saturating_duration_since(at);
+            elapsed < HOLE_HIGHLIGHT_DURATION
+                && (elapsed.as_millis() / HOLE_FLASH_INTERVAL.as_millis()).is_multiple_of(2)
+        });
+        if flashing_on { &self.new_holes } else { &[] }
+    }
+
+    /// Whether the current piece is still fading in as of `now`, so it should be
+    /// drawn dim. Always false with `spawn_fade_in` off.
+    pub fn spawn_fading(&self, now: Instant) -> bool {
+        self.config.spawn_fade_in
+            && self.current_piece.is_some()
+            && self
+                .spawned_at
+                .is_some_and(|at| now.saturating_duration_since(at) < SPAWN_FADE_DURATION)
+    }
+
+    pub fn locked_pieces(&self) -> &[LockedPiece] {
+        &self.locked_pieces
+    }
+
+    /// Longest run of consecutive line-clearing pieces this game.
+    pub fn max_combo(&self) -> u32 {
+        self.max_combo
+    }
+
+    /// Garbage lines the last locked piece would send an opponent; 0 if it cleared
+    /// nothing.
+    pub fn last_attack(&self) -> u32 {
+        self.last_attack
+    }
+
+    /// Garbage lines sent over the whole game.
+    pub fn total_attack_sent(&self) -> u32 {
+        self.total_attack_sent
+    }
+
+    /// Most back-to-back bonuses awarded in a row this game.
+    pub fn max_b2b_chain(&self) -> u32 {
+        self.max_b2b_chain
+    }
+
+    /// The level to announce if a level-up banner is still showing as of `now`.
+    pub fn level_up_banner(&self, now: Instant) -> Option<u32> {
+        self.level_up_at
+            .filter(|&(_, at)| now.saturating_duration_since(at) < LEVEL_UP_BANNER_DURATION)
+            .map(|(level, _)| level)
+    }
+
+    /// Whether the hold/info/next panels should be drawn as of `now`.
+    pub fn side_panels_visible(&self, now: Instant) -> bool {
+        !self.config.minimal_ui
+            || self.stats_revealed_at.is_some_and(|revealed| {
+                now.saturating_duration_since(revealed) < STATS_REVEAL_DURATION
+            })
     }
 
     fn compute_awarded_lines(&self, cleared_lines: u32, is_tspin: bool) -> u32 {
@@ -289,30 +1257,71 @@ impl GameState {
         }
     }
 
-    fn check_tspin(&self) -> bool {
-        // Placeholder for T-Spin detection
-        // This will be implemented to detect:
-        // - Mini T-Spins (1-2 lines)
-        // - Regular T-Spins (3 lines)
-        // - T-Spin Doubles (2 lines with proper rotation)
-        // - T-Spin Triples (3 lines with proper rotation)
-        //
-        // Detection will check:
-        // - Last move was a rotation
-        // - T piece is the current piece
-        // - T piece has 3 corners occupied by blocks
-        // - T-Spin zone is filled appropriately
-        false
-    }
+    /// Classifies `piece` as it is about to lock. Only a T piece whose last successful
+    /// move was a rotation qualifies; walls and the floor count as filled corners.
+    fn check_tspin(&self, piece: &Tetrimino) -> TSpinKind {
+        if piece.kind != TetriminoType::T || !self.last_move_was_rotation {
+            return TSpinKind::None;
+        }
 
-    fn update_level_fixed_goal(&mut self, lines_cleared: u32) {
-        // Fixed Goal System: Advance level after clearing static number of lines
-        let lines_required = self.config.lines_per_level;
+        let filled = |cx: i32, cy: i32| {
+            let x = piece.x + cx;
+            let y = piece.y + cy;
+            if x < 0 || x >= self.board.get_width() as i32 || y >= self.board.get_height() as i32 {
+                return true;
+            }
+            y >= 0 && self.board.get_cell(x as usize, y as usize).is_some()
+        };
 
+        // Corners of the T's 3x3 box, split by which way the nub points
+        let (front, back) = match piece.rotation % 4 {
+            0 => ([(0, 0), (2, 0)], [(0, 2), (2, 2)]),
+            1 => ([(2, 0), (2, 2)], [(0, 0), (0, 2)]),
+            2 => ([(0, 2), (2, 2)], [(0, 0), (2, 0)]),
+            _ => ([(0, 0), (0, 2)], [(2, 0), (2, 2)]),
+        };
+        let front_filled = front.iter().filter(|&&(x, y)| filled(x, y)).count();
+        let back_filled = back.iter().filter(|&&(x, y)| filled(x, y)).count();
+
+        match (front_filled, back_filled) {
+            (2, 1..) => TSpinKind::Full,
+            (1, 2) => TSpinKind::Mini,
+            _ => TSpinKind::None,
+        }
+    }
+
+    /// Level from the `level_thresholds` table, given total lines cleared. Levels never
+    /// go down, so a starting level above the table's reach is kept.
+    fn update_level_from_thresholds(&mut self) {
+        let thresholds = &self.config.level_thresholds;
+        let reached = thresholds
+            .iter()
+            .filter(|&&start| start <= self.lines_cleared)
+            .count() as u32;
+
+        if reached > self.level {
+            // Lines past the threshold that was just crossed count toward the next
+            self.lines_this_level = self.lines_cleared - thresholds[reached as usize - 1];
+            self.level = reached;
+        }
+        self.lines_until_next_level = thresholds
+            .iter()
+            .find(|&&start| start > self.lines_cleared)
+            .map_or(0, |start| start - self.lines_cleared);
+    }
+
+    fn update_level_fixed_goal(&mut self, lines_cleared: u32) {
+        // Fixed Goal System: Advance level after clearing a set number of lines, which
+        // may grow with each level
         if lines_cleared >= self.lines_until_next_level {
             let overflow = lines_cleared - self.lines_until_next_level;
-            self.lines_until_next_level = lines_required - overflow;
             self.level += 1;
+            let lines_required = self
+                .config
+                .lines_per_level_growth
+                .lines_for_level(self.config.lines_per_level, self.level);
+            self.lines_until_next_level = lines_required.saturating_sub(overflow);
+            self.lines_this_level = overflow;
         } else {
             self.lines_until_next_level -= lines_cleared;
         }
@@ -365,6 +1374,7 @@ impl GameState {
             if overflow > 0 {
                 self.lines_until_next_level = self.lines_until_next_level.saturating_sub(overflow);
             }
+            self.lines_this_level = overflow;
 
             self.level += 1;
         } else {
@@ -372,132 +1382,551 @@ impl GameState {
         }
     }
 
-    fn refill_bag(&mut self) {
-        // Create a new bag with all 7 tetrimino types
-        let mut new_bag = vec![
-            TetriminoType::I,
-            TetriminoType::O,
-            TetriminoType::T,
-            TetriminoType::S,
-            TetriminoType::Z,
-            TetriminoType::J,
-            TetriminoType::L,
-        ];
-
-        // Shuffle the bag randomly
-        new_bag.shuffle(&mut rng());
-        self.bag = new_bag;
-    }
-
-    fn populate_next_pieces(&mut self) {
+    /// Tops the NEXT queue up to `preview_count`, e.g. after the setting is raised.
+    pub fn populate_next_pieces(&mut self) {
         let target_count = self.config.preview_count.clamp(1, 6);
 
         while self.next_pieces.len() < target_count {
-            if self.bag.is_empty() {
-                self.refill_bag();
-            }
-
-            if let Some(piece) = self.bag.pop() {
-                self.next_pieces.push(piece);
-            } else {
-                self.refill_bag();
-                if let Some(piece) = self.bag.pop() {
-                    self.next_pieces.push(piece);
-                }
-            }
+            let piece = self.generate_piece();
+            self.next_pieces.push(piece);
         }
     }
 
+    fn generate_piece(&mut self) -> TetriminoType {
+        self.generator.next_piece(&self.config)
+    }
+
     fn get_wall_kicks(
         &self,
         piece_type: TetriminoType,
         from_rotation: usize,
         to_rotation: usize,
     ) -> Vec<(i32, i32)> {
-        // Super Rotation System wall kick tables
-        // Format: (dx, dy) offsets to try
-        match piece_type {
-            TetriminoType::I => {
-                // I piece has special wall kick data
-                match (from_rotation % 4, to_rotation % 4) {
-                    (0, 1) => vec![(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
-                    (1, 0) => vec![(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
-                    (1, 2) => vec![(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
-                    (2, 1) => vec![(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
-                    (2, 3) => vec![(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
-                    (3, 2) => vec![(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
-                    (3, 0) => vec![(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
-                    (0, 3) => vec![(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
-                    _ => vec![(0, 0)],
-                }
+        rotation::wall_kicks(
+            self.config.rotation_system,
+            piece_type,
+            from_rotation,
+            to_rotation,
+        )
+    }
+
+    /// Puts `kind` at the front of the next queue so it is the next piece spawned.
+    pub fn queue_piece_front(&mut self, kind: TetriminoType) {
+        self.next_pieces.insert(0, kind);
+    }
+
+    /// Banks the current piece and brings in the held one. With nothing held yet (the
+    /// first hold of a game) the next queued piece is spawned instead, which tops out
+    /// only if that piece can't spawn. With no piece in play, such as before the first
+    /// spawn, this does nothing unless `initial_hold` saves it for the next piece.
+    pub fn hold_piece(&mut self) {
+        if !self.config.enable_hold {
+            return;
+        }
+
+        let Some(current) = self.current_piece.take() else {
+            if self.config.initial_hold
+                && !self.is_game_over()
+                && !self.buffered_inputs.contains(&BufferedInput::Hold)
+            {
+                self.buffered_inputs.push(BufferedInput::Hold);
             }
-            TetriminoType::O => {
-                // O piece doesn't rotate, but include for completeness
-                vec![(0, 0)]
+            return;
+        };
+
+        // The banked piece's lock timer must not carry over to its replacement
+        self.cancel_lock_delay();
+        self.lock_resets = 0;
+
+        match self.held_piece.replace(current.kind) {
+            Some(held) => {
+                self.current_piece = Some(self.spawn_tetrimino(held));
+                self.spawned_at = Some(Instant::now());
+                self.last_move_was_rotation = false;
             }
-            _ => {
-                // Basic kicks for most pieces (J, L, S, Z, T)
-                match (from_rotation % 4, to_rotation % 4) {
-                    (0, 1) => vec![(0, 0), (0, -1), (-1, 0), (-1, -1)],
-                    (1, 0) => vec![(0, 0), (0, 1), (1, 0), (1, 1)],
-                    (1, 2) => vec![(0, 0), (0, -1), (1, 0), (1, -1)],
-                    (2, 1) => vec![(0, 0), (0, 1), (-1, 0), (-1, 1)],
-                    (2, 3) => vec![(0, 0), (0, -1), (-1, 0), (-1, -1)],
-                    (3, 2) => vec![(0, 0), (0, 1), (1, 0), (1, 1)],
-                    (3, 0) => vec![(0, 0), (0, -1), (1, 0), (1, -1)],
-                    (0, 3) => vec![(0, 0), (0, 1), (-1, 0), (-1, 1)],
-                    _ => vec![(0, 0)],
+            None => self.spawn_piece(),
+        }
+    }
+
+    /// Where the held piece would come to rest if it were dropped from the current
+    /// piece's column and row. `None` without a held piece, or if it doesn't fit there.
+    pub fn held_piece_landing(&self) -> Option<Tetrimino> {
+        let current = self.current_piece?;
+        let kind = self.held_piece?;
+        let piece = Tetrimino {
+            x: current.x,
+            y: current.y,
+            rotation: rotation::spawn_rotation(self.config.rotation_system, kind),
+            ..Tetrimino::new(kind)
+        };
+
+        if !self.board.is_valid_position(&piece) {
+            return None;
+        }
+        Some(self.drop_position(piece))
+    }
+
+    /// Where the current piece would land, if the ghost piece is enabled.
+    pub fn ghost_piece(&self) -> Option<Tetrimino> {
+        if !self.config.enable_ghost_piece {
+            return None;
+        }
+        self.current_piece.map(|piece| self.drop_position(piece))
+    }
+
+    pub fn toggle_ghost(&mut self) {
+        self.config.enable_ghost_piece = !self.config.enable_ghost_piece;
+    }
+
+    /// Where each of the next `PLANNING_DEPTH` pieces would land on the current board,
+    /// when the planning overlay is on.
+    pub fn planning_ghosts(&self) -> Vec<Tetrimino> {
+        if !self.config.planning_mode || !self.show_planning {
+            return Vec::new();
+        }
+        self.next_pieces
+            .iter()
+            .take(PLANNING_DEPTH)
+            .filter_map(|&kind| self.deepest_landing(kind))
+            .collect()
+    }
+
+    pub fn toggle_planning(&mut self) {
+        if self.config.planning_mode {
+            self.show_planning = !self.show_planning;
+        }
+    }
+
+    /// `kind` in spawn orientation dropped into whichever column lets it fall the
+    /// furthest, preferring the leftmost on ties. Each piece is checked against the
+    /// board on its own.
+    fn deepest_landing(&self, kind: TetriminoType) -> Option<Tetrimino> {
+        let spawn = self.spawn_tetrimino(kind);
+        (0..self.board.get_width() as i32)
+            .map(|x| Tetrimino { x, ..spawn })
+            .filter(|piece| self.board.is_valid_position(piece))
+            .map(|piece| self.drop_position(piece))
+            .reduce(|best, piece| if piece.y > best.y { piece } else { best })
+    }
+
+    /// `piece` moved straight down as far as the board allows.
+    fn drop_position(&self, piece: Tetrimino) -> Tetrimino {
+        Tetrimino {
+            y: self.board.drop_row(&piece),
+            ..piece
+        }
+    }
+
+    /// Moves the current piece to its landing row, the same as stepping it down one
+    /// row at a time, and returns how many rows it fell.
+    fn drop_current_piece(&mut self) -> u32 {
+        let Some(piece) = self.current_piece else {
+            return 0;
+        };
+        let landed = self.drop_position(piece);
+        let rows = (landed.y - piece.y) as u32;
+        if rows > 0 {
+            self.current_piece = Some(landed);
+            self.cancel_lock_delay();
+            self.last_move_was_rotation = false;
+        }
+        rows
+    }
+
+    pub fn is_line_clear_animation_active(&self) -> bool {
+        self.line_clear_animation.as_ref().is_some_and(|anim| {
+            anim.start_time.elapsed() < clear_animation_duration(self.config.clear_blinks)
+        })
+    }
+
+    pub fn should_show_cleared_rows(&self) -> bool {
+        self.line_clear_animation.as_ref().is_some_and(|anim| {
+            cleared_rows_visible(anim.start_time.elapsed(), self.config.clear_blinks)
+        })
+    }
+
+    pub fn toggle_help(&mut self) {
+        self.show_help = !self.show_help;
+        self.sync_clock(Instant::now());
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.sync_clock(Instant::now());
+    }
+
+    /// Play is frozen: paused or showing help.
+    /// Marks the settings menu as open or closed; play stays frozen while it is up.
+    pub fn set_settings_open(&mut self, open: bool) {
+        self.settings_open = open;
+        self.sync_clock(Instant::now());
+    }
+
+    fn is_suspended(&self) -> bool {
+        self.paused || self.show_help || self.settings_open
+    }
+
+    /// Total time play has been frozen, including any stop still going on as of `now`.
+    /// The game loop uses it to hold back its gravity timer.
+    pub fn paused_time(&self, now: Instant) -> Duration {
+        self.paused_time
+            + self.paused_at.map_or(Duration::ZERO, |paused_at| {
+                now.saturating_duration_since(paused_at)
+            })
+    }
+
+    /// Stops the clocks as of `now` when play becomes frozen, and when it resumes,
+    /// pushes every running timer forward by the time spent stopped so none of them
+    /// counts it: the game clock, the level's gravity ramp, the lock delay, the spawn
+    /// delay and the clear animation.
+    fn sync_clock(&mut self, now: Instant) {
+        match (self.is_suspended(), self.paused_at) {
+            (true, None) => self.paused_at = Some(now),
+            (false, Some(paused_at)) => {
+                self.paused_at = None;
+                let stopped = now.saturating_duration_since(paused_at);
+                self.paused_time += stopped;
+                self.level_started_at += stopped;
+                if let Some(started) = &mut self.delay_started {
+                    *started += stopped;
+                }
+                if let Phase::LockDelay { started } | Phase::SpawnDelay { started } =
+                    &mut self.phase
+                {
+                    *started += stopped;
+                }
+                if let Some(animation) = &mut self.line_clear_animation {
+                    animation.start_time += stopped;
                 }
             }
+            _ => {}
         }
     }
 
-    pub fn hold_piece(&mut self) {
-        if !self.config.enable_hold {
-            return;
+    pub fn toggle_debug(&mut self) {
+        self.show_debug = !self.show_debug;
+    }
+
+    /// A stable hash of everything a replay has to reproduce: the board, score,
+    /// level, lines, the active and held pieces, and the next queue.
+    pub fn state_checksum(&self) -> u64 {
+        let mut checksum = Checksum::new();
+        checksum.write_u64(self.board.checksum());
+        checksum.write_u64(self.score);
+        checksum.write_u64(self.level as u64);
+        checksum.write_u64(self.lines_cleared as u64);
+        checksum.write_kind(self.current_piece.map(|piece| piece.kind));
+        if let Some(piece) = self.current_piece {
+            checksum.write_u64(piece.x as u64);
+            checksum.write_u64(piece.y as u64);
+            checksum.write_u64(piece.rotation as u64);
+        }
+        checksum.write_kind(self.held_piece);
+        for &kind in &self.next_pieces {
+            checksum.write_kind(Some(kind));
         }
+        checksum.finish()
+    }
 
-        if let Some(current) = self.current_piece.take() {
-            if let Some(held) = self.held_piece {
-                let new_piece = Tetrimino::new(held);
-                self.current_piece = Some(new_piece);
-            } else {
-                self.spawn_piece();
+    pub fn queue_snapshot(&self) -> QueueSnapshot {
+        QueueSnapshot {
+            next_pieces: self.next_pieces.clone(),
+            held_piece: self.held_piece,
+            bag: self.remaining_bag(),
+        }
+    }
+
+    /// Pieces left in the current bag, in the order they will be dealt.
+    pub fn remaining_bag(&self) -> Vec<TetriminoType> {
+        // The bag is dealt from the back
+        self.generator.bag.iter().rev().copied().collect()
+    }
+
+    /// The current piece (if any) followed by the next `depth` pieces, for a bot
+    /// planning past `preview_count`. Pieces beyond the queue come from a fork of the
+    /// generator, so peeking leaves the real queue and bag untouched.
+    pub fn upcoming(&self, depth: usize) -> Vec<TetriminoType> {
+        let mut lookahead = self.generator.clone();
+        let beyond_queue = std::iter::repeat_with(|| lookahead.next_piece(&self.config));
+        self.current_piece
+            .map(|piece| piece.kind)
+            .into_iter()
+            .chain(
+                self.next_pieces
+                    .iter()
+                    .copied()
+                    .chain(beyond_queue)
+                    .take(depth),
+            )
+            .collect()
+    }
+
+    /// Spends a life after a top-out: wipes the top of the stack so play can go on.
+    /// Returns false, leaving the board alone, when that was the last life.
+    pub fn use_life(&mut self) -> bool {
+        self.lives = self.lives.saturating_sub(1);
+        if self.lives == 0 {
+            return false;
+        }
+
+        self.wipe_board(BoardWipe::TopRows(LIFE_CLEAR_ROWS));
+        self.cancel_lock_delay();
+        self.combo_count = 0;
+        self.back_to_back_active = false;
+        self.b2b_chain = 0;
+        true
+    }
+
+    /// Advances play by one frame as of `now`, according to the current [`Phase`]:
+    /// a falling piece moves down a row if `gravity_due`, an expired lock delay locks,
+    /// a finished clear animation clears its rows, and an elapsed spawn delay brings in
+    /// the next piece. Gravity waits while no piece is in play. Frozen while paused or
+    /// showing help. Returns true if a gravity step was taken, so the caller can
+    /// restart its gravity timer.
+    pub fn tick(&mut self, now: Instant, gravity_due: bool) -> bool {
+        if self.is_suspended() {
+            return false;
+        }
+
+        self.check_time_limit(now);
+        match self.phase {
+            Phase::Falling | Phase::LockDelay { .. } => {
+                if gravity_due && !self.move_piece(0, 1) {
+                    self.start_lock_delay();
+                }
+                // A piece slid off its ledge during the delay falls instead of locking
+                if self.lock_delay_expired(now) && !self.move_piece(0, 1) {
+                    self.lock_current_piece();
+                }
+                gravity_due
+            }
+            Phase::ClearAnimation => {
+                if self.line_clear_animation_finished(now) {
+                    self.complete_line_clear();
+                }
+                false
+            }
+            Phase::SpawnDelay { started } => {
+                if now.saturating_duration_since(started)
+                    >= Duration::from_millis(self.config.spawn_delay_ms)
+                {
+                    self.spawn_piece();
+                }
+                false
             }
-            self.held_piece = Some(current.kind);
+            Phase::GameOver => false,
         }
     }
 
-    pub fn is_line_clear_animation_active(&self) -> bool {
-        if let Some(ref anim) = self.line_clear_animation {
-            let elapsed = anim.start_time.elapsed().as_millis() as u64;
-            let duration_ms = anim.total_lines as u64 * 500;
-            elapsed < duration_ms
-        } else {
-            false
+    fn line_clear_animation_finished(&self, now: Instant) -> bool {
+        self.line_clear_animation.as_ref().is_none_or(|anim| {
+            now.saturating_duration_since(anim.start_time)
+                >= clear_animation_duration(self.config.clear_blinks)
+        })
+    }
+
+    /// Ends an Ultra game once its time limit has passed as of `now`.
+    pub fn check_time_limit(&mut self, now: Instant) {
+        if self.time_remaining(now) == Some(Duration::ZERO) {
+            self.end_game();
         }
     }
 
-    pub fn should_show_cleared_rows(&self) -> bool {
-        if let Some(ref anim) = self.line_clear_animation {
-            let elapsed = anim.start_time.elapsed().as_millis() as u64;
-            let blink_interval = 250;
-            let blink_num = elapsed / blink_interval;
-            blink_num.is_multiple_of(2)
+    /// Keeps play going after a top-out if possible: Zen clears the whole board,
+    /// other modes spend a life.
+    fn rescue_top_out(&mut self) -> bool {
+        if self.mode == GameMode::Zen {
+            self.wipe_board(BoardWipe::All);
+            self.cancel_lock_delay();
+            return true;
+        }
+        self.use_life()
+    }
+
+    /// Applies `wipe` and records it against the last locked piece, so the replay
+    /// viewer rebuilds the same board.
+    fn wipe_board(&mut self, wipe: BoardWipe) {
+        wipe.apply(&mut self.board);
+        if let Some(last) = self.locked_pieces.last_mut() {
+            last.wipes_after.push(wipe);
+        }
+    }
+
+    /// Ends the game, emitting a single `GameOver` event.
+    pub fn end_game(&mut self) {
+        if !self.is_game_over() {
+            self.set_phase(Phase::GameOver);
+            self.finished_at = Some(Instant::now());
+            self.events.push(GameEvent::GameOver { score: self.score });
+        }
+    }
+
+    /// Fraction of board cells that are filled, from 0.0 (empty) to 1.0 (full).
+    pub fn board_coverage(&self) -> f64 {
+        let width = self.board.get_width();
+        let height = self.board.get_height();
+        let total = width * height;
+        if total == 0 {
+            return 0.0;
+        }
+
+        let filled = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.board.get_cell(x, y).is_some())
+            .count();
+        filled as f64 / total as f64
+    }
+
+    /// Final stats for tooling; the duration runs until game over, or until now if the
+    /// game is still going.
+    pub fn result(&self) -> GameResult {
+        let duration = self.elapsed(Instant::now());
+        let seconds = duration.as_secs_f64();
+        let pps = if seconds > 0.0 {
+            self.pieces_placed as f64 / seconds
         } else {
-            false
+            0.0
+        };
+
+        GameResult {
+            mode: self.mode.label(),
+            seed: self.seed,
+            score: self.score,
+            level: self.level,
+            lines: self.lines_cleared,
+            duration_ms: duration.as_millis() as u64,
+            pps,
+            finesse_faults: None,
+            max_combo: self.max_combo,
         }
     }
 
-    pub fn toggle_help(&mut self) {
-        self.show_help = !self.show_help;
+    /// Builds a plain-text summary of the board and final stats for sharing.
+    pub fn export_summary(&self) -> String {
+        let elapsed = self.elapsed(Instant::now()).as_secs();
+        let width = self.board.get_width();
+        let mut summary = String::new();
+
+        // Writing to a String cannot fail
+        let _ = writeln!(summary, "TETRIS RESULT");
+        let _ = writeln!(summary, "Mode:  {}", self.mode.label());
+        let _ = writeln!(summary, "Score: {}", self.score);
+        let _ = writeln!(summary, "Level: {}", self.level);
+        let _ = writeln!(summary, "Lines: {}", self.lines_cleared);
+        let _ = writeln!(summary, "Time:  {:02}:{:02}", elapsed / 60, elapsed % 60);
+        let _ = writeln!(summary, "Seed:  {}", self.seed);
+
+        let edge = format!("+{}+", "-".repeat(width));
+        let _ = writeln!(summary, "{}", edge);
+        for y in 0..self.board.get_height() {
+            let row: String = (0..width)
+                .map(|x| Self::cell_char(self.board.get_cell(x, y)))
+                .collect();
+            let _ = writeln!(summary, "|{}|", row);
+        }
+        let _ = writeln!(summary, "{}", edge);
+
+        summary
+    }
+
+    fn cell_char(cell: Option<TetriminoType>) -> char {
+        cell.map_or('.', TetriminoType::to_char)
+    }
+
+    /// Takes all events queued since the last call.
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Places each `(kind, x, rotation)` at the spawn row, drops it straight down and
+    /// locks it whatever `hard_drop_locks` says, resolving line clears immediately
+    /// instead of waiting on the animation. Stops early if a placement tops out.
+    pub fn simulate_placements(&mut self, placements: &[(TetriminoType, i32, usize)]) -> SimResult {
+        for &(kind, x, rotation) in placements {
+            if self.is_game_over() {
+                break;
+            }
+
+            let piece = Tetrimino {
+                x,
+                rotation,
+                ..self.spawn_tetrimino(kind)
+            };
+            if !self.board.is_valid_position(&piece) {
+                self.end_game();
+                break;
+            }
+
+            self.current_piece = Some(piece);
+            self.drop_current_piece();
+            self.lock_current_piece();
+            while self.is_line_clear_pending() {
+                self.complete_line_clear();
+            }
+        }
+
+        SimResult {
+            score: self.score,
+            lines_cleared: self.lines_cleared,
+            game_over: self.is_game_over(),
+        }
     }
 }
 
+/// Total length of the line clear animation for `blinks` blinks.
+fn clear_animation_duration(blinks: u32) -> Duration {
+    CLEAR_BLINK_INTERVAL * 2 * blinks
+}
+
+/// Whether cleared rows are in the shown half of a blink `elapsed` into the animation.
+/// Each blink shows the rows and then hides them; nothing shows once all have played.
+fn cleared_rows_visible(elapsed: Duration, blinks: u32) -> bool {
+    elapsed < clear_animation_duration(blinks)
+        && (elapsed.as_millis() / CLEAR_BLINK_INTERVAL.as_millis()).is_multiple_of(2)
+}
+
+/// Garbage sent for clearing `lines` rows: 0/1/2/4 for a single through a Tetris, or
+/// 2/4/6 for a T-Spin single through triple (a Mini sends one less per line cleared,
+/// bottoming out at 0). Back-to-back, combo and perfect clear bonuses add on top.
+fn attack_for(
+    lines: u32,
+    tspin: TSpinKind,
+    back_to_back: bool,
+    combo: u32,
+    perfect_clear: bool,
+) -> u32 {
+    let base = match (tspin, lines) {
+        (_, 0) => 0,
+        (TSpinKind::Full, lines) => lines * 2,
+        (TSpinKind::Mini, lines) => lines - 1,
+        (TSpinKind::None, 4..) => 4,
+        (TSpinKind::None, lines) => lines - 1,
+    };
+    let combo_bonus = COMBO_ATTACK[(combo as usize).min(COMBO_ATTACK.len() - 1)];
+    base + combo_bonus
+        + if back_to_back { BACK_TO_BACK_ATTACK } else { 0 }
+        + if perfect_clear {
+            PERFECT_CLEAR_ATTACK
+        } else {
+            0
+        }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::config::GameConfig;
+    use super::{
+        BACK_TO_BACK_ATTACK, COMBO_ATTACK, GOOD_OPENERS, GameEvent, GameMode,
+        HARD_DROP_CONFIRM_WINDOW, HOLE_FLASH_INTERVAL, HOLE_HIGHLIGHT_DURATION, MoveResult,
+        PERFECT_CLEAR_ATTACK, Phase, QueueSnapshot, SPAWN_FADE_DURATION, TSpinKind,
+    };
+    use crate::config::{
+        BorderStyle, CONFIG_VERSION, ClearGravity, ConfigError, GameConfig, GhostStyle,
+        LevelGrowth, Randomizer, RotationSystem, ScoreConfig,
+    };
     use crate::tetrimino::{Tetrimino, TetriminoType};
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant};
 
     fn make_test_config(enable_hold: bool) -> GameConfig {
         GameConfig {
@@ -512,7 +1941,87 @@ mod tests {
             preview_count: 3,
             das_delay: 250,
             das_repeat: 50,
+            show_grid: false,
+            border_style: BorderStyle::Square,
+            ghost_style: GhostStyle::Dimmed,
+            clear_gravity: ClearGravity::Naive,
+            randomizer: Randomizer::SevenBag,
+            seed: None,
+            practice_mode: false,
+            spawn_kick: false,
+            adaptive_gravity: false,
+            lock_delay_ms: 500,
+            hard_drop_locks: true,
+            classic_rotation: false,
+            scoring: ScoreConfig::default(),
+            assist_hold_ghost: false,
+            show_fps: false,
+            lives: 1,
+            level_thresholds: Vec::new(),
+            freeze_level: false,
+            show_heights: false,
+            audio_device: None,
+            master_volume: 1.0,
+            music_volume: 0.5,
+            sfx_volume: 1.0,
+            music_follows_level: false,
+            level_music: BTreeMap::new(),
+            initial_queue: Vec::new(),
+            soft_drop_repeat: 20,
+            minimal_ui: false,
+            bag_repeats: 1,
+            lines_per_level_growth: LevelGrowth::default(),
+            planning_mode: false,
+            level_up_chime: false,
+            spawn_row: 0,
+            height_gradient: false,
+            gravity_ramp: 0.0,
+            highlight_holes: false,
+            version: CONFIG_VERSION,
+            rotation_assist: false,
+            clear_blinks: 2,
+            audio_latency_ms: None,
+            rank_thresholds: GameConfig::default().rank_thresholds,
+            ghost_proximity: None,
+            kill_screen: false,
+            kill_screen_level: 29,
+            das_initial_tap: true,
+            clear_tones: false,
+            max_combo: None,
+            first_piece_guaranteed_good: false,
+            safe_hard_drop: false,
+            cell_width: 2,
+            rotation_system: RotationSystem::Srs,
+            poll_timeout_ms: 16,
+            spawn_delay_ms: 0,
+            spawn_fade_in: false,
+            preview_spacing: 1,
+            clock_counts_delays: true,
+            custom_bag: Vec::new(),
+            initial_hold: false,
+        }
+    }
+
+    fn generate_sequence(randomizer: Randomizer, seed: u64, count: usize) -> Vec<TetriminoType> {
+        let config = GameConfig {
+            randomizer,
+            seed: Some(seed),
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        let mut sequence = state.next_pieces.clone();
+        while sequence.len() < count {
+            sequence.push(state.generate_piece());
         }
+        sequence.truncate(count);
+        sequence
+    }
+
+    fn has_repeat(window: &[TetriminoType]) -> bool {
+        window
+            .iter()
+            .enumerate()
+            .any(|(i, piece)| window[i + 1..].contains(piece))
     }
 
     #[test]
@@ -566,6 +2075,128 @@ mod tests {
         assert_eq!(state.current_piece.unwrap().kind, TetriminoType::I);
     }
 
+    #[test]
+    fn test_first_hold_of_game_pulls_next_piece() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        state.spawn_piece();
+        let first = state.current_piece.unwrap().kind;
+        let queued = state.next_pieces.clone();
+
+        state.hold_piece();
+
+        assert!(!state.is_game_over());
+        assert_eq!(state.held_piece, Some(first));
+        assert_eq!(state.current_piece.unwrap().kind, queued[0]);
+        assert_eq!(state.next_pieces[..queued.len() - 1], queued[1..]);
+        assert_eq!(state.next_pieces.len(), queued.len());
+    }
+
+    #[test]
+    fn test_first_hold_on_near_full_board_keeps_playing() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        // Everything below the top three rows is filled except one well column
+        for y in 3..20 {
+            for x in 0..9 {
+                state.board.cells_mut()[y][x] = Some(TetriminoType::J);
+            }
+        }
+        state.next_pieces[0] = TetriminoType::I;
+        state.current_piece = Some(Tetrimino::new(TetriminoType::T));
+
+        state.hold_piece();
+
+        assert!(!state.is_game_over());
+        assert_eq!(state.held_piece, Some(TetriminoType::T));
+        assert_eq!(state.current_piece.unwrap().kind, TetriminoType::I);
+    }
+
+    #[test]
+    fn test_first_hold_into_blocked_spawn_tops_out() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        state.current_piece = Some(Tetrimino {
+            y: 5,
+            ..Tetrimino::new(TetriminoType::O)
+        });
+        block_t_spawn(&mut state);
+
+        state.hold_piece();
+
+        assert!(state.is_game_over());
+        assert_eq!(state.held_piece, Some(TetriminoType::O));
+    }
+
+    #[test]
+    fn test_hold_before_first_spawn_without_ihs_does_nothing() {
+        let mut state = super::GameState::new(make_test_config(true));
+        let first = state.next_pieces[0];
+
+        state.hold_piece();
+        assert_eq!(state.held_piece, None);
+        assert_eq!(state.current_piece, None);
+        assert!(state.buffered_inputs.is_empty());
+
+        state.spawn_piece();
+        assert_eq!(state.current_piece.unwrap().kind, first);
+        assert_eq!(state.held_piece, None);
+    }
+
+    #[test]
+    fn test_hold_before_first_spawn_with_ihs_holds_the_first_piece() {
+        let config = GameConfig {
+            initial_hold: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        let (first, second) = (state.next_pieces[0], state.next_pieces[1]);
+
+        // Pressing twice still holds only once
+        state.hold_piece();
+        state.hold_piece();
+        assert_eq!(state.held_piece, None);
+        assert_eq!(state.current_piece, None);
+
+        state.spawn_piece();
+        assert_eq!(state.held_piece, Some(first));
+        assert_eq!(state.current_piece.unwrap().kind, second);
+        assert!(state.buffered_inputs.is_empty());
+    }
+
+    #[test]
+    fn test_ihs_during_line_clear_holds_the_next_piece() {
+        let config = GameConfig {
+            initial_hold: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        state.spawn_piece();
+        state
+            .board
+            .fill_region(0, 19, 9, 19, Some(TetriminoType::J));
+        state.hard_drop();
+        assert!(state.is_line_clear_pending());
+
+        let (next, after) = (state.next_pieces[0], state.next_pieces[1]);
+        state.hold_piece();
+        state.complete_line_clear();
+        assert_eq!(state.held_piece, Some(next));
+        assert_eq!(state.current_piece.unwrap().kind, after);
+    }
+
+    #[test]
+    fn test_hold_clears_running_lock_delay() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        state.spawn_piece();
+        state.start_lock_delay();
+
+        state.hold_piece();
+
+        assert!(!state.is_lock_delay_active());
+    }
+
     #[test]
     fn test_hold_piece_with_held_swaps() {
         let config = make_test_config(true);
@@ -702,7 +2333,7 @@ mod tests {
         assert_eq!(state.score, 0);
         assert_eq!(state.level, 1);
         assert_eq!(state.lines_cleared, 0);
-        assert!(!state.game_over);
+        assert!(!state.is_game_over());
         assert!(state.current_piece.is_none());
         assert_eq!(state.next_pieces.len(), 3);
         assert!(state.held_piece.is_none());
@@ -722,6 +2353,64 @@ mod tests {
             preview_count: 5,
             das_delay: 200,
             das_repeat: 30,
+            show_grid: true,
+            border_style: BorderStyle::Rounded,
+            ghost_style: GhostStyle::Outline,
+            clear_gravity: ClearGravity::Cascade,
+            randomizer: Randomizer::TgmHistory,
+            seed: Some(42),
+            practice_mode: true,
+            spawn_kick: true,
+            adaptive_gravity: true,
+            lock_delay_ms: 300,
+            hard_drop_locks: false,
+            classic_rotation: true,
+            scoring: ScoreConfig::default(),
+            assist_hold_ghost: true,
+            show_fps: true,
+            lives: 3,
+            level_thresholds: Vec::new(),
+            freeze_level: true,
+            show_heights: true,
+            audio_device: Some("USB Headset".to_string()),
+            master_volume: 0.8,
+            music_volume: 0.6,
+            sfx_volume: 0.7,
+            music_follows_level: true,
+            level_music: BTreeMap::from([(10, PathBuf::from("fast.wav"))]),
+            initial_queue: vec![TetriminoType::T],
+            soft_drop_repeat: 10,
+            minimal_ui: false,
+            bag_repeats: 2,
+            lines_per_level_growth: LevelGrowth::None,
+            planning_mode: true,
+            level_up_chime: true,
+            spawn_row: 0,
+            height_gradient: true,
+            gravity_ramp: 0.5,
+            highlight_holes: true,
+            version: CONFIG_VERSION,
+            rotation_assist: true,
+            clear_blinks: 3,
+            audio_latency_ms: Some(20),
+            rank_thresholds: GameConfig::default().rank_thresholds,
+            ghost_proximity: Some(4),
+            kill_screen: true,
+            kill_screen_level: 15,
+            das_initial_tap: false,
+            clear_tones: true,
+            max_combo: Some(10),
+            first_piece_guaranteed_good: true,
+            safe_hard_drop: true,
+            cell_width: 1,
+            rotation_system: RotationSystem::Ars,
+            poll_timeout_ms: 50,
+            spawn_delay_ms: 100,
+            spawn_fade_in: true,
+            preview_spacing: 0,
+            clock_counts_delays: false,
+            custom_bag: vec![TetriminoType::I, TetriminoType::T],
+            initial_hold: true,
         };
         let state = super::GameState::new(config);
 
@@ -745,7 +2434,71 @@ mod tests {
     }
 
     #[test]
-    fn test_spawn_piece_game_over() {
+    fn test_empty_custom_bag_is_rejected() {
+        let config = GameConfig {
+            randomizer: Randomizer::Custom,
+            custom_bag: Vec::new(),
+            ..make_test_config(true)
+        };
+        assert!(matches!(
+            super::GameState::try_new(config.clone()),
+            Err(ConfigError::Validation(_))
+        ));
+
+        // `new` falls back to the 7-bag rather than leaving nothing to spawn
+        let mut state = super::GameState::new(config);
+        assert_eq!(state.config.randomizer, Randomizer::SevenBag);
+        for _ in 0..10 {
+            state.spawn_piece();
+            assert!(state.current_piece.is_some());
+            state.current_piece = None;
+        }
+    }
+
+    #[test]
+    fn test_custom_bag_deals_only_its_pieces() {
+        let config = GameConfig {
+            randomizer: Randomizer::Custom,
+            custom_bag: vec![TetriminoType::I, TetriminoType::I, TetriminoType::T],
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::try_new(config).unwrap();
+        // Start on a fresh bag so the dealt pieces line up with bag boundaries
+        state.generator.bag.clear();
+        let dealt: Vec<_> = (0..9).map(|_| state.generate_piece()).collect();
+
+        for bag in dealt.chunks(3) {
+            let count = |kind| bag.iter().filter(|&&dealt| dealt == kind).count();
+            assert_eq!((count(TetriminoType::I), count(TetriminoType::T)), (2, 1));
+        }
+    }
+
+    #[test]
+    fn test_initial_queue_spawns_in_order() {
+        let queue = vec![
+            TetriminoType::I,
+            TetriminoType::I,
+            TetriminoType::Z,
+            TetriminoType::O,
+            TetriminoType::T,
+        ];
+        let config = GameConfig {
+            initial_queue: queue.clone(),
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+
+        for expected in queue {
+            state.spawn_piece();
+            assert_eq!(state.current_piece.unwrap().kind, expected);
+        }
+
+        // The randomizer takes over once the opening queue runs out
+        assert_eq!(state.next_pieces.len(), 3);
+    }
+
+    #[test]
+    fn test_spawn_piece_game_over() {
         let config = make_test_config(true);
         let mut new_state = super::GameState::new(config);
 
@@ -757,7 +2510,277 @@ mod tests {
 
         new_state.spawn_piece();
 
-        assert!(new_state.game_over);
+        assert!(new_state.is_game_over());
+    }
+
+    fn fill_board(state: &mut super::GameState) {
+        state
+            .board
+            .fill_region(0, 0, usize::MAX, usize::MAX, Some(TetriminoType::I));
+    }
+
+    #[test]
+    fn test_topout_with_lives_continues_then_ends() {
+        let config = GameConfig {
+            lives: 2,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+
+        fill_board(&mut state);
+        state.spawn_piece();
+
+        assert!(!state.is_game_over());
+        assert_eq!(state.lives, 1);
+        assert!(state.board.get_cell(0, 0).is_none());
+        assert!(state.board.get_cell(0, 19).is_some());
+
+        fill_board(&mut state);
+        state.spawn_piece();
+
+        assert!(state.is_game_over());
+        assert_eq!(state.lives, 0);
+    }
+
+    #[test]
+    fn test_use_life_on_last_life_keeps_board() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        fill_board(&mut state);
+
+        assert!(!state.use_life());
+        assert!(state.board.get_cell(0, 0).is_some());
+    }
+
+    fn block_t_spawn(state: &mut super::GameState) {
+        // A T at x = 0 needs (0, 1); one column over it only needs x >= 1
+        state.board.cells_mut()[1][0] = Some(TetriminoType::I);
+        state.queue_piece_front(TetriminoType::T);
+    }
+
+    #[test]
+    fn test_spawn_piece_allows_stack_at_visible_top() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        for y in 0..20 {
+            state.board.cells_mut()[y][9] = Some(TetriminoType::I);
+        }
+        state.queue_piece_front(TetriminoType::T);
+
+        state.spawn_piece();
+
+        assert!(!state.is_game_over());
+    }
+
+    #[test]
+    fn test_spawn_kick_rescues_blocked_spawn() {
+        let config = GameConfig {
+            spawn_kick: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        block_t_spawn(&mut state);
+
+        state.spawn_piece();
+
+        assert!(!state.is_game_over());
+        let piece = state.current_piece.unwrap();
+        assert_eq!(piece.kind, TetriminoType::T);
+        assert_eq!(piece.x, 1);
+        assert!(state.board.can_spawn(&piece));
+    }
+
+    #[test]
+    fn test_spawn_row_sets_spawn_height() {
+        let config = GameConfig {
+            spawn_row: 4,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        state.spawn_piece();
+        assert_eq!(state.current_piece.unwrap().y, 4);
+    }
+
+    #[test]
+    fn test_negative_spawn_row_starts_above_board() {
+        let config = GameConfig {
+            spawn_row: -1,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        state.queue_piece_front(TetriminoType::T);
+        state.spawn_piece();
+
+        let piece = state.current_piece.unwrap();
+        assert_eq!(piece.y, -1);
+        assert!(!state.is_game_over());
+        assert!(state.board.is_valid_position(&piece));
+
+        // The piece can still fall and move normally
+        assert!(state.move_piece(0, 1));
+        assert!(state.move_piece(1, 0));
+    }
+
+    #[test]
+    fn test_piece_above_board_falls_onto_it() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        state.current_piece = Some(Tetrimino {
+            kind: TetriminoType::I,
+            x: 0,
+            y: -3,
+            rotation: 1,
+        });
+
+        assert!(state.move_piece(0, 1));
+        state.hard_drop();
+
+        assert!(!state.is_game_over());
+        assert!((16..20).all(|y| state.board.get_cell(2, y).is_some()));
+    }
+
+    #[test]
+    fn test_locking_entirely_above_board_tops_out() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        state.current_piece = Some(Tetrimino {
+            kind: TetriminoType::O,
+            x: 4,
+            y: -2,
+            rotation: 0,
+        });
+
+        state.lock_current_piece();
+
+        assert!(state.is_game_over());
+    }
+
+    #[test]
+    fn test_locking_partly_above_board_keeps_playing() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        for y in 0..20 {
+            state.board.cells_mut()[y][4] = Some(TetriminoType::I);
+        }
+        state.current_piece = Some(Tetrimino {
+            kind: TetriminoType::O,
+            x: 5,
+            y: -1,
+            rotation: 0,
+        });
+
+        state.lock_current_piece();
+
+        assert!(!state.is_game_over());
+        assert_eq!(state.board.get_cell(5, 0), Some(TetriminoType::O));
+    }
+
+    #[test]
+    fn test_sprint_ends_at_forty_lines() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::with_mode(config, GameMode::Sprint);
+        state.lines_cleared = 39;
+
+        for x in 0..10 {
+            state.board.cells_mut()[19][x] = Some(TetriminoType::I);
+        }
+        state.phase = Phase::ClearAnimation;
+        state.complete_line_clear();
+
+        assert_eq!(state.lines_cleared, 40);
+        assert!(state.is_game_over());
+        assert_eq!(state.current_piece, None);
+    }
+
+    #[test]
+    fn test_marathon_keeps_going_past_forty_lines() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        assert_eq!(state.mode, GameMode::Marathon);
+        state.lines_cleared = 39;
+
+        for x in 0..10 {
+            state.board.cells_mut()[19][x] = Some(TetriminoType::I);
+        }
+        state.phase = Phase::ClearAnimation;
+        state.complete_line_clear();
+
+        assert!(!state.is_game_over());
+    }
+
+    #[test]
+    fn test_ultra_ends_after_time_limit() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::with_mode(config.clone(), GameMode::Ultra);
+        let start = state.started_at;
+
+        state.check_time_limit(start + Duration::from_secs(119));
+        assert!(!state.is_game_over());
+        state.check_time_limit(start + Duration::from_secs(120));
+        assert!(state.is_game_over());
+
+        let mut marathon = super::GameState::new(config);
+        marathon.check_time_limit(start + Duration::from_secs(600));
+        assert!(!marathon.is_game_over());
+    }
+
+    #[test]
+    fn test_zen_clears_board_instead_of_topping_out() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::with_mode(config, GameMode::Zen);
+        block_t_spawn(&mut state);
+        state.board.cells_mut()[19][5] = Some(TetriminoType::O);
+
+        state.spawn_piece();
+
+        assert!(!state.is_game_over());
+        assert!(state.board.cells().iter().flatten().all(Option::is_none));
+        assert_eq!(state.current_piece.unwrap().kind, TetriminoType::T);
+    }
+
+    #[test]
+    fn test_spawn_kick_disabled_tops_out() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        block_t_spawn(&mut state);
+
+        state.spawn_piece();
+
+        assert!(state.is_game_over());
+        assert_eq!(state.blocking_cells, vec![(0, 1)]);
+    }
+
+    #[test]
+    fn test_blocking_cells_lists_every_overlap() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        for x in 0..10 {
+            state.board.cells_mut()[1][x] = Some(TetriminoType::I);
+        }
+        state.queue_piece_front(TetriminoType::T);
+
+        assert!(state.blocking_cells.is_empty());
+        state.spawn_piece();
+
+        assert!(state.is_game_over());
+        assert_eq!(state.blocking_cells, vec![(0, 1), (1, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn test_spawn_kick_fails_when_no_offset_fits() {
+        let config = GameConfig {
+            spawn_kick: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        for x in 0..10 {
+            state.board.cells_mut()[1][x] = Some(TetriminoType::I);
+        }
+        state.queue_piece_front(TetriminoType::T);
+
+        state.spawn_piece();
+
+        assert!(state.is_game_over());
     }
 
     #[test]
@@ -806,7 +2829,7 @@ mod tests {
         state.board.lock_tetromino(&piece);
 
         state.spawn_piece();
-        if state.game_over {
+        if state.is_game_over() {
             return;
         }
         if state.current_piece.is_none() {
@@ -820,6 +2843,68 @@ mod tests {
         assert!(!moved);
     }
 
+    #[test]
+    fn test_try_move_moved() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        state.current_piece = Some(Tetrimino::new(TetriminoType::O));
+
+        assert_eq!(state.try_move(1, 0), MoveResult::Moved);
+        assert_eq!(state.try_move(0, 1), MoveResult::Moved);
+        assert_eq!(state.current_piece.unwrap().x, 1);
+        assert_eq!(state.current_piece.unwrap().y, 1);
+    }
+
+    #[test]
+    fn test_try_move_blocked_by_wall() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        state.current_piece = Some(Tetrimino::new(TetriminoType::O));
+
+        assert_eq!(state.try_move(-1, 0), MoveResult::BlockedHorizontal);
+        assert_eq!(state.current_piece.unwrap().x, 0);
+    }
+
+    #[test]
+    fn test_try_move_blocked_by_stack_sideways() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        state.board.cells_mut()[0][2] = Some(TetriminoType::I);
+        state.current_piece = Some(Tetrimino::new(TetriminoType::O));
+
+        assert_eq!(state.try_move(1, 0), MoveResult::BlockedHorizontal);
+    }
+
+    #[test]
+    fn test_try_move_blocked_by_floor() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        let mut piece = Tetrimino::new(TetriminoType::O);
+        piece.y = 18;
+        state.current_piece = Some(piece);
+
+        assert_eq!(state.try_move(0, 1), MoveResult::BlockedByFloor);
+        assert_eq!(state.current_piece.unwrap().y, 18);
+    }
+
+    #[test]
+    fn test_try_move_blocked_by_stack_below() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        state.board.cells_mut()[2][0] = Some(TetriminoType::I);
+        state.current_piece = Some(Tetrimino::new(TetriminoType::O));
+
+        assert_eq!(state.try_move(0, 1), MoveResult::BlockedByFloor);
+    }
+
+    #[test]
+    fn test_try_move_no_piece() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        assert_eq!(state.try_move(1, 0), MoveResult::NoPiece);
+    }
+
     #[test]
     fn test_rotate_piece_clockwise() {
         let config = make_test_config(true);
@@ -845,50 +2930,495 @@ mod tests {
     }
 
     #[test]
-    fn test_rotate_piece_no_current_piece() {
+    fn test_move_to_wall_left_and_right() {
         let config = make_test_config(true);
         let mut state = super::GameState::new(config);
+        state.current_piece = Some(Tetrimino {
+            x: 4,
+            y: 5,
+            ..Tetrimino::new(TetriminoType::O)
+        });
 
-        state.current_piece = None;
-        state.rotate_piece(true);
+        state.move_to_wall(-1);
+        assert_eq!(state.current_piece.unwrap().x, 0);
 
-        assert_eq!(state.current_piece, None);
+        state.move_to_wall(1);
+        assert_eq!(state.current_piece.unwrap().x, 8);
     }
 
     #[test]
-    fn test_hard_drop() {
+    fn test_move_to_wall_stops_at_stack() {
         let config = make_test_config(true);
         let mut state = super::GameState::new(config);
+        state.board.cells_mut()[6][7] = Some(TetriminoType::I);
+        state.current_piece = Some(Tetrimino {
+            x: 2,
+            y: 5,
+            ..Tetrimino::new(TetriminoType::O)
+        });
 
-        state.spawn_piece();
+        state.move_to_wall(1);
 
-        state.hard_drop();
+        assert_eq!(state.current_piece.unwrap().x, 5);
+    }
 
-        assert!(state.current_piece.is_some());
+    /// A vertical T hugging the left wall; rotating it clockwise needs a kick.
+    fn t_against_left_wall() -> Tetrimino {
+        Tetrimino::from_spec(TetriminoType::T, -1, 5, 1)
     }
 
     #[test]
-    fn test_lock_current_piece() {
+    fn test_rotate_piece_kicks_off_wall() {
         let config = make_test_config(true);
         let mut state = super::GameState::new(config);
+        state.current_piece = Some(t_against_left_wall());
 
-        state.spawn_piece();
-        state.current_piece.as_mut().unwrap().x = 0;
-        state.current_piece.as_mut().unwrap().y = 18;
-        let blocks_before = state.current_piece.unwrap().get_blocks();
+        state.rotate_piece(true);
 
-        state.lock_current_piece();
+        let piece = state.current_piece.unwrap();
+        assert_eq!(piece.rotation, 2);
+        assert_eq!(piece.x, 0);
+    }
 
-        assert!(state.current_piece.is_some());
-        for (dx, dy) in blocks_before {
-            let x = (0 + dx) as usize;
-            let y = (18 + dy) as usize;
-            assert!(
-                state.board.get_cell(x, y).is_some(),
-                "Cell ({}, {}) should be filled",
-                x,
-                y
-            );
+    #[test]
+    fn test_classic_rotation_fails_instead_of_kicking() {
+        let config = GameConfig {
+            classic_rotation: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        state.current_piece = Some(t_against_left_wall());
+
+        state.rotate_piece(true);
+
+        assert_eq!(state.current_piece, Some(t_against_left_wall()));
+    }
+
+    #[test]
+    fn test_ars_i_piece_does_not_kick_off_the_wall() {
+        // Vertical I hugging the left wall; turning flat needs a two-cell SRS kick
+        let against_wall = Tetrimino::from_spec(TetriminoType::I, -2, 5, 1);
+        let mut srs = super::GameState::new(make_test_config(true));
+        srs.current_piece = Some(against_wall);
+        assert!(srs.rotate_piece(true));
+        assert_eq!(srs.current_piece.unwrap().x, 0);
+
+        let mut ars = super::GameState::new(GameConfig {
+            rotation_system: RotationSystem::Ars,
+            ..make_test_config(true)
+        });
+        ars.current_piece = Some(against_wall);
+        assert!(!ars.rotate_piece(true));
+        assert_eq!(ars.current_piece, Some(against_wall));
+    }
+
+    #[test]
+    fn test_ars_spawns_t_flat_side_down() {
+        let mut state = super::GameState::new(GameConfig {
+            rotation_system: RotationSystem::Ars,
+            initial_queue: vec![TetriminoType::T, TetriminoType::I],
+            ..make_test_config(true)
+        });
+        state.spawn_piece();
+        assert_eq!(state.current_piece.unwrap().rotation, 2);
+        state.spawn_piece();
+        assert_eq!(state.current_piece.unwrap().rotation, 0);
+    }
+
+    #[test]
+    fn test_replay_deals_the_same_pieces() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::with_mode(config, GameMode::Sprint);
+        let mut dealt = Vec::new();
+        for _ in 0..20 {
+            state.spawn_piece();
+            dealt.push(state.current_piece.unwrap().kind);
+            state.hard_drop();
+        }
+
+        let mut replay = state.replay();
+        assert_eq!(replay.seed, state.seed);
+        assert_eq!(replay.mode, GameMode::Sprint);
+        assert_eq!(replay.config.seed, None);
+        assert_eq!(replay.pieces_placed, 0);
+        assert!(replay.board.cells().iter().flatten().all(|c| c.is_none()));
+
+        let replayed: Vec<_> = (0..20)
+            .map(|_| {
+                replay.spawn_piece();
+                let kind = replay.current_piece.unwrap().kind;
+                replay.hard_drop();
+                kind
+            })
+            .collect();
+        assert_eq!(replayed, dealt);
+    }
+
+    #[test]
+    fn test_sonic_drop_lands_on_ghost_without_locking() {
+        let config = GameConfig {
+            enable_ghost_piece: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        state.spawn_piece();
+        let ghost = state.ghost_piece().unwrap();
+        let start_y = state.current_piece.unwrap().y;
+        let pieces_placed = state.pieces_placed;
+
+        let rows = state.sonic_drop();
+
+        assert_eq!(rows, (ghost.y - start_y) as u32);
+        assert_eq!(state.current_piece, Some(ghost));
+        assert_eq!(state.pieces_placed, pieces_placed);
+        assert_eq!(state.score, rows as u64);
+        assert!(
+            state
+                .board
+                .cells()
+                .iter()
+                .flatten()
+                .all(|cell| cell.is_none())
+        );
+
+        // Already grounded: nothing moves and nothing scores
+        assert_eq!(state.sonic_drop(), 0);
+        assert_eq!(state.score, rows as u64);
+    }
+
+    #[test]
+    fn test_rotation_assist_nudges_off_wall() {
+        let config = GameConfig {
+            classic_rotation: true,
+            rotation_assist: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        state.current_piece = Some(t_against_left_wall());
+
+        assert!(state.rotate_piece(true));
+
+        let piece = state.current_piece.unwrap();
+        assert_eq!(piece.rotation, 2);
+        assert_eq!(piece.x, 0);
+        assert_eq!(piece.y, 5);
+    }
+
+    #[test]
+    fn test_rotation_assist_gives_up_when_boxed_in() {
+        let config = GameConfig {
+            classic_rotation: true,
+            rotation_assist: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        // Wall on the left, and the cell a nudge right would need is filled
+        state.board.set_cell(2, 6, Some(TetriminoType::O)).unwrap();
+        state.current_piece = Some(t_against_left_wall());
+
+        assert!(!state.rotate_piece(true));
+        assert_eq!(state.current_piece, Some(t_against_left_wall()));
+    }
+
+    /// Puts `piece` in play with a lock delay that started `ago` in the past.
+    fn grounded_with_lock_delay(state: &mut super::GameState, piece: Tetrimino, ago: Duration) {
+        state.current_piece = Some(piece);
+        state.phase = Phase::LockDelay {
+            started: Instant::now() - ago,
+        };
+    }
+
+    #[test]
+    fn test_failed_rotation_does_not_reset_lock_delay() {
+        let config = GameConfig {
+            classic_rotation: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        grounded_with_lock_delay(&mut state, t_against_left_wall(), Duration::from_secs(1));
+        let phase = state.phase();
+
+        assert!(!state.rotate_piece(true));
+        assert_eq!(state.phase(), phase);
+        assert!(state.lock_delay_expired(Instant::now()));
+    }
+
+    #[test]
+    fn test_successful_rotation_resets_lock_delay() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        grounded_with_lock_delay(&mut state, t_against_left_wall(), Duration::from_secs(1));
+
+        assert!(state.rotate_piece(true));
+        assert!(!state.lock_delay_expired(Instant::now()));
+    }
+
+    #[test]
+    fn test_blocked_move_does_not_reset_lock_delay() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        grounded_with_lock_delay(&mut state, t_against_left_wall(), Duration::from_secs(1));
+
+        assert!(!state.move_piece(-1, 0));
+        assert!(state.lock_delay_expired(Instant::now()));
+
+        assert!(state.move_piece(1, 0));
+        assert!(!state.lock_delay_expired(Instant::now()));
+    }
+
+    #[test]
+    fn test_lock_delay_resets_are_capped() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        let piece = Tetrimino {
+            x: 4,
+            ..t_against_left_wall()
+        };
+        grounded_with_lock_delay(&mut state, piece, Duration::from_secs(1));
+
+        for i in 0..super::MAX_LOCK_RESETS {
+            let dx = if i % 2 == 0 { 1 } else { -1 };
+            assert!(state.move_piece(dx, 0));
+        }
+        state.phase = Phase::LockDelay {
+            started: Instant::now() - Duration::from_secs(1),
+        };
+
+        assert!(state.move_piece(1, 0));
+        assert!(state.lock_delay_expired(Instant::now()));
+    }
+
+    #[test]
+    fn test_classic_rotation_ignores_counter_clockwise() {
+        let config = GameConfig {
+            classic_rotation: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        state.spawn_piece();
+        state.current_piece.as_mut().unwrap().rotation = 2;
+
+        state.rotate_piece(false);
+
+        assert_eq!(state.current_piece.unwrap().rotation, 2);
+    }
+
+    #[test]
+    fn test_held_piece_landing_rests_on_stack() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        for x in 4..6 {
+            state.board.cells_mut()[19][x] = Some(TetriminoType::I);
+        }
+        state.held_piece = Some(TetriminoType::O);
+        state.current_piece = Some(Tetrimino {
+            x: 4,
+            ..Tetrimino::new(TetriminoType::T)
+        });
+
+        let landing = state.held_piece_landing().unwrap();
+
+        assert_eq!(landing.kind, TetriminoType::O);
+        assert_eq!((landing.x, landing.y), (4, 17));
+    }
+
+    #[test]
+    fn test_held_piece_landing_uses_spawn_orientation() {
+        let config = GameConfig {
+            rotation_system: RotationSystem::Ars,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        state.held_piece = Some(TetriminoType::T);
+        state.current_piece = Some(Tetrimino::new(TetriminoType::O));
+
+        let landing = state.held_piece_landing().unwrap();
+
+        assert_eq!(landing.rotation, 2);
+    }
+
+    #[test]
+    fn test_toggle_ghost_flips_flag_and_ghost_piece() {
+        let config = GameConfig {
+            enable_ghost_piece: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        state.current_piece = Some(Tetrimino::new(TetriminoType::O));
+
+        assert_eq!(state.ghost_piece().map(|ghost| ghost.y), Some(18));
+
+        state.toggle_ghost();
+        assert!(!state.config.enable_ghost_piece);
+        assert_eq!(state.ghost_piece(), None);
+
+        state.toggle_ghost();
+        assert!(state.config.enable_ghost_piece);
+        assert!(state.ghost_piece().is_some());
+    }
+
+    #[test]
+    fn test_held_piece_landing_requires_held_piece() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        state.current_piece = Some(Tetrimino::new(TetriminoType::T));
+
+        assert_eq!(state.held_piece_landing(), None);
+    }
+
+    #[test]
+    fn test_held_piece_landing_none_when_blocked() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        state.board.cells_mut()[0][8] = Some(TetriminoType::I);
+        state.held_piece = Some(TetriminoType::I);
+        state.current_piece = Some(Tetrimino {
+            x: 6,
+            ..Tetrimino::new(TetriminoType::O)
+        });
+
+        assert_eq!(state.held_piece_landing(), None);
+    }
+
+    #[test]
+    fn test_rotation_round_trip_on_empty_board() {
+        for kind in TetriminoType::ALL {
+            for rotation in 0..4 {
+                for first_clockwise in [true, false] {
+                    let mut state = super::GameState::new(make_test_config(true));
+                    let original = Tetrimino {
+                        kind,
+                        x: 3,
+                        y: 8,
+                        rotation,
+                    };
+                    state.current_piece = Some(original);
+
+                    state.rotate_piece(first_clockwise);
+                    state.rotate_piece(!first_clockwise);
+
+                    let piece = state.current_piece.unwrap();
+                    assert_eq!(
+                        (piece.x, piece.y, piece.rotation),
+                        (original.x, original.y, original.rotation),
+                        "{kind:?} from rotation {rotation}, clockwise first: {first_clockwise}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotate_piece_no_current_piece() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        state.current_piece = None;
+        state.rotate_piece(true);
+
+        assert_eq!(state.current_piece, None);
+    }
+
+    #[test]
+    fn test_hard_drop() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        state.spawn_piece();
+
+        state.hard_drop();
+
+        assert!(state.current_piece.is_some());
+    }
+
+    #[test]
+    fn test_hard_drop_locks_immediately_by_default() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        state.current_piece = Some(Tetrimino::new(TetriminoType::O));
+
+        state.hard_drop();
+
+        assert_eq!(state.board.get_cell(0, 19), Some(TetriminoType::O));
+        assert!(!state.is_lock_delay_active());
+    }
+
+    #[test]
+    fn test_hard_drop_without_lock_starts_lock_delay() {
+        let config = GameConfig {
+            hard_drop_locks: false,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        state.current_piece = Some(Tetrimino::new(TetriminoType::O));
+
+        state.hard_drop();
+
+        let piece = state.current_piece.unwrap();
+        assert_eq!(piece.kind, TetriminoType::O);
+        assert_eq!(piece.y, 18);
+        assert_eq!(state.board.get_cell(0, 19), None);
+        assert!(state.is_lock_delay_active());
+
+        // The piece can still slide before the delay runs out
+        assert!(state.move_piece(1, 0));
+        assert_eq!(state.current_piece.unwrap().x, 1);
+    }
+
+    #[test]
+    fn test_lock_delay_expires_after_configured_time() {
+        let config = GameConfig {
+            hard_drop_locks: false,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        state.current_piece = Some(Tetrimino::new(TetriminoType::O));
+
+        let before = std::time::Instant::now();
+        state.hard_drop();
+
+        assert!(!state.lock_delay_expired(before));
+        assert!(
+            state.lock_delay_expired(
+                std::time::Instant::now() + std::time::Duration::from_millis(500)
+            )
+        );
+    }
+
+    #[test]
+    fn test_lock_delay_cancelled_when_piece_falls_again() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        state.current_piece = Some(Tetrimino::new(TetriminoType::O));
+
+        state.start_lock_delay();
+        assert!(state.move_piece(0, 1));
+
+        assert!(!state.is_lock_delay_active());
+    }
+
+    #[test]
+    fn test_lock_current_piece() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        state.spawn_piece();
+        state.current_piece.as_mut().unwrap().x = 0;
+        state.current_piece.as_mut().unwrap().y = 18;
+        let blocks_before = state.current_piece.unwrap().get_blocks();
+
+        state.lock_current_piece();
+
+        assert!(state.current_piece.is_some());
+        for (dx, dy) in blocks_before {
+            let x = (0 + dx) as usize;
+            let y = (18 + dy) as usize;
+            assert!(
+                state.board.get_cell(x, y).is_some(),
+                "Cell ({}, {}) should be filled",
+                x,
+                y
+            );
         }
     }
 
@@ -907,7 +3437,7 @@ mod tests {
 
         state.lock_current_piece();
 
-        assert!(state.pending_line_clear);
+        assert!(state.is_line_clear_pending());
         assert!(state.line_clear_animation.is_some());
         assert_eq!(state.line_clear_animation.as_ref().unwrap().total_lines, 1);
     }
@@ -923,7 +3453,7 @@ mod tests {
         bottom_piece.rotation = 0;
         state.board.lock_tetromino(&bottom_piece);
 
-        state.pending_line_clear = true;
+        state.phase = Phase::ClearAnimation;
         state.line_clear_animation = Some(super::LineClearAnimation {
             cleared_rows: vec![19],
             start_time: std::time::Instant::now(),
@@ -932,353 +3462,1945 @@ mod tests {
 
         state.complete_line_clear();
 
-        assert!(!state.pending_line_clear);
+        assert!(!state.is_line_clear_pending());
         assert!(state.line_clear_animation.is_none());
         assert!(state.board.get_full_lines().is_empty());
     }
 
+    /// Locks an I piece that completes the bottom row, leaving the clear pending.
+    fn start_line_clear(state: &mut super::GameState) {
+        for x in 0..6 {
+            state.board.cells_mut()[19][x] = Some(TetriminoType::O);
+        }
+        state.current_piece = Some(Tetrimino {
+            kind: TetriminoType::I,
+            x: 6,
+            y: 19,
+            rotation: 0,
+        });
+        state.lock_current_piece();
+        assert!(state.is_line_clear_pending());
+    }
+
     #[test]
-    fn test_complete_line_clear_no_pending() {
-        let config = make_test_config(true);
-        let mut state = super::GameState::new(config);
+    fn test_move_during_line_clear_applies_to_next_piece() {
+        let mut state = super::GameState::new(make_test_config(true));
+        state.next_pieces[0] = TetriminoType::T;
+        start_line_clear(&mut state);
+
+        assert!(!state.move_piece(1, 0));
+        assert!(!state.move_piece(1, 0));
+        state.complete_line_clear();
+
+        let piece = state.current_piece.unwrap();
+        assert_eq!(piece.kind, TetriminoType::T);
+        assert_eq!(piece.x, Tetrimino::new(TetriminoType::T).x + 2);
+    }
+
+    #[test]
+    fn test_rotation_during_line_clear_applies_to_next_piece() {
+        let mut state = super::GameState::new(make_test_config(true));
+        state.next_pieces[0] = TetriminoType::T;
+        start_line_clear(&mut state);
 
+        state.rotate_piece(false);
         state.complete_line_clear();
 
-        assert!(!state.pending_line_clear);
+        assert_eq!(state.current_piece.unwrap().rotation, 3);
+
+        // The buffer is consumed by the piece it was replayed on
+        assert!(state.buffered_inputs.is_empty());
+    }
+
+    #[test]
+    fn test_complete_line_clear_cascade_chains() {
+        let config = GameConfig {
+            clear_gravity: ClearGravity::Cascade,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+
+        // Clearing row 18 drops the lone block at (0, 17) into the gap at (0, 19)
+        state.board.cells_mut()[17][0] = Some(TetriminoType::T);
+        for x in 0..10 {
+            state.board.cells_mut()[18][x] = Some(TetriminoType::I);
+        }
+        for x in 1..10 {
+            state.board.cells_mut()[19][x] = Some(TetriminoType::O);
+        }
+        state.phase = Phase::ClearAnimation;
+
+        // The chained row flashes on its own before it clears
+        state.complete_line_clear();
+        assert_eq!(state.lines_cleared, 1);
+        assert!(state.is_line_clear_pending());
+        assert_eq!(
+            state.line_clear_animation.as_ref().unwrap().cleared_rows,
+            vec![19]
+        );
+
+        state.complete_line_clear();
+        assert_eq!(state.lines_cleared, 2);
+        assert!(!state.is_line_clear_pending());
+        assert!(
+            state
+                .board
+                .cells()
+                .iter()
+                .flatten()
+                .all(|cell| cell.is_none())
+        );
+    }
+
+    #[test]
+    fn test_cascade_chain_past_four_lines_scores_each_step() {
+        let config = GameConfig {
+            clear_gravity: ClearGravity::Cascade,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+
+        // A vertical I in column 5 completes rows 16-19; row 15 and the lone block
+        // at (5, 10) then fall into a full row 19
+        for y in 15..20 {
+            for x in (0..10).filter(|&x| x != 5) {
+                state.board.cells_mut()[y][x] = Some(TetriminoType::O);
+            }
+        }
+        state.board.cells_mut()[10][5] = Some(TetriminoType::T);
+        state.current_piece = Some(Tetrimino {
+            kind: TetriminoType::I,
+            x: 3,
+            y: 16,
+            rotation: 1,
+        });
+        state.lock_current_piece();
+
+        state.complete_line_clear();
+        let tetris_score = state.score;
+        assert_eq!(state.lines_cleared, 4);
+        assert!(tetris_score > 0);
+
+        state.complete_line_clear();
+        assert_eq!(state.lines_cleared, 5);
+        assert_eq!(state.max_combo(), 2);
+        assert!(state.score > tetris_score);
+    }
+
+    #[test]
+    fn test_complete_line_clear_no_pending() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        state.complete_line_clear();
+
+        assert!(!state.is_line_clear_pending());
+    }
+
+    #[test]
+    fn test_update_score_zero_lines() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        state.score = 100;
+        state.combo_count = 5;
+
+        state.update_score(0, false);
+
+        assert_eq!(state.score, 100);
+        assert_eq!(state.combo_count, 0);
+    }
+
+    fn high_reward_scoring() -> ScoreConfig {
+        ScoreConfig {
+            double: 500,
+            tetris: 1200,
+            tspin_double_bonus: 2000,
+            back_to_back_multiplier: 2.0,
+            ..ScoreConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_custom_scoring_tetris_payout() {
+        let mut default_state = super::GameState::new(make_test_config(true));
+        let mut custom_state = super::GameState::new(GameConfig {
+            scoring: high_reward_scoring(),
+            ..make_test_config(true)
+        });
+
+        default_state.update_score(4, true);
+        custom_state.update_score(4, true);
+
+        assert_eq!(default_state.score, 800);
+        assert_eq!(custom_state.score, 1200);
+    }
+
+    #[test]
+    fn test_custom_scoring_tspin_payout() {
+        let mut default_state = super::GameState::new(make_test_config(true));
+        let mut custom_state = super::GameState::new(GameConfig {
+            scoring: high_reward_scoring(),
+            ..make_test_config(true)
+        });
+
+        for state in [&mut default_state, &mut custom_state] {
+            state.pending_tspin = TSpinKind::Full;
+            state.update_score(2, true);
+        }
+
+        assert_eq!(default_state.score, 1200);
+        assert_eq!(custom_state.score, 2000);
+    }
+
+    #[test]
+    fn test_full_tspin_clearing_four_scores_quad_bonus() {
+        let mut state = super::GameState::new(make_test_config(true));
+        state.pending_tspin = TSpinKind::Full;
+        state.update_score(4, true);
+
+        assert_eq!(state.score, 2000);
+    }
+
+    #[test]
+    fn test_custom_scoring_back_to_back_multiplier() {
+        let mut default_state = super::GameState::new(make_test_config(true));
+        let mut custom_state = super::GameState::new(GameConfig {
+            scoring: high_reward_scoring(),
+            ..make_test_config(true)
+        });
+
+        for state in [&mut default_state, &mut custom_state] {
+            state.back_to_back_active = true;
+            state.update_score(4, true);
+        }
+
+        assert_eq!(default_state.score, 1200);
+        assert_eq!(custom_state.score, 2400);
+    }
+
+    #[test]
+    fn test_compute_awarded_lines_no_tspin() {
+        let config = make_test_config(true);
+        let state = super::GameState::new(config);
+
+        assert_eq!(state.compute_awarded_lines(1, false), 1);
+        assert_eq!(state.compute_awarded_lines(2, false), 2);
+        assert_eq!(state.compute_awarded_lines(3, false), 3);
+        assert_eq!(state.compute_awarded_lines(4, false), 4);
+    }
+
+    #[test]
+    fn test_compute_awarded_lines_with_tspin() {
+        let config = make_test_config(true);
+        let state = super::GameState::new(config);
+
+        assert_eq!(state.compute_awarded_lines(1, true), 1);
+        assert_eq!(state.compute_awarded_lines(2, true), 2);
+        assert_eq!(state.compute_awarded_lines(3, true), 3);
+    }
+
+    /// Upward T resting on the floor with one front corner filled: a Mini single.
+    fn mini_tspin_setup(state: &mut super::GameState) -> Tetrimino {
+        for x in (0..10).filter(|x| !(3..=5).contains(x)) {
+            state.board.cells_mut()[19][x] = Some(TetriminoType::I);
+        }
+        state.board.cells_mut()[18][3] = Some(TetriminoType::I);
+        Tetrimino {
+            kind: TetriminoType::T,
+            x: 3,
+            y: 18,
+            rotation: 0,
+        }
+    }
+
+    /// Downward T in a slot with both front corners and one back corner filled.
+    fn full_tspin_setup(state: &mut super::GameState) -> Tetrimino {
+        for x in (0..10).filter(|&x| x != 4) {
+            state.board.cells_mut()[19][x] = Some(TetriminoType::I);
+        }
+        state.board.cells_mut()[17][3] = Some(TetriminoType::I);
+        Tetrimino {
+            kind: TetriminoType::T,
+            x: 3,
+            y: 17,
+            rotation: 2,
+        }
+    }
+
+    #[test]
+    fn test_check_tspin_requires_rotation() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        let piece = mini_tspin_setup(&mut state);
+
+        state.last_move_was_rotation = false;
+        assert_eq!(state.check_tspin(&piece), TSpinKind::None);
+    }
+
+    #[test]
+    fn test_check_tspin_ignores_other_pieces() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        let piece = mini_tspin_setup(&mut state);
+
+        state.last_move_was_rotation = true;
+        let l_piece = Tetrimino {
+            kind: TetriminoType::L,
+            ..piece
+        };
+        assert_eq!(state.check_tspin(&l_piece), TSpinKind::None);
+    }
+
+    #[test]
+    fn test_check_tspin_mini() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        let piece = mini_tspin_setup(&mut state);
+
+        state.last_move_was_rotation = true;
+        assert_eq!(state.check_tspin(&piece), TSpinKind::Mini);
+    }
+
+    #[test]
+    fn test_check_tspin_full() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        let piece = full_tspin_setup(&mut state);
+
+        state.last_move_was_rotation = true;
+        assert_eq!(state.check_tspin(&piece), TSpinKind::Full);
+    }
+
+    #[test]
+    fn test_mini_tspin_single_scores_mini_table() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        let piece = mini_tspin_setup(&mut state);
+        state.score = 0;
+
+        state.current_piece = Some(piece);
+        state.last_move_was_rotation = true;
+        state.lock_current_piece();
+        state.complete_line_clear();
+
+        assert_eq!(state.score, 200);
+        assert!(state.drain_events().contains(&GameEvent::TSpin {
+            kind: TSpinKind::Mini,
+            lines: 1
+        }));
+    }
+
+    #[test]
+    fn test_full_tspin_single_scores_full_table() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+        let piece = full_tspin_setup(&mut state);
+        state.score = 0;
+
+        state.current_piece = Some(piece);
+        state.last_move_was_rotation = true;
+        state.lock_current_piece();
+        state.complete_line_clear();
+
+        assert_eq!(state.score, 800);
+    }
+
+    fn threshold_config() -> GameConfig {
+        GameConfig {
+            level_thresholds: vec![0, 4, 6, 12],
+            ..make_test_config(true)
+        }
+    }
+
+    fn clear_lines(state: &mut super::GameState, lines: u32) {
+        state.lines_cleared += lines;
+        state.update_score(lines, true);
+    }
+
+    #[test]
+    fn test_level_thresholds_initial_goal() {
+        let state = super::GameState::new(threshold_config());
+
+        assert_eq!(state.level, 1);
+        assert_eq!(state.lines_until_next_level, 4);
+    }
+
+    #[test]
+    fn test_level_thresholds_drive_level_ups() {
+        let mut state = super::GameState::new(threshold_config());
+
+        clear_lines(&mut state, 3);
+        assert_eq!(state.level, 1);
+        assert_eq!(state.lines_until_next_level, 1);
+
+        clear_lines(&mut state, 1);
+        assert_eq!(state.level, 2);
+        assert_eq!(state.lines_until_next_level, 2);
+
+        // A big clear can skip straight past a short level
+        clear_lines(&mut state, 4);
+        assert_eq!(state.level, 3);
+        assert_eq!(state.lines_until_next_level, 4);
+        // Lines 6 and 7 carry past level 3's threshold
+        assert_eq!(state.lines_this_level, 2);
+    }
+
+    #[test]
+    fn test_level_thresholds_stop_at_end_of_table() {
+        let mut state = super::GameState::new(threshold_config());
+
+        clear_lines(&mut state, 4);
+        clear_lines(&mut state, 4);
+        clear_lines(&mut state, 4);
+        clear_lines(&mut state, 4);
+
+        assert_eq!(state.level, 4);
+        assert_eq!(state.lines_until_next_level, 0);
+    }
+
+    #[test]
+    fn test_level_thresholds_keep_higher_starting_level() {
+        let config = GameConfig {
+            starting_level: 3,
+            ..threshold_config()
+        };
+        let mut state = super::GameState::new(config);
+
+        clear_lines(&mut state, 4);
+
+        assert_eq!(state.level, 3);
+    }
+
+    #[test]
+    fn test_freeze_level_tetris_scores_at_fixed_level() {
+        let config = GameConfig {
+            starting_level: 5,
+            lines_per_level: 2,
+            freeze_level: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+
+        clear_lines(&mut state, 4);
+        assert_eq!(state.score, 800 * 5);
+        assert_eq!(state.level, 5);
+
+        state.score = 0;
+        state.back_to_back_active = false;
+        state.combo_count = 0;
+        clear_lines(&mut state, 4);
+        assert_eq!(state.score, 800 * 5);
+        assert_eq!(state.level, 5);
+        assert!(
+            !state
+                .drain_events()
+                .iter()
+                .any(|event| matches!(event, GameEvent::LevelUp { .. }))
+        );
+    }
+
+    #[test]
+    fn test_minimal_ui_reveals_panels_briefly() {
+        let config = GameConfig {
+            minimal_ui: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        let now = Instant::now();
+
+        assert!(!state.side_panels_visible(now));
+
+        state.reveal_stats(now);
+        assert!(state.side_panels_visible(now + Duration::from_secs(1)));
+        assert!(!state.side_panels_visible(now + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_minimal_ui_reveals_panels_on_level_up() {
+        let config = GameConfig {
+            minimal_ui: true,
+            lines_per_level: 1,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+
+        clear_lines(&mut state, 1);
+
+        assert!(state.side_panels_visible(Instant::now()));
+    }
+
+    #[test]
+    fn test_update_level_fixed_goal() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        state.update_level_fixed_goal(5);
+
+        assert_eq!(state.lines_until_next_level, 5);
+        assert_eq!(state.level, 1);
+    }
+
+    #[test]
+    fn test_update_level_fixed_goal_level_up() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        state.update_level_fixed_goal(15);
+
+        assert_eq!(state.level, 2);
+        assert_eq!(state.lines_until_next_level, 5);
+    }
+
+    #[test]
+    fn test_update_level_fixed_goal_exact_level_up() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        state.update_level_fixed_goal(10);
+
+        assert_eq!(state.level, 2);
+        assert_eq!(state.lines_until_next_level, 10);
+    }
+
+    #[test]
+    fn test_lines_per_level_growth_add() {
+        let config = GameConfig {
+            lines_per_level: 5,
+            lines_per_level_growth: LevelGrowth::Add(5),
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config.clone());
+
+        // Level 1 -> 2 needs 5 lines, then level 2 -> 3 needs 10
+        assert_eq!(state.lines_until_next_level, 5);
+        state.update_level_fixed_goal(5);
+        assert_eq!(state.level, 2);
+        assert_eq!(state.lines_until_next_level, 10);
+
+        // Level 5 -> 6 needs 25 lines, then level 6 -> 7 needs 30
+        let mut state = super::GameState::new(GameConfig {
+            starting_level: 5,
+            ..config
+        });
+        assert_eq!(state.lines_until_next_level, 25);
+        state.update_level_fixed_goal(24);
+        assert_eq!(state.level, 5);
+        state.update_level_fixed_goal(1);
+        assert_eq!(state.level, 6);
+        assert_eq!(state.lines_until_next_level, 30);
+    }
+
+    #[test]
+    fn test_lines_per_level_growth_multiply() {
+        let config = GameConfig {
+            lines_per_level: 10,
+            lines_per_level_growth: LevelGrowth::Multiply(1.5),
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+
+        state.update_level_fixed_goal(10);
+        assert_eq!(state.lines_until_next_level, 15);
+        state.update_level_fixed_goal(15);
+        assert_eq!(state.level, 3);
+        assert_eq!(state.lines_until_next_level, 23);
+    }
+
+    #[test]
+    fn test_update_level_variable_goal() {
+        let config = GameConfig {
+            enable_variable_goal: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+
+        state.pieces_placed = 10;
+        state.update_level_variable_goal(5, false);
+
+        assert_eq!(state.level, 1);
+    }
+
+    #[test]
+    fn test_update_level_variable_goal_level_up() {
+        let config = GameConfig {
+            enable_variable_goal: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+
+        state.pieces_placed = 10;
+        state.update_level_variable_goal(15, false);
+
+        assert_eq!(state.level, 2);
+    }
+
+    #[test]
+    fn test_get_wall_kicks_i_piece() {
+        let config = make_test_config(true);
+        let state = super::GameState::new(config);
+
+        let kicks_0_1 = state.get_wall_kicks(TetriminoType::I, 0, 1);
+        assert!(!kicks_0_1.is_empty());
+        assert_eq!(kicks_0_1[0], (0, 0));
+
+        let kicks_1_0 = state.get_wall_kicks(TetriminoType::I, 1, 0);
+        assert!(!kicks_1_0.is_empty());
+    }
+
+    #[test]
+    fn test_get_wall_kicks_o_piece() {
+        let config = make_test_config(true);
+        let state = super::GameState::new(config);
+
+        let kicks = state.get_wall_kicks(TetriminoType::O, 0, 1);
+        assert_eq!(kicks, vec![(0, 0)]);
+    }
+
+    #[test]
+    fn test_get_wall_kicks_t_piece() {
+        let config = make_test_config(true);
+        let state = super::GameState::new(config);
+
+        let kicks_0_1 = state.get_wall_kicks(TetriminoType::T, 0, 1);
+        assert_eq!(kicks_0_1, vec![(0, 0), (0, -1), (-1, 0), (-1, -1)]);
+
+        let kicks_1_2 = state.get_wall_kicks(TetriminoType::T, 1, 2);
+        assert_eq!(kicks_1_2, vec![(0, 0), (0, -1), (1, 0), (1, -1)]);
+    }
+
+    #[test]
+    fn test_get_wall_kicks_default() {
+        let config = make_test_config(true);
+        let state = super::GameState::new(config);
+
+        let kicks = state.get_wall_kicks(TetriminoType::Z, 2, 2);
+        assert_eq!(kicks, vec![(0, 0)]);
+    }
+
+    #[test]
+    fn test_is_line_clear_animation_active_no_animation() {
+        let config = make_test_config(true);
+        let state = super::GameState::new(config);
+
+        assert!(!state.is_line_clear_animation_active());
+    }
+
+    #[test]
+    fn test_is_line_clear_animation_active_with_animation() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        state.line_clear_animation = Some(super::LineClearAnimation {
+            cleared_rows: vec![19],
+            start_time: std::time::Instant::now(),
+            total_lines: 1,
+        });
+
+        assert!(state.is_line_clear_animation_active());
+    }
+
+    #[test]
+    fn test_should_show_cleared_rows_no_animation() {
+        let config = make_test_config(true);
+        let state = super::GameState::new(config);
+
+        assert!(!state.should_show_cleared_rows());
+    }
+
+    #[test]
+    fn test_should_show_cleared_rows_with_animation() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        state.line_clear_animation = Some(super::LineClearAnimation {
+            cleared_rows: vec![19],
+            start_time: std::time::Instant::now(),
+            total_lines: 2,
+        });
+
+        assert!(state.should_show_cleared_rows());
+    }
+
+    #[test]
+    fn test_clear_animation_duration_follows_blinks() {
+        assert_eq!(super::clear_animation_duration(0), Duration::ZERO);
+        assert_eq!(
+            super::clear_animation_duration(2),
+            Duration::from_millis(1000)
+        );
+        assert_eq!(
+            super::clear_animation_duration(5),
+            Duration::from_millis(2500)
+        );
+    }
+
+    #[test]
+    fn test_cleared_rows_visible_phases() {
+        let at = |ms| super::cleared_rows_visible(Duration::from_millis(ms), 2);
+
+        assert!(at(0));
+        assert!(at(249));
+        assert!(!at(250));
+        assert!(!at(499));
+        assert!(at(500));
+        assert!(!at(750));
+        // Both blinks have played
+        assert!(!at(1000));
+        assert!(!at(1250));
+
+        assert!(!super::cleared_rows_visible(Duration::ZERO, 0));
+    }
+
+    #[test]
+    fn test_line_clear_animation_length_ignores_line_count() {
+        let config = GameConfig {
+            clear_blinks: 1,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+
+        state.line_clear_animation = Some(super::LineClearAnimation {
+            cleared_rows: vec![16, 17, 18, 19],
+            start_time: Instant::now() - Duration::from_millis(600),
+            total_lines: 4,
+        });
+
+        assert!(!state.is_line_clear_animation_active());
+    }
+
+    #[test]
+    fn test_line_clear_animation_struct() {
+        let anim = super::LineClearAnimation {
+            cleared_rows: vec![18, 19],
+            start_time: std::time::Instant::now(),
+            total_lines: 2,
+        };
+
+        assert_eq!(anim.cleared_rows.len(), 2);
+        assert_eq!(anim.total_lines, 2);
+    }
+
+    #[test]
+    fn test_score_accumulates() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        state.score = 100;
+        state.update_score(1, true);
+
+        assert!(state.score > 100);
+    }
+
+    #[test]
+    fn test_lines_cleared_accumulates() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        state.lines_cleared = 5;
+        for x in 0..10 {
+            state.board.cells_mut()[19][x] = Some(TetriminoType::I);
+        }
+        state.phase = Phase::ClearAnimation;
+        state.line_clear_animation = Some(super::LineClearAnimation {
+            cleared_rows: vec![19],
+            start_time: std::time::Instant::now(),
+            total_lines: 1,
+        });
+
+        state.complete_line_clear();
+
+        assert_eq!(state.lines_cleared, 6);
+    }
+
+    #[test]
+    fn test_back_to_back_state() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        state.back_to_back_active = true;
+        state.update_score(4, true);
+
+        assert!(state.back_to_back_active);
+    }
+
+    /// A state with one stray block, so clears in these tests aren't perfect clears.
+    fn attack_state() -> super::GameState {
+        let mut state = super::GameState::new(make_test_config(true));
+        state.board.set_cell(0, 19, Some(TetriminoType::J)).unwrap();
+        state
+    }
+
+    #[test]
+    fn test_attack_for_tetris() {
+        let mut state = attack_state();
+        state.update_score(4, true);
+        assert_eq!(state.last_attack(), 4);
+
+        // A second Tetris in a row is back-to-back and part of a combo
+        state.update_score(4, true);
+        assert_eq!(
+            state.last_attack(),
+            4 + BACK_TO_BACK_ATTACK + COMBO_ATTACK[1]
+        );
+        assert_eq!(state.total_attack_sent(), 10);
+    }
+
+    #[test]
+    fn test_attack_for_tspin_double() {
+        let mut state = attack_state();
+        state.pending_tspin = TSpinKind::Full;
+        state.update_score(2, true);
+        assert_eq!(state.last_attack(), 4);
+
+        let mut mini = attack_state();
+        mini.pending_tspin = TSpinKind::Mini;
+        mini.update_score(2, true);
+        assert_eq!(mini.last_attack(), 1);
+    }
+
+    #[test]
+    fn test_attack_for_combo() {
+        let mut state = attack_state();
+        let sent: Vec<u32> = (0..5)
+            .map(|_| {
+                state.update_score(1, true);
+                state.last_attack()
+            })
+            .collect();
+        assert_eq!(sent, vec![0, 1, 1, 2, 2]);
+
+        state.update_score(0, false);
+        assert_eq!(state.last_attack(), 0);
+        state.update_score(1, true);
+        assert_eq!(state.last_attack(), 0);
+        assert_eq!(state.total_attack_sent(), 6);
+    }
+
+    #[test]
+    fn test_attack_for_perfect_clear() {
+        assert_eq!(
+            super::attack_for(4, TSpinKind::None, false, 0, true),
+            4 + PERFECT_CLEAR_ATTACK
+        );
+        assert_eq!(super::attack_for(1, TSpinKind::None, false, 0, false), 0);
+        assert_eq!(super::attack_for(1, TSpinKind::Mini, false, 0, false), 0);
+        assert_eq!(super::attack_for(3, TSpinKind::Full, false, 0, false), 6);
+    }
+
+    #[test]
+    fn test_combo_count() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        state.combo_count = 2;
+        state.update_score(2, true);
+
+        assert_eq!(state.combo_count, 3);
+    }
+
+    #[test]
+    fn test_max_combo_caps_combo_bonus() {
+        let capped_config = GameConfig {
+            max_combo: Some(10),
+            ..make_test_config(true)
+        };
+        let mut capped = super::GameState::new(capped_config.clone());
+        capped.combo_count = 15;
+        capped.update_score(1, false);
+
+        let mut at_cap = super::GameState::new(capped_config);
+        at_cap.combo_count = 10;
+        at_cap.update_score(1, false);
+
+        let mut uncapped = super::GameState::new(make_test_config(true));
+        uncapped.combo_count = 15;
+        uncapped.update_score(1, false);
+
+        assert_eq!(capped.score, at_cap.score);
+        assert!(uncapped.score > capped.score);
+        assert_eq!(capped.combo_count, 16);
+    }
+
+    #[test]
+    fn test_pieces_placed() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        assert_eq!(state.pieces_placed, 0);
+
+        state.spawn_piece();
+        state.lock_current_piece();
+
+        assert_eq!(state.pieces_placed, 1);
+    }
+
+    #[test]
+    fn test_hold_disabled_returns_early() {
+        let config = make_test_config(false);
+        let mut state = super::GameState::new(config);
+
+        state.current_piece = Some(Tetrimino::new(TetriminoType::T));
+        state.hold_piece();
+
+        assert_eq!(state.current_piece.unwrap().kind, TetriminoType::T);
+        assert_eq!(state.held_piece, None);
+    }
+
+    #[test]
+    fn test_all_tetrimino_types_in_bag() {
+        let config = make_test_config(true);
+        let state = super::GameState::new(config);
+
+        assert!(state.generator.bag.len() < 7);
+        assert_eq!(state.next_pieces.len(), 3);
+    }
+
+    #[test]
+    fn test_next_pieces_populated() {
+        let config = make_test_config(true);
+        let state = super::GameState::new(config);
+
+        assert_eq!(state.next_pieces.len(), 3);
+        assert!(!state.current_piece.is_some());
+    }
+
+    #[test]
+    fn test_state_checksum_tracks_replayed_state() {
+        let config = GameConfig {
+            seed: Some(7),
+            ..make_test_config(true)
+        };
+        let mut original = super::GameState::new(config.clone());
+        let mut replayed = super::GameState::new(config);
+        for state in [&mut original, &mut replayed] {
+            state.spawn_piece();
+            state.move_piece(1, 0);
+            state.hard_drop();
+            state.complete_line_clear();
+        }
+        assert!(original.current_piece.is_some());
+        assert_eq!(original.state_checksum(), replayed.state_checksum());
+
+        replayed.score += 1;
+        assert_ne!(original.state_checksum(), replayed.state_checksum());
+        replayed.score -= 1;
+        assert!(replayed.move_piece(1, 0));
+        assert_ne!(original.state_checksum(), replayed.state_checksum());
+    }
+
+    #[test]
+    fn test_first_piece_guaranteed_good_avoids_s_z_o() {
+        for seed in 0..200 {
+            let config = GameConfig {
+                seed: Some(seed),
+                first_piece_guaranteed_good: true,
+                ..make_test_config(true)
+            };
+            let state = super::GameState::new(config);
+            assert!(
+                GOOD_OPENERS.contains(&state.next_pieces[0]),
+                "seed {} opened with {:?}",
+                seed,
+                state.next_pieces[0]
+            );
+        }
+    }
+
+    #[test]
+    fn test_first_piece_guaranteed_good_keeps_a_full_bag() {
+        let config = GameConfig {
+            seed: Some(3),
+            first_piece_guaranteed_good: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        let mut sequence = state.next_pieces.clone();
+        while sequence.len() < 7 {
+            sequence.push(state.generate_piece());
+        }
+        sequence.sort_by_key(|kind| kind.to_char());
+        let mut all = TetriminoType::ALL.to_vec();
+        all.sort_by_key(|kind| kind.to_char());
+        assert_eq!(sequence, all);
+    }
+
+    /// Plays `clears` line clears, each with its animation lasting `delay`, and
+    /// returns the wall-clock time the game has run.
+    fn play_slow_clears(state: &mut super::GameState, clears: usize, delay: Duration) -> Duration {
+        state.spawn_piece();
+        for _ in 0..clears {
+            state
+                .board
+                .fill_region(0, 19, 9, 19, Some(TetriminoType::J));
+            state.hard_drop();
+            assert_eq!(state.phase(), Phase::ClearAnimation);
+            std::thread::sleep(delay);
+            state.complete_line_clear();
+            assert_eq!(state.phase(), Phase::Falling);
+        }
+        state.started_at.elapsed()
+    }
+
+    #[test]
+    fn test_clock_can_leave_out_delays() {
+        let delay = Duration::from_millis(15);
+        let config = GameConfig {
+            clock_counts_delays: false,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::with_mode(config, GameMode::Ultra);
+        let now = state.started_at + play_slow_clears(&mut state, 3, delay);
+
+        assert!(state.elapsed(now) + delay * 3 <= now - state.started_at);
+        assert_eq!(
+            state.time_remaining(now),
+            Some(super::ULTRA_TIME_LIMIT - state.elapsed(now))
+        );
+    }
+
+    #[test]
+    fn test_clock_counts_delays_by_default() {
+        let mut state = super::GameState::new(make_test_config(true));
+        let wall = play_slow_clears(&mut state, 2, Duration::from_millis(5));
+        let now = state.started_at + wall;
+
+        assert_eq!(state.elapsed(now), wall);
+        assert_eq!(state.time_remaining(now), None);
+    }
+
+    #[test]
+    fn test_spawn_fading() {
+        let config = GameConfig {
+            spawn_fade_in: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        let now = Instant::now();
+        assert!(!state.spawn_fading(now));
+
+        state.spawn_piece();
+        let spawned = state.spawned_at.unwrap();
+        assert!(state.spawn_fading(spawned));
+        assert!(state.spawn_fading(spawned + SPAWN_FADE_DURATION / 2));
+        assert!(!state.spawn_fading(spawned + SPAWN_FADE_DURATION));
+
+        state.config.spawn_fade_in = false;
+        assert!(!state.spawn_fading(spawned));
+    }
+
+    #[test]
+    fn test_pause_freezes_gravity_in_tick() {
+        let mut state = super::GameState::new(make_test_config(true));
+        state.spawn_piece();
+        let start_y = state.current_piece.unwrap().y;
+
+        state.toggle_pause();
+        assert!(state.paused);
+        assert!(!state.tick(Instant::now(), true));
+        assert_eq!(state.current_piece.unwrap().y, start_y);
+
+        state.toggle_pause();
+        assert!(!state.paused);
+        assert!(state.tick(Instant::now(), true));
+        assert_eq!(state.current_piece.unwrap().y, start_y + 1);
+    }
+
+    #[test]
+    fn test_tick_walks_through_every_phase() {
+        let config = GameConfig {
+            spawn_delay_ms: 100,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        state
+            .board
+            .fill_region(0, 16, 8, 19, Some(TetriminoType::J));
+        // A vertical I down the open right column
+        state.current_piece = Some(Tetrimino::from_spec(TetriminoType::I, 7, 0, 1));
+        assert_eq!(state.phase(), Phase::Falling);
+
+        let now = Instant::now();
+        let mut steps = 0;
+        while state.phase() == Phase::Falling {
+            assert!(state.tick(now, true));
+            steps += 1;
+            assert!(steps <= 20, "piece never landed");
+        }
+        assert!(matches!(state.phase(), Phase::LockDelay { .. }));
+        assert!(state.current_piece.is_some());
+
+        let later = Instant::now() + Duration::from_secs(1);
+        state.tick(later, false);
+        assert_eq!(state.phase(), Phase::ClearAnimation);
+        assert!(state.current_piece.is_none());
+        // Gravity waits for the animation
+        assert!(!state.tick(Instant::now(), true));
+        assert_eq!(state.phase(), Phase::ClearAnimation);
+
+        state.tick(Instant::now() + Duration::from_secs(10), false);
+        assert!(matches!(state.phase(), Phase::SpawnDelay { .. }));
+        assert_eq!(state.lines_cleared, 4);
+        assert!(state.current_piece.is_none());
+        assert!(!state.tick(Instant::now(), true));
+        assert!(state.current_piece.is_none());
+
+        state.tick(Instant::now() + Duration::from_secs(1), false);
+        assert_eq!(state.phase(), Phase::Falling);
+        assert!(state.current_piece.is_some());
+
+        // Bury the spawn area so the next piece can't come in
+        state.board.fill_region(0, 1, 8, 19, Some(TetriminoType::J));
+        state.current_piece = Some(Tetrimino::from_spec(TetriminoType::T, 3, -1, 0));
+        state.hard_drop();
+        state.tick(Instant::now() + Duration::from_secs(1), false);
+        assert_eq!(state.phase(), Phase::GameOver);
+        assert!(state.is_game_over());
+        assert!(!state.tick(Instant::now() + Duration::from_secs(2), true));
+    }
+
+    #[test]
+    fn test_spawn_delay_buffers_moves() {
+        let config = GameConfig {
+            spawn_delay_ms: 100,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        state.spawn_piece();
+        state.hard_drop();
+        assert!(matches!(state.phase(), Phase::SpawnDelay { .. }));
+
+        assert_eq!(state.try_move(1, 0), MoveResult::NoPiece);
+        state.tick(Instant::now() + Duration::from_secs(1), false);
+        assert_eq!(state.current_piece.unwrap().x, 1);
+    }
+
+    /// A board filled from row 1 down, except the right column so nothing clears,
+    /// with a T about to drop.
+    fn near_full_state(config: GameConfig) -> super::GameState {
+        let mut state = super::GameState::new(config);
+        state.board.fill_region(0, 1, 8, 19, Some(TetriminoType::J));
+        state.current_piece = Some(Tetrimino::from_spec(TetriminoType::T, 3, -1, 0));
+        state.next_pieces[0] = TetriminoType::O;
+        state
+    }
+
+    #[test]
+    fn test_hard_drop_tops_out_on_near_full_board() {
+        let state = near_full_state(make_test_config(true));
+        assert!(state.hard_drop_tops_out());
+
+        let mut open = super::GameState::new(make_test_config(true));
+        open.board.fill_region(0, 10, 8, 19, Some(TetriminoType::J));
+        open.current_piece = Some(Tetrimino::from_spec(TetriminoType::T, 3, 0, 0));
+        assert!(!open.hard_drop_tops_out());
+    }
+
+    #[test]
+    fn test_safe_hard_drop_needs_a_second_press() {
+        let config = GameConfig {
+            safe_hard_drop: true,
+            ..make_test_config(true)
+        };
+        let mut state = near_full_state(config);
+        let now = Instant::now();
+
+        assert!(!state.request_hard_drop(now));
+        assert!(state.hard_drop_warning(now));
+        assert!(!state.is_game_over());
+
+        assert!(state.request_hard_drop(now + Duration::from_millis(300)));
+        assert!(state.is_game_over());
+    }
+
+    #[test]
+    fn test_safe_hard_drop_warning_expires() {
+        let config = GameConfig {
+            safe_hard_drop: true,
+            ..make_test_config(true)
+        };
+        let mut state = near_full_state(config);
+        let now = Instant::now();
+
+        assert!(!state.request_hard_drop(now));
+        let later = now + HARD_DROP_CONFIRM_WINDOW;
+        assert!(!state.hard_drop_warning(later));
+        assert!(!state.request_hard_drop(later));
+        assert!(!state.is_game_over());
+    }
+
+    #[test]
+    fn test_queue_snapshot_round_trips_through_json() {
+        let config = GameConfig {
+            seed: Some(11),
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        state.spawn_piece();
+        state.hold_piece();
+        let snapshot = state.queue_snapshot();
+        assert_eq!(snapshot.next_pieces, state.next_pieces);
+        assert!(snapshot.held_piece.is_some());
+        assert_eq!(snapshot.bag, state.remaining_bag());
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let held = snapshot.held_piece.unwrap().to_char();
+        assert!(json.contains(&format!("\"held_piece\":\"{}\"", held)));
+        let restored: QueueSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, snapshot);
+    }
+
+    #[test]
+    fn test_upcoming_is_deterministic_and_keeps_deal_order() {
+        let config = GameConfig {
+            seed: Some(42),
+            ..make_test_config(true)
+        };
+        let mut peeked = super::GameState::new(config.clone());
+        let mut plain = super::GameState::new(config);
+        peeked.spawn_piece();
+        plain.spawn_piece();
+        let queue_before = peeked.next_pieces.clone();
+
+        let ahead = peeked.upcoming(10);
+        assert_eq!(ahead.len(), 11);
+        assert_eq!(ahead[0], peeked.current_piece.unwrap().kind);
+        assert_eq!(ahead[1..4], queue_before[..]);
+        assert_eq!(peeked.upcoming(10), ahead);
+
+        let mut dealt = vec![plain.current_piece.unwrap().kind];
+        for _ in 0..10 {
+            plain.current_piece = None;
+            plain.spawn_piece();
+            dealt.push(plain.current_piece.unwrap().kind);
+        }
+        assert_eq!(dealt, ahead);
+    }
+
+    #[test]
+    fn test_upcoming_leaves_queue_and_bag_alone() {
+        let config = GameConfig {
+            seed: Some(7),
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        state.spawn_piece();
+        let next_before = state.next_pieces.clone();
+        let bag_before = state.remaining_bag();
+        let checksum_before = state.state_checksum();
+
+        let ahead = state.upcoming(10);
+
+        assert_eq!(state.next_pieces, next_before);
+        assert_eq!(state.remaining_bag(), bag_before);
+        assert_eq!(state.state_checksum(), checksum_before);
+
+        // The real generator still deals what the peek promised
+        for &expected in &ahead[1..] {
+            state.current_piece = None;
+            state.spawn_piece();
+            assert_eq!(state.current_piece.unwrap().kind, expected);
+        }
+    }
+
+    #[test]
+    fn test_populate_next_pieces_after_raising_preview_count() {
+        let mut state = super::GameState::new(make_test_config(true));
+        assert_eq!(state.next_pieces.len(), 3);
+
+        state.config.preview_count = 5;
+        state.populate_next_pieces();
+
+        assert_eq!(state.next_pieces.len(), 5);
+    }
+
+    #[test]
+    fn test_spawn_piece_refills_next_pieces() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        let initial_next_len = state.next_pieces.len();
+        state.spawn_piece();
+
+        assert_eq!(state.next_pieces.len(), initial_next_len);
+    }
+
+    #[test]
+    fn test_game_over_flag() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        assert!(!state.is_game_over());
+
+        state.end_game();
+
+        assert!(state.is_game_over());
+    }
+
+    #[test]
+    fn test_level_increases_with_lines() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        state.lines_until_next_level = 2;
+        state.update_level_fixed_goal(5);
+
+        assert_eq!(state.level, 2);
+    }
+
+    #[test]
+    fn test_simulate_placements_tetris() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        // Vertical I pieces occupy column x + 2, so fill columns 0..=9 left to right
+        let placements: Vec<_> = (0..10).map(|col| (TetriminoType::I, col - 2, 1)).collect();
+        let result = state.simulate_placements(&placements);
+
+        assert_eq!(result.lines_cleared, 4);
+        assert_eq!(result.score, 800);
+        assert!(!result.game_over);
+        assert!(state.board.get_full_lines().is_empty());
+    }
+
+    #[test]
+    fn test_simulate_placements_stops_on_topout() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        // Stack vertical I pieces in one column until the next one can't fit
+        let placements = vec![(TetriminoType::I, -2, 1); 6];
+        let result = state.simulate_placements(&placements);
+
+        assert!(result.game_over);
+        assert_eq!(result.lines_cleared, 0);
+    }
+
+    #[test]
+    fn test_simulate_placements_locks_without_hard_drop_locks() {
+        let config = GameConfig {
+            hard_drop_locks: false,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+
+        let placements: Vec<_> = (0..10).map(|col| (TetriminoType::I, col - 2, 1)).collect();
+        let result = state.simulate_placements(&placements);
+
+        assert_eq!(result.lines_cleared, 4);
+        assert!(!result.game_over);
+        assert!(state.board.get_full_lines().is_empty());
+    }
+
+    #[test]
+    fn test_line_clear_emits_lines_cleared_event() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        for x in 0..6 {
+            state.board.cells_mut()[19][x] = Some(TetriminoType::O);
+        }
+        let mut piece = Tetrimino::new(TetriminoType::I);
+        piece.x = 6;
+        piece.y = 19;
+        state.current_piece = Some(piece);
+
+        state.lock_current_piece();
+        state.complete_line_clear();
+
+        let events = state.drain_events();
+        assert!(events.contains(&GameEvent::LinesCleared {
+            lines: 1,
+            score_delta: 100,
+        }));
+        assert!(state.drain_events().is_empty());
+    }
+
+    #[test]
+    fn test_level_up_emits_event() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        state.lines_until_next_level = 1;
+        state.update_score(1, true);
+
+        assert!(
+            state
+                .drain_events()
+                .contains(&GameEvent::LevelUp { level: 2 })
+        );
+    }
+
+    #[test]
+    fn test_update_score_tracks_max_combo() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        clear_lines(&mut state, 1);
+        clear_lines(&mut state, 1);
+        clear_lines(&mut state, 1);
+        state.update_score(0, false);
+        clear_lines(&mut state, 1);
+
+        assert_eq!(state.max_combo(), 3);
+    }
+
+    #[test]
+    fn test_update_score_tracks_max_b2b_chain() {
+        let config = make_test_config(true);
+        let mut state = super::GameState::new(config);
+
+        // The first Tetris starts the chain; the next three are back-to-back
+        for _ in 0..4 {
+            clear_lines(&mut state, 4);
+        }
+        assert_eq!(state.max_b2b_chain(), 3);
+
+        // Non-clearing pieces keep the chain alive
+        state.update_score(0, false);
+        clear_lines(&mut state, 4);
+        assert_eq!(state.max_b2b_chain(), 4);
+
+        // A plain single breaks it, but the best stays
+        clear_lines(&mut state, 1);
+        clear_lines(&mut state, 4);
+        clear_lines(&mut state, 4);
+        assert_eq!(state.max_b2b_chain(), 4);
+        assert_eq!(state.b2b_chain, 1);
+    }
+
+    #[test]
+    fn test_result_serializes_to_expected_json() {
+        let config = GameConfig {
+            seed: Some(42),
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        state.score = 1200;
+        state.level = 3;
+        state.lines_cleared = 25;
+        state.pieces_placed = 90;
+        state.max_combo = 4;
+        state.finished_at = Some(state.started_at + Duration::from_secs(60));
+
+        let json = serde_json::to_value(state.result()).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "mode": "Marathon",
+                "seed": 42,
+                "score": 1200,
+                "level": 3,
+                "lines": 25,
+                "duration_ms": 60000,
+                "pps": 1.5,
+                "finesse_faults": null,
+                "max_combo": 4,
+            })
+        );
+    }
+
+    fn planning_config() -> GameConfig {
+        GameConfig {
+            planning_mode: true,
+            ..make_test_config(true)
+        }
+    }
+
+    #[test]
+    fn test_planning_ghosts_land_in_deepest_columns() {
+        let mut state = super::GameState::new(planning_config());
+        state.next_pieces = vec![TetriminoType::O, TetriminoType::I, TetriminoType::T];
+        // Everything but columns 6-9 is filled up to row 10
+        for y in 10..20 {
+            for x in 0..6 {
+                state.board.cells_mut()[y][x] = Some(TetriminoType::J);
+            }
+        }
+
+        let ghosts = state.planning_ghosts();
+
+        assert_eq!(ghosts.len(), 2);
+        assert_eq!(
+            (ghosts[0].kind, ghosts[0].x, ghosts[0].y),
+            (TetriminoType::O, 6, 18)
+        );
+        assert_eq!(
+            (ghosts[1].kind, ghosts[1].x, ghosts[1].y),
+            (TetriminoType::I, 6, 19)
+        );
     }
 
     #[test]
-    fn test_update_score_zero_lines() {
-        let config = make_test_config(true);
-        let mut state = super::GameState::new(config);
-
-        state.score = 100;
-        state.combo_count = 5;
+    fn test_planning_ghosts_are_independent_of_each_other() {
+        let mut state = super::GameState::new(planning_config());
+        state.next_pieces = vec![TetriminoType::O, TetriminoType::O];
 
-        state.update_score(0, false);
+        let ghosts = state.planning_ghosts();
 
-        assert_eq!(state.score, 100);
-        assert_eq!(state.combo_count, 0);
+        // Both drop into the same empty spot since neither is locked
+        assert_eq!(ghosts[0], ghosts[1]);
+        assert_eq!((ghosts[0].x, ghosts[0].y), (0, 18));
     }
 
     #[test]
-    fn test_compute_awarded_lines_no_tspin() {
-        let config = make_test_config(true);
-        let state = super::GameState::new(config);
+    fn test_planning_ghosts_toggle_and_gate() {
+        let mut state = super::GameState::new(planning_config());
+        assert!(!state.planning_ghosts().is_empty());
 
-        assert_eq!(state.compute_awarded_lines(1, false), 1);
-        assert_eq!(state.compute_awarded_lines(2, false), 2);
-        assert_eq!(state.compute_awarded_lines(3, false), 3);
-        assert_eq!(state.compute_awarded_lines(4, false), 4);
+        state.toggle_planning();
+        assert!(state.planning_ghosts().is_empty());
+
+        let mut state = super::GameState::new(make_test_config(true));
+        state.toggle_planning();
+        assert!(state.show_planning);
+        assert!(state.planning_ghosts().is_empty());
     }
 
     #[test]
-    fn test_compute_awarded_lines_with_tspin() {
+    fn test_level_up_sets_banner() {
         let config = make_test_config(true);
-        let state = super::GameState::new(config);
+        let mut state = super::GameState::new(config);
+        assert_eq!(state.level_up_banner(Instant::now()), None);
 
-        assert_eq!(state.compute_awarded_lines(1, true), 1);
-        assert_eq!(state.compute_awarded_lines(2, true), 2);
-        assert_eq!(state.compute_awarded_lines(3, true), 3);
+        state.lines_until_next_level = 1;
+        state.update_score(1, true);
+
+        assert_eq!(state.level_up_banner(Instant::now()), Some(2));
     }
 
     #[test]
-    fn test_check_tspin_always_false() {
-        let config = make_test_config(true);
-        let state = super::GameState::new(config);
+    fn test_highlight_holes_records_new_holes_and_flashes() {
+        let config = GameConfig {
+            highlight_holes: true,
+            ..make_test_config(true)
+        };
+        let mut state = super::GameState::new(config);
+        // A flat I laid on a one-high stub roofs over the three cells beside it
+        state.board.set_cell(0, 19, Some(TetriminoType::O)).unwrap();
+        let mut piece = Tetrimino::new(TetriminoType::I);
+        piece.x = 0;
+        piece.y = 18;
+        state.current_piece = Some(piece);
+        state.lock_current_piece();
 
-        assert!(!state.check_tspin());
+        let at = state.new_holes_at.unwrap();
+        assert_eq!(state.highlighted_holes(at), &[(1, 19), (2, 19), (3, 19)]);
+        assert!(state.highlighted_holes(at + HOLE_FLASH_INTERVAL).is_empty());
+        assert_eq!(
+            state.highlighted_holes(at + HOLE_FLASH_INTERVAL * 2).len(),
+            3
+        );
+        assert!(
+            state
+                .highlighted_holes(at + HOLE_HIGHLIGHT_DURATION)
+                .is_empty()
+        );
     }
 
     #[test]
-    fn test_update_level_fixed_goal() {
-        let config = make_test_config(true);
-        let mut state = super::GameState::new(config);
+    fn test_highlight_holes_off_records_nothing() {
+        let mut state = super::GameState::new(make_test_config(true));
+        state.board.set_cell(0, 19, Some(TetriminoType::O)).unwrap();
+        let mut piece = Tetrimino::new(TetriminoType::I);
+        piece.y = 18;
+        state.current_piece = Some(piece);
+        state.lock_current_piece();
 
-        state.update_level_fixed_goal(5);
+        assert!(state.highlighted_holes(Instant::now()).is_empty());
+    }
 
-        assert_eq!(state.lines_until_next_level, 5);
-        assert_eq!(state.level, 1);
+    /// Locks a flat I on the bottom row, finishing a line already filled except for
+    /// `x = 6..10`, and completes the clear.
+    fn clear_one_line(state: &mut super::GameState) {
+        state
+            .board
+            .fill_region(0, 19, 5, 19, Some(TetriminoType::O));
+        state.current_piece = Some(Tetrimino::from_spec(TetriminoType::I, 6, 19, 0));
+        state.lock_current_piece();
+        state.complete_line_clear();
     }
 
     #[test]
-    fn test_update_level_fixed_goal_level_up() {
-        let config = make_test_config(true);
+    fn test_lines_this_level_resets_on_level_up() {
+        let config = GameConfig {
+            lines_per_level: 3,
+            ..make_test_config(true)
+        };
         let mut state = super::GameState::new(config);
 
-        state.update_level_fixed_goal(15);
+        clear_one_line(&mut state);
+        clear_one_line(&mut state);
+        assert_eq!(state.lines_this_level, 2);
+        assert!((state.level_progress() - 2.0 / 3.0).abs() < 1e-9);
 
+        clear_one_line(&mut state);
         assert_eq!(state.level, 2);
-        assert_eq!(state.lines_until_next_level, 5);
+        assert_eq!(state.lines_this_level, 0);
+        assert_eq!(state.level_progress(), 0.0);
+
+        clear_one_line(&mut state);
+        assert_eq!(state.lines_this_level, 1);
+        assert!((state.level_progress() - 1.0 / 3.0).abs() < 1e-9);
     }
 
-    #[test]
-    fn test_update_level_fixed_goal_exact_level_up() {
-        let config = make_test_config(true);
-        let mut state = super::GameState::new(config);
+    /// Sets up 9 of 10 lines toward the next level, then clears a Tetris.
+    fn tetris_from_nine_of_ten(config: GameConfig) -> super::GameState {
+        let mut state = super::GameState::new(GameConfig {
+            lines_per_level: 10,
+            ..config
+        });
+        state.lines_this_level = 9;
+        state.lines_until_next_level = 1;
+        state
+            .board
+            .fill_region(0, 16, 8, 19, Some(TetriminoType::O));
+        state.current_piece = Some(Tetrimino::from_spec(TetriminoType::I, 7, 16, 1));
+        state.lock_current_piece();
+        state.complete_line_clear();
+        state
+    }
 
-        state.update_level_fixed_goal(10);
+    #[test]
+    fn test_overflowing_tetris_carries_into_next_level() {
+        let state = tetris_from_nine_of_ten(make_test_config(true));
 
         assert_eq!(state.level, 2);
-        assert_eq!(state.lines_until_next_level, 10);
+        assert_eq!(state.lines_this_level, 3);
+        assert_eq!(state.lines_until_next_level, 7);
+        assert!((state.level_progress() - 0.3).abs() < 1e-9);
     }
 
     #[test]
-    fn test_update_level_variable_goal() {
-        let config = GameConfig {
+    fn test_overflowing_tetris_carries_into_next_level_with_variable_goal() {
+        let state = tetris_from_nine_of_ten(GameConfig {
             enable_variable_goal: true,
             ..make_test_config(true)
-        };
-        let mut state = super::GameState::new(config);
-
-        state.pieces_placed = 10;
-        state.update_level_variable_goal(5, false);
+        });
 
-        assert_eq!(state.level, 1);
+        assert_eq!(state.level, 2);
+        assert_eq!(state.lines_this_level, 3);
+        let goal = state.lines_this_level + state.lines_until_next_level;
+        assert!((state.level_progress() - 3.0 / goal as f64).abs() < 1e-9);
     }
 
     #[test]
-    fn test_update_level_variable_goal_level_up() {
+    fn test_level_progress_with_variable_goal() {
         let config = GameConfig {
             enable_variable_goal: true,
             ..make_test_config(true)
         };
         let mut state = super::GameState::new(config);
+        state.lines_this_level = 3;
+        state.lines_until_next_level = 9;
+        assert_eq!(state.level_progress(), 0.25);
 
-        state.pieces_placed = 10;
-        state.update_level_variable_goal(15, false);
-
-        assert_eq!(state.level, 2);
+        // The last level of a threshold table has no further goal
+        state.lines_until_next_level = 0;
+        assert_eq!(state.level_progress(), 1.0);
     }
 
     #[test]
-    fn test_get_wall_kicks_i_piece() {
-        let config = make_test_config(true);
-        let state = super::GameState::new(config);
+    fn test_ultra_clock_stops_while_paused() {
+        let mut state = super::GameState::with_mode(make_test_config(true), GameMode::Ultra);
+        let now = Instant::now();
+        let start = now - Duration::from_secs(150);
+        state.start_clock(start);
+        state.spawn_piece();
 
-        let kicks_0_1 = state.get_wall_kicks(TetriminoType::I, 0, 1);
-        assert!(!kicks_0_1.is_empty());
-        assert_eq!(kicks_0_1[0], (0, 0));
+        // Paused with 20 seconds left, for 50 seconds
+        state.toggle_pause();
+        state.paused_at = Some(start + Duration::from_secs(100));
+        state.toggle_pause();
+        state.tick(now, false);
 
-        let kicks_1_0 = state.get_wall_kicks(TetriminoType::I, 1, 0);
-        assert!(!kicks_1_0.is_empty());
+        assert!(!state.is_game_over());
+        let remaining = state.time_remaining(now).unwrap();
+        assert!(remaining >= Duration::from_secs(20) && remaining < Duration::from_secs(21));
     }
 
     #[test]
-    fn test_get_wall_kicks_o_piece() {
-        let config = make_test_config(true);
-        let state = super::GameState::new(config);
+    fn test_lock_delay_waits_out_a_pause() {
+        let mut state = super::GameState::new(make_test_config(true));
+        state.spawn_piece();
+        let started = Instant::now() - Duration::from_millis(400);
+        state.phase = Phase::LockDelay { started };
 
-        let kicks = state.get_wall_kicks(TetriminoType::O, 0, 1);
-        assert_eq!(kicks, vec![(0, 0)]);
+        state.toggle_pause();
+        state.paused_at = Some(started + Duration::from_millis(100));
+        state.toggle_pause();
+
+        // 400 ms have passed, but only 100 of them unpaused
+        assert!(!state.lock_delay_expired(Instant::now()));
     }
 
     #[test]
-    fn test_get_wall_kicks_t_piece() {
-        let config = make_test_config(true);
-        let state = super::GameState::new(config);
+    fn test_settings_menu_stops_the_clock() {
+        let mut state = super::GameState::with_mode(make_test_config(true), GameMode::Ultra);
+        let now = Instant::now();
+        let start = now - Duration::from_secs(150);
+        state.start_clock(start);
+        state.spawn_piece();
 
-        let kicks_0_1 = state.get_wall_kicks(TetriminoType::T, 0, 1);
-        assert_eq!(kicks_0_1, vec![(0, 0), (0, -1), (-1, 0), (-1, -1)]);
+        state.set_settings_open(true);
+        state.paused_at = Some(start + Duration::from_secs(100));
+        let piece = state.current_piece;
+        state.tick(now, false);
+        assert_eq!(state.current_piece, piece);
 
-        let kicks_1_2 = state.get_wall_kicks(TetriminoType::T, 1, 2);
-        assert_eq!(kicks_1_2, vec![(0, 0), (0, -1), (1, 0), (1, -1)]);
+        state.set_settings_open(false);
+        state.tick(now, false);
+        assert!(!state.is_game_over());
+        assert!(state.time_remaining(now).unwrap() >= Duration::from_secs(20));
     }
 
     #[test]
-    fn test_get_wall_kicks_default() {
-        let config = make_test_config(true);
-        let state = super::GameState::new(config);
-
-        let kicks = state.get_wall_kicks(TetriminoType::Z, 2, 2);
-        assert_eq!(kicks, vec![(0, 0)]);
-    }
+    fn test_start_clock_resets_game_and_level_clocks() {
+        let mut state = super::GameState::with_mode(make_test_config(true), GameMode::Ultra);
+        let later = state.started_at + Duration::from_secs(90);
 
-    #[test]
-    fn test_is_line_clear_animation_active_no_animation() {
-        let config = make_test_config(true);
-        let state = super::GameState::new(config);
+        state.start_clock(later);
 
-        assert!(!state.is_line_clear_animation_active());
+        assert_eq!(state.started_at, later);
+        assert_eq!(state.level_started_at, later);
+        // Ultra's two minutes count from the start, not from setup
+        state.check_time_limit(later + Duration::from_secs(60));
+        assert!(!state.is_game_over());
     }
 
     #[test]
-    fn test_is_line_clear_animation_active_with_animation() {
+    fn test_level_up_restarts_level_clock() {
         let config = make_test_config(true);
         let mut state = super::GameState::new(config);
+        let earlier = Instant::now() - Duration::from_secs(30);
+        state.level_started_at = earlier;
 
-        state.line_clear_animation = Some(super::LineClearAnimation {
-            cleared_rows: vec![19],
-            start_time: std::time::Instant::now(),
-            total_lines: 1,
-        });
+        state.update_score(1, true);
+        assert_eq!(state.level_started_at, earlier);
 
-        assert!(state.is_line_clear_animation_active());
+        state.lines_until_next_level = 1;
+        state.update_score(1, true);
+        assert!(state.level_started_at > earlier);
     }
 
     #[test]
-    fn test_should_show_cleared_rows_no_animation() {
+    fn test_level_up_banner_timing() {
         let config = make_test_config(true);
-        let state = super::GameState::new(config);
-
-        assert!(!state.should_show_cleared_rows());
+        let mut state = super::GameState::new(config);
+        let start = Instant::now();
+        state.level_up_at = Some((4, start));
+
+        assert_eq!(state.level_up_banner(start), Some(4));
+        assert_eq!(
+            state.level_up_banner(start + Duration::from_millis(699)),
+            Some(4)
+        );
+        assert_eq!(
+            state.level_up_banner(start + Duration::from_millis(700)),
+            None
+        );
     }
 
     #[test]
-    fn test_should_show_cleared_rows_with_animation() {
+    fn test_end_game_emits_single_game_over_event() {
         let config = make_test_config(true);
         let mut state = super::GameState::new(config);
 
-        state.line_clear_animation = Some(super::LineClearAnimation {
-            cleared_rows: vec![19],
-            start_time: std::time::Instant::now(),
-            total_lines: 2,
-        });
+        state.score = 1234;
+        state.end_game();
+        state.end_game();
 
-        assert!(state.should_show_cleared_rows());
+        assert!(state.is_game_over());
+        assert_eq!(
+            state.drain_events(),
+            vec![GameEvent::GameOver { score: 1234 }]
+        );
     }
 
     #[test]
-    fn test_line_clear_animation_struct() {
-        let anim = super::LineClearAnimation {
-            cleared_rows: vec![18, 19],
-            start_time: std::time::Instant::now(),
-            total_lines: 2,
-        };
-
-        assert_eq!(anim.cleared_rows.len(), 2);
-        assert_eq!(anim.total_lines, 2);
+    fn test_seven_bag_never_repeats_within_a_bag() {
+        for seed in 0..20 {
+            let sequence = generate_sequence(Randomizer::SevenBag, seed, 70);
+            for bag in sequence.chunks(7) {
+                assert!(!has_repeat(bag), "seed {} produced {:?}", seed, bag);
+            }
+        }
     }
 
     #[test]
-    fn test_score_accumulates() {
-        let config = make_test_config(true);
-        let mut state = super::GameState::new(config);
-
-        state.score = 100;
-        state.update_score(1, true);
+    fn test_double_bag_deals_each_type_twice_per_fourteen() {
+        for seed in 0..10 {
+            let config = GameConfig {
+                seed: Some(seed),
+                bag_repeats: 2,
+                ..make_test_config(true)
+            };
+            let mut state = super::GameState::new(config);
+            let mut sequence = state.next_pieces.clone();
+            while sequence.len() < 70 {
+                sequence.push(state.generate_piece());
+            }
 
-        assert!(state.score > 100);
+            for bag in sequence[..70].chunks(14) {
+                for kind in TetriminoType::ALL {
+                    let count = bag.iter().filter(|&&k| k == kind).count();
+                    assert_eq!(count, 2, "seed {} produced {:?}", seed, bag);
+                }
+            }
+        }
     }
 
     #[test]
-    fn test_lines_cleared_accumulates() {
-        let config = make_test_config(true);
-        let mut state = super::GameState::new(config);
+    fn test_true_random_can_repeat_within_seven() {
+        let sequence = generate_sequence(Randomizer::TrueRandom, 7, 70);
+        assert!(sequence.chunks(7).any(has_repeat));
+    }
 
-        state.lines_cleared = 5;
-        for x in 0..10 {
-            state.board.cells_mut()[19][x] = Some(TetriminoType::I);
+    #[test]
+    fn test_tgm_history_first_piece_not_s_z_or_o() {
+        for seed in 0..500 {
+            let first = generate_sequence(Randomizer::TgmHistory, seed, 1)[0];
+            assert!(
+                ![TetriminoType::S, TetriminoType::Z, TetriminoType::O].contains(&first),
+                "seed {} opened with {:?}",
+                seed,
+                first
+            );
         }
-        state.pending_line_clear = true;
-        state.line_clear_animation = Some(super::LineClearAnimation {
-            cleared_rows: vec![19],
-            start_time: std::time::Instant::now(),
-            total_lines: 1,
-        });
-
-        state.complete_line_clear();
-
-        assert_eq!(state.lines_cleared, 6);
     }
 
     #[test]
-    fn test_back_to_back_state() {
-        let config = make_test_config(true);
-        let mut state = super::GameState::new(config);
-
-        state.back_to_back_active = true;
-        state.update_score(4, true);
-
-        assert!(state.back_to_back_active);
+    fn test_same_seed_same_sequence() {
+        for randomizer in [
+            Randomizer::SevenBag,
+            Randomizer::TrueRandom,
+            Randomizer::TgmHistory,
+        ] {
+            assert_eq!(
+                generate_sequence(randomizer, 99, 30),
+                generate_sequence(randomizer, 99, 30)
+            );
+        }
     }
 
     #[test]
-    fn test_combo_count() {
-        let config = make_test_config(true);
+    fn test_export_summary_contains_board_and_stats() {
+        let config = GameConfig {
+            seed: Some(7),
+            ..make_test_config(true)
+        };
         let mut state = super::GameState::new(config);
+        state.score = 4200;
+        state.level = 3;
+        state.lines_cleared = 21;
+        let mut piece = Tetrimino::new(TetriminoType::I);
+        piece.y = 19;
+        state.board.lock_tetromino(&piece);
 
-        state.combo_count = 2;
-        state.update_score(2, true);
-
-        assert_eq!(state.combo_count, 3);
+        let summary = state.export_summary();
+
+        assert!(summary.contains("Mode:  Marathon"));
+        assert!(summary.contains("Score: 4200"));
+        assert!(summary.contains("Level: 3"));
+        assert!(summary.contains("Lines: 21"));
+        assert!(summary.contains("Time:  00:00"));
+        assert!(summary.contains("Seed:  7"));
+        assert!(summary.contains("|..........|"));
+        assert!(summary.contains("|IIII......|"));
+        assert_eq!(summary.matches("+----------+").count(), 2);
+        assert_eq!(summary.lines().filter(|l| l.starts_with('|')).count(), 20);
     }
 
     #[test]
-    fn test_pieces_placed() {
+    fn test_queue_piece_front_spawns_next() {
         let config = make_test_config(true);
         let mut state = super::GameState::new(config);
+        let original_next = state.next_pieces.clone();
 
-        assert_eq!(state.pieces_placed, 0);
-
+        state.queue_piece_front(TetriminoType::Z);
         state.spawn_piece();
-        state.lock_current_piece();
 
-        assert_eq!(state.pieces_placed, 1);
+        assert_eq!(state.current_piece.unwrap().kind, TetriminoType::Z);
+        assert_eq!(state.next_pieces, original_next);
     }
 
     #[test]
-    fn test_hold_disabled_returns_early() {
-        let config = make_test_config(false);
+    fn test_queue_piece_front_stacks_in_order() {
+        let config = make_test_config(true);
         let mut state = super::GameState::new(config);
 
-        state.current_piece = Some(Tetrimino::new(TetriminoType::T));
-        state.hold_piece();
+        state.queue_piece_front(TetriminoType::O);
+        state.queue_piece_front(TetriminoType::I);
 
-        assert_eq!(state.current_piece.unwrap().kind, TetriminoType::T);
-        assert_eq!(state.held_piece, None);
+        state.spawn_piece();
+        assert_eq!(state.current_piece.unwrap().kind, TetriminoType::I);
+        state.spawn_piece();
+        assert_eq!(state.current_piece.unwrap().kind, TetriminoType::O);
     }
 
     #[test]
-    fn test_all_tetrimino_types_in_bag() {
+    fn test_board_coverage_empty() {
         let config = make_test_config(true);
         let state = super::GameState::new(config);
 
-        assert!(state.bag.len() < 7);
-        assert_eq!(state.next_pieces.len(), 3);
+        assert_eq!(state.board_coverage(), 0.0);
     }
 
     #[test]
-    fn test_next_pieces_populated() {
+    fn test_board_coverage_half() {
         let config = make_test_config(true);
-        let state = super::GameState::new(config);
+        let mut state = super::GameState::new(config);
+        for y in 10..20 {
+            for x in 0..10 {
+                state.board.cells_mut()[y][x] = Some(TetriminoType::I);
+            }
+        }
 
-        assert_eq!(state.next_pieces.len(), 3);
-        assert!(!state.current_piece.is_some());
+        assert_eq!(state.board_coverage(), 0.5);
     }
 
     #[test]
-    fn test_spawn_piece_refills_next_pieces() {
+    fn test_board_coverage_full() {
         let config = make_test_config(true);
         let mut state = super::GameState::new(config);
+        for y in 0..20 {
+            for x in 0..10 {
+                state.board.cells_mut()[y][x] = Some(TetriminoType::I);
+            }
+        }
 
-        let initial_next_len = state.next_pieces.len();
-        state.spawn_piece();
-
-        assert_eq!(state.next_pieces.len(), initial_next_len);
+        assert_eq!(state.board_coverage(), 1.0);
     }
 
     #[test]
-    fn test_game_over_flag() {
-        let config = make_test_config(true);
+    fn test_remaining_bag_after_spawns() {
+        let config = GameConfig {
+            seed: Some(3),
+            ..make_test_config(true)
+        };
         let mut state = super::GameState::new(config);
+        assert_eq!(state.remaining_bag().len(), 4);
 
-        assert!(!state.game_over);
-
-        state.game_over = true;
-
-        assert!(state.game_over);
-    }
+        let mut dealt = Vec::new();
+        for _ in 0..2 {
+            state.spawn_piece();
+            dealt.push(state.current_piece.unwrap().kind);
+        }
 
-    #[test]
-    fn test_level_increases_with_lines() {
-        let config = make_test_config(true);
-        let mut state = super::GameState::new(config);
+        let remaining = state.remaining_bag();
+        assert_eq!(remaining.len(), 2);
 
-        state.lines_until_next_level = 2;
-        state.update_level_fixed_goal(5);
+        // Everything dealt, queued, and left over makes up exactly one bag
+        dealt.extend(&state.next_pieces);
+        dealt.extend(&remaining);
+        for kind in TetriminoType::ALL {
+            assert_eq!(dealt.iter().filter(|&&k| k == kind).count(), 1);
+        }
 
-        assert_eq!(state.level, 2);
+        // The next piece pulled into the queue is the first remaining one
+        state.spawn_piece();
+        assert_eq!(state.next_pieces.last(), Some(&remaining[0]));
     }
 
     #[test]
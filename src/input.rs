@@ -1,20 +1,172 @@
+use crate::tetrimino::TetriminoType;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use std::time::{Duration, Instant};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum InputAction {
     MoveLeft,
     MoveRight,
     MoveDown,
+    /// Slide the piece left until it is blocked.
+    MoveToLeftWall,
+    /// Slide the piece right until it is blocked.
+    MoveToRightWall,
     RotateClockwise,
     RotateCounterClockwise,
     HardDrop,
+    /// Drop to the landing row without locking, as in TGM.
+    SonicDrop,
     Hold,
     Pause,
     ToggleHelp,
+    /// Show or hide the ghost piece.
+    ToggleGhost,
+    /// Planning mode only: show or hide landing previews for the next pieces.
+    TogglePlanning,
+    OpenSettings,
+    /// Debug builds only: show the randomizer bag and queue.
+    ToggleDebug,
+    /// Practice mode only: force the given piece to come next.
+    SelectNext(TetriminoType),
     Quit,
 }
 
-pub struct InputHandler;
+/// A mapped key going down, or coming back up on terminals that report releases.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum InputEvent {
+    Pressed(InputAction),
+    Released(InputAction),
+}
+
+/// Navigation keys for the settings screen.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MenuInput {
+    Up,
+    Down,
+    Decrease,
+    Increase,
+    Close,
+}
+
+/// Choices on the game-over screen.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GameOverAction {
+    Restart,
+    /// Restart with the same seed, so the same pieces come again.
+    Replay,
+    /// Step back through the game that just ended.
+    Review,
+    Quit,
+}
+
+/// Controls for the replay viewer.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ReplayInput {
+    TogglePlay,
+    /// Place the next piece.
+    Step,
+    /// Place pieces up to the next line clear.
+    NextClear,
+    Close,
+}
+
+/// Timing for a held key: nothing until `delay` has passed, then one repeat every
+/// `interval`. The initial press is handled by the caller, which `press` tells
+/// whether to move.
+#[derive(Debug, Clone, Copy)]
+pub struct AutoRepeat {
+    delay: Duration,
+    interval: Duration,
+    initial_tap: bool,
+    held_since: Option<Instant>,
+    repeats_fired: u64,
+}
+
+impl AutoRepeat {
+    pub fn new(delay: Duration, interval: Duration) -> Self {
+        Self {
+            delay,
+            interval: interval.max(Duration::from_millis(1)),
+            initial_tap: true,
+            held_since: None,
+            repeats_fired: 0,
+        }
+    }
+
+    /// Whether repeats wait out the delay after the press (the default). Without it
+    /// they start one interval after the press, charging right away.
+    pub fn with_initial_tap(mut self, initial_tap: bool) -> Self {
+        self.initial_tap = initial_tap;
+        self
+    }
+
+    /// Starts timing a held key. The caller acts on the press itself once.
+    pub fn press(&mut self, now: Instant) {
+        self.held_since = Some(now);
+        self.repeats_fired = 0;
+    }
+
+    pub fn release(&mut self) {
+        self.held_since = None;
+    }
+
+    /// Number of repeats that have come due since the last poll.
+    pub fn poll(&mut self, now: Instant) -> u64 {
+        let Some(held_since) = self.held_since else {
+            return 0;
+        };
+        let held = now.saturating_duration_since(held_since);
+        let delay = if self.initial_tap {
+            self.delay
+        } else {
+            self.interval
+        };
+        if held < delay {
+            return 0;
+        }
+
+        let due = ((held - delay).as_micros() / self.interval.as_micros()) as u64 + 1;
+        let fired = due - self.repeats_fired;
+        self.repeats_fired = due;
+        fired
+    }
+}
+
+/// Minimum time between hard drops while the key has not been released, so a held
+/// space bar can't drop several pieces in a row.
+const HARD_DROP_COOLDOWN: Duration = Duration::from_millis(150);
+
+/// Bounds for `poll_timeout_ms`: waits always block for a little while instead of
+/// spinning, and never so long that animated screens stop redrawing.
+const MIN_POLL_TIMEOUT: Duration = Duration::from_millis(1);
+const MAX_POLL_TIMEOUT: Duration = Duration::from_millis(250);
+/// Poll timeout used until `with_poll_timeout` sets one, about a frame.
+const DEFAULT_POLL_TIMEOUT_MS: u64 = 16;
+
+/// How long idle screens wait for input per check, from `poll_timeout_ms`.
+pub fn poll_timeout(ms: u64) -> Duration {
+    Duration::from_millis(ms).clamp(MIN_POLL_TIMEOUT, MAX_POLL_TIMEOUT)
+}
+
+/// The next key press within `timeout`, if any. Other events are consumed.
+fn read_key_press(timeout: Duration) -> Option<KeyCode> {
+    if let Ok(true) = event::poll(timeout)
+        && let Ok(Event::Key(KeyEvent {
+            code,
+            kind: KeyEventKind::Press,
+            ..
+        })) = event::read()
+    {
+        return Some(code);
+    }
+    None
+}
+
+pub struct InputHandler {
+    hard_drop_cooldown: Duration,
+    last_hard_drop: Option<Instant>,
+    poll_timeout: Duration,
+}
 
 impl Default for InputHandler {
     fn default() -> Self {
@@ -24,22 +176,132 @@ impl Default for InputHandler {
 
 impl InputHandler {
     pub fn new() -> Self {
-        Self
+        Self {
+            hard_drop_cooldown: HARD_DROP_COOLDOWN,
+            last_hard_drop: None,
+            poll_timeout: poll_timeout(DEFAULT_POLL_TIMEOUT_MS),
+        }
+    }
+
+    /// Sets how long the `wait_*` methods block for input, clamped by `poll_timeout`.
+    pub fn with_poll_timeout(mut self, ms: u64) -> Self {
+        self.poll_timeout = poll_timeout(ms);
+        self
     }
 
-    pub fn poll_input(&self) -> Option<InputAction> {
+    /// Returns a pending key press without waiting, for the frame loop.
+    pub fn poll_input(&mut self) -> Option<InputAction> {
+        self.input_within(Duration::ZERO)
+    }
+
+    /// Like `poll_input`, but waits up to the poll timeout for a key.
+    pub fn wait_input(&mut self) -> Option<InputAction> {
+        self.input_within(self.poll_timeout)
+    }
+
+    fn input_within(&mut self, timeout: Duration) -> Option<InputAction> {
+        let action = self.key_to_action(read_key_press(timeout)?)?;
+        self.debounce(InputEvent::Pressed(action), Instant::now())
+            .map(|_| action)
+    }
+
+    /// Drops a hard drop press that arrives within `hard_drop_cooldown` of the last one,
+    /// unless the key was released in between.
+    fn debounce(&mut self, event: InputEvent, now: Instant) -> Option<InputEvent> {
+        match event {
+            InputEvent::Pressed(InputAction::HardDrop) => {
+                if let Some(last) = self.last_hard_drop
+                    && now.saturating_duration_since(last) < self.hard_drop_cooldown
+                {
+                    return None;
+                }
+                self.last_hard_drop = Some(now);
+            }
+            InputEvent::Released(InputAction::HardDrop) => self.last_hard_drop = None,
+            _ => {}
+        }
+        Some(event)
+    }
+
+    /// Like `poll_input`, but also reports key releases. Terminal auto-repeat events
+    /// are dropped, since held keys are repeated with `AutoRepeat` instead.
+    pub fn poll_event(&mut self) -> Option<InputEvent> {
         if let Ok(true) = event::poll(std::time::Duration::from_millis(0))
-            && let Ok(Event::Key(KeyEvent {
-                code,
-                kind: KeyEventKind::Press,
-                ..
-            })) = event::read()
+            && let Ok(Event::Key(KeyEvent { code, kind, .. })) = event::read()
         {
-            return self.key_to_action(code);
+            let action = self.key_to_action(code)?;
+            let event = match kind {
+                KeyEventKind::Press => InputEvent::Pressed(action),
+                KeyEventKind::Release => InputEvent::Released(action),
+                KeyEventKind::Repeat => return None,
+            };
+            return self.debounce(event, Instant::now());
         }
         None
     }
 
+    /// Waits up to the poll timeout for a menu key.
+    pub fn wait_menu_input(&self) -> Option<MenuInput> {
+        self.key_to_menu_input(read_key_press(self.poll_timeout)?)
+    }
+
+    /// Waits for the next key press on the game-over screen. Key releases and
+    /// non-key events are skipped, so letting go of the key that ended the game
+    /// doesn't count as a choice.
+    pub fn wait_game_over_action(&self) -> GameOverAction {
+        loop {
+            match event::read() {
+                Ok(Event::Key(KeyEvent {
+                    code,
+                    kind: KeyEventKind::Press,
+                    ..
+                })) => return self.key_to_game_over_action(code),
+                Ok(_) => continue,
+                Err(_) => return GameOverAction::Quit,
+            }
+        }
+    }
+
+    fn key_to_game_over_action(&self, key_code: KeyCode) -> GameOverAction {
+        match key_code {
+            KeyCode::Char('r') | KeyCode::Char('R') => GameOverAction::Restart,
+            KeyCode::Char('s') | KeyCode::Char('S') => GameOverAction::Replay,
+            KeyCode::Char('v') | KeyCode::Char('V') => GameOverAction::Review,
+            _ => GameOverAction::Quit,
+        }
+    }
+
+    /// Waits up to the poll timeout for a replay viewer key.
+    pub fn wait_replay_input(&self) -> Option<ReplayInput> {
+        self.key_to_replay_input(read_key_press(self.poll_timeout)?)
+    }
+
+    fn key_to_replay_input(&self, key_code: KeyCode) -> Option<ReplayInput> {
+        match key_code {
+            KeyCode::Char(' ') | KeyCode::Char('p') => Some(ReplayInput::TogglePlay),
+            KeyCode::Right | KeyCode::Char('.') => Some(ReplayInput::Step),
+            KeyCode::Down | KeyCode::Char('n') => Some(ReplayInput::NextClear),
+            KeyCode::Esc | KeyCode::Char('q') => Some(ReplayInput::Close),
+            _ => None,
+        }
+    }
+
+    fn key_to_menu_input(&self, key_code: KeyCode) -> Option<MenuInput> {
+        match key_code {
+            KeyCode::Up => Some(MenuInput::Up),
+            KeyCode::Down => Some(MenuInput::Down),
+            KeyCode::Left => Some(MenuInput::Decrease),
+            KeyCode::Right | KeyCode::Enter | KeyCode::Char(' ') => Some(MenuInput::Increase),
+            KeyCode::Esc | KeyCode::Char('o') | KeyCode::Char('q') => Some(MenuInput::Close),
+            _ => None,
+        }
+    }
+
+    /// True if a key is pressed within the poll timeout. Other events are consumed.
+    pub fn wait_key_press(&self) -> bool {
+        read_key_press(self.poll_timeout).is_some()
+    }
+
     pub fn has_input(&self) -> bool {
         event::poll(std::time::Duration::from_millis(0)).unwrap_or(false)
     }
@@ -49,13 +311,24 @@ impl InputHandler {
             KeyCode::Left => Some(InputAction::MoveLeft),
             KeyCode::Right => Some(InputAction::MoveRight),
             KeyCode::Down => Some(InputAction::MoveDown),
+            KeyCode::Up => Some(InputAction::SonicDrop),
+            KeyCode::Home => Some(InputAction::MoveToLeftWall),
+            KeyCode::End => Some(InputAction::MoveToRightWall),
             KeyCode::Char('x') => Some(InputAction::RotateClockwise),
             KeyCode::Char('z') => Some(InputAction::RotateCounterClockwise),
             KeyCode::Char(' ') => Some(InputAction::HardDrop),
             KeyCode::Char('c') => Some(InputAction::Hold),
             KeyCode::Esc | KeyCode::Char('p') => Some(InputAction::Pause),
             KeyCode::Char('h') | KeyCode::Char('H') => Some(InputAction::ToggleHelp),
+            KeyCode::Char('g') => Some(InputAction::ToggleGhost),
+            KeyCode::Char('n') => Some(InputAction::TogglePlanning),
+            KeyCode::Char('o') => Some(InputAction::OpenSettings),
             KeyCode::Char('q') => Some(InputAction::Quit),
+            KeyCode::Char('d') if cfg!(debug_assertions) => Some(InputAction::ToggleDebug),
+            KeyCode::Char(c @ '1'..='7') => {
+                let index = c as usize - '1' as usize;
+                Some(InputAction::SelectNext(TetriminoType::ALL[index]))
+            }
             _ => None,
         }
     }
@@ -65,6 +338,142 @@ impl InputHandler {
 mod tests {
     use super::*;
 
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_poll_timeout_clamps() {
+        assert_eq!(poll_timeout(16), ms(16));
+        assert_eq!(poll_timeout(0), MIN_POLL_TIMEOUT);
+        assert_eq!(poll_timeout(10_000), MAX_POLL_TIMEOUT);
+    }
+
+    #[test]
+    fn test_with_poll_timeout_applies_clamp() {
+        assert_eq!(
+            InputHandler::new().poll_timeout,
+            ms(DEFAULT_POLL_TIMEOUT_MS)
+        );
+        assert_eq!(
+            InputHandler::new().with_poll_timeout(0).poll_timeout,
+            MIN_POLL_TIMEOUT
+        );
+        assert_eq!(
+            InputHandler::new().with_poll_timeout(100).poll_timeout,
+            ms(100)
+        );
+    }
+
+    #[test]
+    fn test_auto_repeat_horizontal_cadence() {
+        let start = Instant::now();
+        let mut das = AutoRepeat::new(ms(250), ms(50));
+        das.press(start);
+
+        assert_eq!(das.poll(start + ms(100)), 0);
+        assert_eq!(das.poll(start + ms(250)), 1);
+        assert_eq!(das.poll(start + ms(299)), 0);
+        assert_eq!(das.poll(start + ms(300)), 1);
+        assert_eq!(das.poll(start + ms(400)), 2);
+    }
+
+    #[test]
+    fn test_auto_repeat_initial_tap_moves_on_press() {
+        let start = Instant::now();
+        let mut das = AutoRepeat::new(ms(250), ms(50));
+
+        das.press(start);
+        assert_eq!(das.poll(start + ms(100)), 0);
+        assert_eq!(das.poll(start + ms(250)), 1);
+    }
+
+    #[test]
+    fn test_auto_repeat_without_initial_tap_charges_immediately() {
+        let start = Instant::now();
+        let mut das = AutoRepeat::new(ms(250), ms(50)).with_initial_tap(false);
+
+        das.press(start);
+        assert_eq!(das.poll(start + ms(40)), 0);
+        assert_eq!(das.poll(start + ms(50)), 1);
+        assert_eq!(das.poll(start + ms(250)), 4);
+    }
+
+    #[test]
+    fn test_auto_repeat_soft_drop_cadence() {
+        let start = Instant::now();
+        let mut soft_drop = AutoRepeat::new(ms(20), ms(20));
+        soft_drop.press(start);
+
+        assert_eq!(soft_drop.poll(start + ms(10)), 0);
+        assert_eq!(soft_drop.poll(start + ms(20)), 1);
+        assert_eq!(soft_drop.poll(start + ms(100)), 4);
+    }
+
+    #[test]
+    fn test_auto_repeat_cadences_are_independent() {
+        let start = Instant::now();
+        let mut das = AutoRepeat::new(ms(250), ms(50));
+        let mut soft_drop = AutoRepeat::new(ms(20), ms(20));
+        das.press(start);
+        soft_drop.press(start);
+
+        let now = start + ms(300);
+        assert_eq!(das.poll(now), 2);
+        assert_eq!(soft_drop.poll(now), 15);
+    }
+
+    #[test]
+    fn test_auto_repeat_release_stops_and_press_restarts() {
+        let start = Instant::now();
+        let mut das = AutoRepeat::new(ms(100), ms(50));
+        das.press(start);
+        assert_eq!(das.poll(start + ms(150)), 2);
+
+        das.release();
+        assert_eq!(das.poll(start + ms(500)), 0);
+
+        das.press(start + ms(500));
+        assert_eq!(das.poll(start + ms(550)), 0);
+        assert_eq!(das.poll(start + ms(600)), 1);
+    }
+
+    #[test]
+    fn test_repeated_hard_drop_within_cooldown_fires_once() {
+        let start = Instant::now();
+        let mut handler = InputHandler::new();
+        let press = InputEvent::Pressed(InputAction::HardDrop);
+
+        let fired = (0..5)
+            .filter_map(|i| handler.debounce(press, start + ms(i * 20)))
+            .count();
+        assert_eq!(fired, 1);
+
+        assert_eq!(handler.debounce(press, start + ms(250)), Some(press));
+    }
+
+    #[test]
+    fn test_hard_drop_release_clears_cooldown() {
+        let start = Instant::now();
+        let mut handler = InputHandler::new();
+        let press = InputEvent::Pressed(InputAction::HardDrop);
+        let release = InputEvent::Released(InputAction::HardDrop);
+
+        assert_eq!(handler.debounce(press, start), Some(press));
+        assert_eq!(handler.debounce(release, start + ms(10)), Some(release));
+        assert_eq!(handler.debounce(press, start + ms(20)), Some(press));
+    }
+
+    #[test]
+    fn test_cooldown_only_applies_to_hard_drop() {
+        let start = Instant::now();
+        let mut handler = InputHandler::new();
+        let press = InputEvent::Pressed(InputAction::MoveLeft);
+
+        assert_eq!(handler.debounce(press, start), Some(press));
+        assert_eq!(handler.debounce(press, start), Some(press));
+    }
+
     #[test]
     fn test_new() {
         let handler = InputHandler::new();
@@ -131,6 +540,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_key_to_action_ignores_r_during_play() {
+        let handler = InputHandler::new();
+        assert_eq!(handler.key_to_action(KeyCode::Char('r')), None);
+    }
+
+    #[test]
+    fn test_key_to_action_sonic_drop() {
+        let handler = InputHandler::new();
+        assert_eq!(
+            handler.key_to_action(KeyCode::Up),
+            Some(InputAction::SonicDrop)
+        );
+    }
+
     #[test]
     fn test_key_to_action_hold() {
         let handler = InputHandler::new();
@@ -158,6 +582,112 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_key_to_action_toggle_ghost() {
+        let handler = InputHandler::new();
+        assert_eq!(
+            handler.key_to_action(KeyCode::Char('g')),
+            Some(InputAction::ToggleGhost)
+        );
+    }
+
+    #[test]
+    fn test_key_to_action_toggle_planning() {
+        let handler = InputHandler::new();
+        assert_eq!(
+            handler.key_to_action(KeyCode::Char('n')),
+            Some(InputAction::TogglePlanning)
+        );
+    }
+
+    #[test]
+    fn test_key_to_action_open_settings() {
+        let handler = InputHandler::new();
+        assert_eq!(
+            handler.key_to_action(KeyCode::Char('o')),
+            Some(InputAction::OpenSettings)
+        );
+    }
+
+    #[test]
+    fn test_key_to_menu_input() {
+        let handler = InputHandler::new();
+        assert_eq!(handler.key_to_menu_input(KeyCode::Up), Some(MenuInput::Up));
+        assert_eq!(
+            handler.key_to_menu_input(KeyCode::Down),
+            Some(MenuInput::Down)
+        );
+        assert_eq!(
+            handler.key_to_menu_input(KeyCode::Left),
+            Some(MenuInput::Decrease)
+        );
+        assert_eq!(
+            handler.key_to_menu_input(KeyCode::Enter),
+            Some(MenuInput::Increase)
+        );
+        assert_eq!(
+            handler.key_to_menu_input(KeyCode::Esc),
+            Some(MenuInput::Close)
+        );
+        assert_eq!(handler.key_to_menu_input(KeyCode::Char('x')), None);
+    }
+
+    #[test]
+    fn test_key_to_game_over_action() {
+        let handler = InputHandler::new();
+        assert_eq!(
+            handler.key_to_game_over_action(KeyCode::Char('r')),
+            GameOverAction::Restart
+        );
+        assert_eq!(
+            handler.key_to_game_over_action(KeyCode::Char('R')),
+            GameOverAction::Restart
+        );
+        assert_eq!(
+            handler.key_to_game_over_action(KeyCode::Char('s')),
+            GameOverAction::Replay
+        );
+        assert_eq!(
+            handler.key_to_game_over_action(KeyCode::Char('v')),
+            GameOverAction::Review
+        );
+        for key in [
+            KeyCode::Char('q'),
+            KeyCode::Char(' '),
+            KeyCode::Esc,
+            KeyCode::Enter,
+        ] {
+            assert_eq!(
+                handler.key_to_game_over_action(key),
+                GameOverAction::Quit,
+                "{:?}",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn test_key_to_replay_input() {
+        let handler = InputHandler::new();
+        assert_eq!(
+            handler.key_to_replay_input(KeyCode::Char(' ')),
+            Some(ReplayInput::TogglePlay)
+        );
+        assert_eq!(
+            handler.key_to_replay_input(KeyCode::Right),
+            Some(ReplayInput::Step)
+        );
+        assert_eq!(
+            handler.key_to_replay_input(KeyCode::Char('n')),
+            Some(ReplayInput::NextClear)
+        );
+        assert_eq!(
+            handler.key_to_replay_input(KeyCode::Esc),
+            Some(ReplayInput::Close)
+        );
+        assert_eq!(handler.key_to_replay_input(KeyCode::Char('x')), None);
+    }
+
     #[test]
     fn test_key_to_action_quit() {
         let handler = InputHandler::new();
@@ -167,10 +697,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_key_to_action_select_next() {
+        let handler = InputHandler::new();
+        for (i, kind) in TetriminoType::ALL.iter().enumerate() {
+            let key = char::from(b'1' + i as u8);
+            assert_eq!(
+                handler.key_to_action(KeyCode::Char(key)),
+                Some(InputAction::SelectNext(*kind))
+            );
+        }
+        assert_eq!(handler.key_to_action(KeyCode::Char('0')), None);
+        assert_eq!(handler.key_to_action(KeyCode::Char('8')), None);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_key_to_action_toggle_debug() {
+        let handler = InputHandler::new();
+        assert_eq!(
+            handler.key_to_action(KeyCode::Char('d')),
+            Some(InputAction::ToggleDebug)
+        );
+    }
+
     #[test]
     fn test_key_to_action_unknown_key_returns_none() {
         let handler = InputHandler::new();
-        assert_eq!(handler.key_to_action(KeyCode::Up), None);
+        assert_eq!(handler.key_to_action(KeyCode::PageUp), None);
         assert_eq!(handler.key_to_action(KeyCode::Backspace), None);
         assert_eq!(handler.key_to_action(KeyCode::Enter), None);
         assert_eq!(handler.key_to_action(KeyCode::Char('a')), None);
@@ -187,13 +741,19 @@ mod tests {
     #[test]
     fn test_key_to_action_home() {
         let handler = InputHandler::new();
-        assert_eq!(handler.key_to_action(KeyCode::Home), None);
+        assert_eq!(
+            handler.key_to_action(KeyCode::Home),
+            Some(InputAction::MoveToLeftWall)
+        );
     }
 
     #[test]
     fn test_key_to_action_end() {
         let handler = InputHandler::new();
-        assert_eq!(handler.key_to_action(KeyCode::End), None);
+        assert_eq!(
+            handler.key_to_action(KeyCode::End),
+            Some(InputAction::MoveToRightWall)
+        );
     }
 
     #[test]
@@ -239,12 +799,14 @@ mod tests {
         let _ = Hold;
         let _ = Pause;
         let _ = ToggleHelp;
+        let _ = ToggleDebug;
+        let _ = SelectNext(TetriminoType::T);
         let _ = Quit;
     }
 
     #[test]
     fn test_poll_input_returns_none_when_no_input() {
-        let handler = InputHandler::new();
+        let mut handler = InputHandler::new();
         let _ = handler.poll_input();
     }
 
@@ -1,7 +1,14 @@
 pub mod audio;
 pub mod board;
+pub mod bot;
 pub mod config;
+pub mod game;
 pub mod game_state;
 pub mod input;
+pub mod replay;
+pub mod rotation;
+pub mod settings;
+pub mod stats;
 pub mod tetrimino;
+pub mod title;
 pub mod ui;
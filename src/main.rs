@@ -1,24 +1,59 @@
-mod audio;
-mod board;
-mod config;
-mod game;
-mod game_state;
-mod input;
-mod tetrimino;
-mod ui;
-
-use crate::config::GameConfig;
-use crate::game::Game;
+use std::env;
 use std::path::PathBuf;
 
 use anyhow::Result;
+use tetris_rs::config::{ConfigError, GameConfig};
+use tetris_rs::game::Game;
+use tetris_rs::stats::LifetimeStats;
 
 fn main() -> Result<()> {
     let config_path = PathBuf::from("tetris_config.json");
-    let config = GameConfig::load_from_file(&config_path).unwrap_or_else(|_| GameConfig::default());
+    let config = match GameConfig::load_from_file(&config_path) {
+        Ok(config) => config,
+        // No config file yet is the normal first run
+        Err(ConfigError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+            GameConfig::default()
+        }
+        Err(e) => {
+            eprintln!("Warning: {}. Using the default settings.", e);
+            GameConfig::default()
+        }
+    };
 
+    let args: Vec<String> = env::args().skip(1).collect();
     let mut game = Game::new(config)?;
+    game.set_config_path(config_path);
+
+    let stats_path = PathBuf::from("tetris_stats.json");
+    let mut stats = LifetimeStats::load(&stats_path).unwrap_or_else(|e| {
+        eprintln!(
+            "Warning: couldn't read lifetime stats: {}. Starting fresh.",
+            e
+        );
+        LifetimeStats::default()
+    });
+    if args.iter().any(|arg| arg == "--reset-stats") {
+        stats.reset();
+        stats.save(&stats_path)?;
+    }
+    game.set_lifetime_stats(stats, stats_path);
+
+    if let Some(path) = parse_path_arg(&args, "--export") {
+        game.set_export_path(path);
+    }
+    if let Some(path) = parse_path_arg(&args, "--result-json") {
+        game.set_result_path(path);
+    }
+    game.set_attract_mode(args.iter().any(|arg| arg == "--demo"));
     game.run()?;
 
     Ok(())
 }
+
+/// Returns the path following `flag`, if given.
+fn parse_path_arg(args: &[String], flag: &str) -> Option<PathBuf> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(PathBuf::from)
+}
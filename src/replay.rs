@@ -0,0 +1,238 @@
+use crate::game_state::{GameState, LockedPiece};
+use std::time::{Duration, Instant};
+
+/// Time between pieces while the viewer is playing.
+const PLAY_STEP_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Index of the first piece at or after `from` that cleared lines, if any.
+pub fn next_clear(pieces: &[LockedPiece], from: usize) -> Option<usize> {
+    pieces
+        .iter()
+        .skip(from)
+        .position(|locked| locked.lines > 0)
+        .map(|offset| from + offset)
+}
+
+/// Steps back through a finished game one locked piece at a time, rebuilding the
+/// board as it goes. Driven by keys rather than gravity: play, pause, step, and jump
+/// to the next line clear.
+pub struct ReplayViewer {
+    pieces: Vec<LockedPiece>,
+    /// How many pieces have been placed so far.
+    position: usize,
+    playing: bool,
+    last_step: Instant,
+    state: GameState,
+}
+
+impl ReplayViewer {
+    /// A viewer over the pieces `finished` locked, starting from an empty board.
+    pub fn new(finished: &GameState) -> Self {
+        let mut viewer = Self {
+            pieces: finished.locked_pieces().to_vec(),
+            position: 0,
+            playing: false,
+            last_step: Instant::now(),
+            state: finished.replay(),
+        };
+        viewer.show_next_piece();
+        viewer
+    }
+
+    /// The board so far, with the next piece to be placed shown where it locked.
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// Pieces placed so far, and in the whole game.
+    pub fn position(&self) -> (usize, usize) {
+        (self.position, self.pieces.len())
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.pieces.len()
+    }
+
+    pub fn toggle_play(&mut self) {
+        self.playing = !self.playing && !self.is_finished();
+        self.last_step = Instant::now();
+    }
+
+    /// Places the next piece. Returns false once every piece has been placed.
+    pub fn step(&mut self) -> bool {
+        let Some(locked) = self.pieces.get(self.position) else {
+            return false;
+        };
+        self.state.board.lock_tetromino(&locked.piece);
+        self.state.lines_cleared += self
+            .state
+            .board
+            .clear_lines_with(self.state.config.clear_gravity);
+        for wipe in &locked.wipes_after {
+            wipe.apply(&mut self.state.board);
+        }
+        self.position += 1;
+        self.show_next_piece();
+        true
+    }
+
+    /// Places pieces up to and including the next one that clears lines, or all of
+    /// them if none does. Pauses playback.
+    pub fn seek_next_clear(&mut self) {
+        self.playing = false;
+        let target = next_clear(&self.pieces, self.position).unwrap_or(self.pieces.len());
+        while self.position <= target && self.step() {}
+    }
+
+    /// While playing, places a piece every `PLAY_STEP_INTERVAL` as of `now`, stopping
+    /// at the end.
+    pub fn advance(&mut self, now: Instant) {
+        if self.playing && now.saturating_duration_since(self.last_step) >= PLAY_STEP_INTERVAL {
+            self.step();
+            self.last_step = now;
+            self.playing = !self.is_finished();
+        }
+    }
+
+    fn show_next_piece(&mut self) {
+        self.state.current_piece = self.pieces.get(self.position).map(|locked| locked.piece);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot;
+    use crate::config::GameConfig;
+    use crate::tetrimino::{Tetrimino, TetriminoType};
+
+    fn locked(lines: u32) -> LockedPiece {
+        LockedPiece {
+            piece: Tetrimino::from_spec(TetriminoType::O, 0, 0, 0),
+            lines,
+            wipes_after: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_next_clear() {
+        let pieces = [locked(0), locked(0), locked(1), locked(0), locked(4)];
+        assert_eq!(next_clear(&pieces, 0), Some(2));
+        assert_eq!(next_clear(&pieces, 2), Some(2));
+        assert_eq!(next_clear(&pieces, 3), Some(4));
+        assert_eq!(next_clear(&pieces, 5), None);
+        assert_eq!(next_clear(&pieces, 9), None);
+        assert_eq!(next_clear(&[], 0), None);
+    }
+
+    /// A bot-played game of `moves` pieces.
+    fn played_game(moves: usize) -> GameState {
+        let mut state = GameState::new(GameConfig {
+            seed: Some(7),
+            ..GameConfig::default()
+        });
+        state.spawn_piece();
+        for _ in 0..moves {
+            bot::play_move(&mut state);
+        }
+        state
+    }
+
+    #[test]
+    fn test_step_rebuilds_the_board() {
+        let game = played_game(30);
+        assert_eq!(game.locked_pieces().len(), 30);
+        assert!(game.lines_cleared > 0);
+
+        let mut viewer = ReplayViewer::new(&game);
+        assert_eq!(viewer.position(), (0, 30));
+        assert_eq!(
+            viewer.state().current_piece,
+            Some(game.locked_pieces()[0].piece)
+        );
+
+        while viewer.step() {}
+        assert!(viewer.is_finished());
+        assert_eq!(viewer.position(), (30, 30));
+        assert_eq!(viewer.state().board.checksum(), game.board.checksum());
+        assert_eq!(viewer.state().lines_cleared, game.lines_cleared);
+        assert_eq!(viewer.state().current_piece, None);
+        assert!(!viewer.step());
+    }
+
+    #[test]
+    fn test_step_replays_lives_spent() {
+        let mut game = GameState::new(GameConfig {
+            seed: Some(7),
+            lives: 3,
+            ..GameConfig::default()
+        });
+        game.spawn_piece();
+        // Dropping every piece where it spawns soon tops out
+        while game.lives == 3 && !game.is_game_over() {
+            game.hard_drop();
+        }
+        assert_eq!(game.lives, 2);
+        let count = game.locked_pieces().len();
+        game.hard_drop();
+        game.hard_drop();
+
+        let mut viewer = ReplayViewer::new(&game);
+        while viewer.step() {}
+        assert_eq!(viewer.state().board.checksum(), game.board.checksum());
+        assert!(
+            game.locked_pieces()[..count + 1]
+                .iter()
+                .any(|locked| !locked.wipes_after.is_empty())
+        );
+    }
+
+    #[test]
+    fn test_seek_next_clear_stops_after_the_clearing_piece() {
+        let game = played_game(30);
+        let pieces = game.locked_pieces();
+        let first = next_clear(pieces, 0).unwrap();
+
+        let mut viewer = ReplayViewer::new(&game);
+        viewer.seek_next_clear();
+        assert_eq!(viewer.position().0, first + 1);
+        assert_eq!(viewer.state().lines_cleared, pieces[first].lines);
+
+        let mut viewer = ReplayViewer::new(&game);
+        viewer.seek_next_clear();
+        viewer.seek_next_clear();
+        let second = next_clear(pieces, first + 1).unwrap_or(pieces.len() - 1);
+        assert_eq!(viewer.position().0, second + 1);
+    }
+
+    #[test]
+    fn test_play_advances_on_its_interval() {
+        let game = played_game(3);
+        let mut viewer = ReplayViewer::new(&game);
+        let start = Instant::now();
+
+        viewer.advance(start + PLAY_STEP_INTERVAL);
+        assert_eq!(viewer.position().0, 0, "paused viewers don't move");
+
+        viewer.toggle_play();
+        assert!(viewer.is_playing());
+        let started = viewer.last_step;
+        viewer.advance(started + PLAY_STEP_INTERVAL / 2);
+        assert_eq!(viewer.position().0, 0);
+        viewer.advance(started + PLAY_STEP_INTERVAL);
+        assert_eq!(viewer.position().0, 1);
+
+        viewer.advance(started + PLAY_STEP_INTERVAL * 2);
+        viewer.advance(started + PLAY_STEP_INTERVAL * 3);
+        assert!(viewer.is_finished());
+        assert!(!viewer.is_playing());
+
+        // Nothing left to play
+        viewer.toggle_play();
+        assert!(!viewer.is_playing());
+    }
+}
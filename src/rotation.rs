@@ -0,0 +1,143 @@
+use crate::config::RotationSystem;
+use crate::tetrimino::TetriminoType;
+
+/// Rotation state a piece spawns in. ARS deals T, J and L flat side down, which in
+/// these piece tables is the SRS "2" state; everything else spawns as in SRS.
+pub fn spawn_rotation(system: RotationSystem, kind: TetriminoType) -> usize {
+    match (system, kind) {
+        (RotationSystem::Ars, TetriminoType::T | TetriminoType::J | TetriminoType::L) => 2,
+        _ => 0,
+    }
+}
+
+/// `(dx, dy)` offsets to try, in order, when turning `kind` from `from_rotation` to
+/// `to_rotation`. The first entry is always the unkicked rotation.
+pub fn wall_kicks(
+    system: RotationSystem,
+    kind: TetriminoType,
+    from_rotation: usize,
+    to_rotation: usize,
+) -> Vec<(i32, i32)> {
+    match system {
+        RotationSystem::Srs => srs_kicks(kind, from_rotation, to_rotation),
+        RotationSystem::Ars => ars_kicks(kind),
+    }
+}
+
+fn srs_kicks(kind: TetriminoType, from_rotation: usize, to_rotation: usize) -> Vec<(i32, i32)> {
+    // Super Rotation System wall kick tables
+    // Format: (dx, dy) offsets to try
+    match kind {
+        TetriminoType::I => {
+            // I piece has special wall kick data
+            match (from_rotation % 4, to_rotation % 4) {
+                (0, 1) => vec![(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+                (1, 0) => vec![(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+                (1, 2) => vec![(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+                (2, 1) => vec![(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+                (2, 3) => vec![(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+                (3, 2) => vec![(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+                (3, 0) => vec![(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+                (0, 3) => vec![(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+                _ => vec![(0, 0)],
+            }
+        }
+        TetriminoType::O => {
+            // O piece doesn't rotate, but include for completeness
+            vec![(0, 0)]
+        }
+        _ => {
+            // Basic kicks for most pieces (J, L, S, Z, T)
+            match (from_rotation % 4, to_rotation % 4) {
+                (0, 1) => vec![(0, 0), (0, -1), (-1, 0), (-1, -1)],
+                (1, 0) => vec![(0, 0), (0, 1), (1, 0), (1, 1)],
+                (1, 2) => vec![(0, 0), (0, -1), (1, 0), (1, -1)],
+                (2, 1) => vec![(0, 0), (0, 1), (-1, 0), (-1, 1)],
+                (2, 3) => vec![(0, 0), (0, -1), (-1, 0), (-1, -1)],
+                (3, 2) => vec![(0, 0), (0, 1), (1, 0), (1, 1)],
+                (3, 0) => vec![(0, 0), (0, -1), (1, 0), (1, -1)],
+                (0, 3) => vec![(0, 0), (0, 1), (-1, 0), (-1, 1)],
+                _ => vec![(0, 0)],
+            }
+        }
+    }
+}
+
+/// ARS kicks are the same for every turn: one cell right, then one cell left. The
+/// I and O never kick.
+fn ars_kicks(kind: TetriminoType) -> Vec<(i32, i32)> {
+    match kind {
+        TetriminoType::I | TetriminoType::O => vec![(0, 0)],
+        _ => vec![(0, 0), (1, 0), (-1, 0)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TURNS: [(usize, usize); 8] = [
+        (0, 1),
+        (1, 0),
+        (1, 2),
+        (2, 1),
+        (2, 3),
+        (3, 2),
+        (3, 0),
+        (0, 3),
+    ];
+
+    #[test]
+    fn test_srs_and_ars_kick_differently() {
+        assert_ne!(
+            wall_kicks(RotationSystem::Srs, TetriminoType::T, 0, 1),
+            wall_kicks(RotationSystem::Ars, TetriminoType::T, 0, 1)
+        );
+        assert_eq!(
+            wall_kicks(RotationSystem::Ars, TetriminoType::I, 0, 1),
+            vec![(0, 0)]
+        );
+        assert!(wall_kicks(RotationSystem::Srs, TetriminoType::I, 0, 1).len() > 1);
+    }
+
+    #[test]
+    fn test_ars_has_no_floor_kicks() {
+        for kind in TetriminoType::ALL {
+            for (from, to) in TURNS {
+                let kicks = wall_kicks(RotationSystem::Ars, kind, from, to);
+                assert!(
+                    kicks.iter().all(|&(_, dy)| dy == 0),
+                    "{:?} {}->{}",
+                    kind,
+                    from,
+                    to
+                );
+            }
+        }
+        let srs = wall_kicks(RotationSystem::Srs, TetriminoType::T, 0, 1);
+        assert!(srs.iter().any(|&(_, dy)| dy < 0));
+    }
+
+    #[test]
+    fn test_every_kick_list_starts_unkicked() {
+        for system in [RotationSystem::Srs, RotationSystem::Ars] {
+            for kind in TetriminoType::ALL {
+                for (from, to) in TURNS {
+                    assert_eq!(wall_kicks(system, kind, from, to)[0], (0, 0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_spawn_rotation() {
+        for kind in TetriminoType::ALL {
+            assert_eq!(spawn_rotation(RotationSystem::Srs, kind), 0);
+        }
+        assert_eq!(spawn_rotation(RotationSystem::Ars, TetriminoType::T), 2);
+        assert_eq!(spawn_rotation(RotationSystem::Ars, TetriminoType::J), 2);
+        assert_eq!(spawn_rotation(RotationSystem::Ars, TetriminoType::L), 2);
+        assert_eq!(spawn_rotation(RotationSystem::Ars, TetriminoType::I), 0);
+        assert_eq!(spawn_rotation(RotationSystem::Ars, TetriminoType::S), 0);
+    }
+}
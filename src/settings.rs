@@ -0,0 +1,182 @@
+use crate::config::GameConfig;
+
+/// Step used when adjusting DAS timings from the settings screen, in ms.
+const DAS_STEP_MS: u64 = 10;
+/// Lowest DAS delay/repeat the settings screen allows, in ms.
+const DAS_MIN_MS: u64 = 10;
+/// Highest DAS delay/repeat the settings screen allows, in ms.
+const DAS_MAX_MS: u64 = 1000;
+
+/// A config value the in-game settings screen can edit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsItem {
+    Ghost,
+    Hold,
+    Sound,
+    PreviewCount,
+    DasDelay,
+    DasRepeat,
+}
+
+impl SettingsItem {
+    pub const ALL: [SettingsItem; 6] = [
+        SettingsItem::Ghost,
+        SettingsItem::Hold,
+        SettingsItem::Sound,
+        SettingsItem::PreviewCount,
+        SettingsItem::DasDelay,
+        SettingsItem::DasRepeat,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SettingsItem::Ghost => "Ghost piece",
+            SettingsItem::Hold => "Hold",
+            SettingsItem::Sound => "Sound",
+            SettingsItem::PreviewCount => "Preview count",
+            SettingsItem::DasDelay => "DAS delay",
+            SettingsItem::DasRepeat => "DAS repeat",
+        }
+    }
+
+    pub fn value(self, config: &GameConfig) -> String {
+        let on_off = |enabled: bool| if enabled { "On" } else { "Off" }.to_string();
+        match self {
+            SettingsItem::Ghost => on_off(config.enable_ghost_piece),
+            SettingsItem::Hold => on_off(config.enable_hold),
+            SettingsItem::Sound => on_off(config.enable_sound),
+            SettingsItem::PreviewCount => config.preview_count.to_string(),
+            SettingsItem::DasDelay => format!("{} ms", config.das_delay),
+            SettingsItem::DasRepeat => format!("{} ms", config.das_repeat),
+        }
+    }
+
+    /// Changes the value by one step in the direction of `delta`; toggles flip
+    /// whichever way they are adjusted.
+    pub fn adjust(self, config: &mut GameConfig, delta: i32) {
+        let step_ms = |value: u64| {
+            let stepped = if delta < 0 {
+                value.saturating_sub(DAS_STEP_MS)
+            } else {
+                value + DAS_STEP_MS
+            };
+            stepped.clamp(DAS_MIN_MS, DAS_MAX_MS)
+        };
+
+        match self {
+            SettingsItem::Ghost => config.enable_ghost_piece = !config.enable_ghost_piece,
+            SettingsItem::Hold => config.enable_hold = !config.enable_hold,
+            SettingsItem::Sound => config.enable_sound = !config.enable_sound,
+            SettingsItem::PreviewCount => {
+                config.preview_count = config
+                    .preview_count
+                    .saturating_add_signed(delta.signum() as isize)
+                    .clamp(1, 6);
+            }
+            SettingsItem::DasDelay => config.das_delay = step_ms(config.das_delay),
+            SettingsItem::DasRepeat => config.das_repeat = step_ms(config.das_repeat),
+        }
+    }
+}
+
+/// Cursor state for the settings screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SettingsMenu {
+    selected: usize,
+    changed: bool,
+}
+
+impl SettingsMenu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn selected(&self) -> SettingsItem {
+        SettingsItem::ALL[self.selected]
+    }
+
+    /// True once any value has been edited.
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
+
+    /// Moves the cursor by `delta` rows, wrapping around the ends.
+    pub fn move_selection(&mut self, delta: i32) {
+        let count = SettingsItem::ALL.len() as i32;
+        self.selected = (self.selected as i32 + delta).rem_euclid(count) as usize;
+    }
+
+    /// Adjusts the selected item in `config`.
+    pub fn adjust(&mut self, config: &mut GameConfig, delta: i32) {
+        self.selected().adjust(config, delta);
+        self.changed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_selection_wraps() {
+        let mut menu = SettingsMenu::new();
+        assert_eq!(menu.selected(), SettingsItem::Ghost);
+
+        menu.move_selection(-1);
+        assert_eq!(menu.selected(), SettingsItem::DasRepeat);
+
+        menu.move_selection(1);
+        menu.move_selection(1);
+        assert_eq!(menu.selected(), SettingsItem::Hold);
+    }
+
+    #[test]
+    fn test_adjust_toggles_flags() {
+        let mut config = GameConfig::default();
+        let mut menu = SettingsMenu::new();
+        assert!(!menu.changed());
+
+        menu.adjust(&mut config, 1);
+        assert!(!config.enable_ghost_piece);
+        assert!(menu.changed());
+
+        menu.adjust(&mut config, -1);
+        assert!(config.enable_ghost_piece);
+    }
+
+    #[test]
+    fn test_adjust_preview_count_clamps() {
+        let mut config = GameConfig {
+            preview_count: 5,
+            ..GameConfig::default()
+        };
+
+        SettingsItem::PreviewCount.adjust(&mut config, 1);
+        SettingsItem::PreviewCount.adjust(&mut config, 1);
+        assert_eq!(config.preview_count, 6);
+
+        config.preview_count = 1;
+        SettingsItem::PreviewCount.adjust(&mut config, -1);
+        assert_eq!(config.preview_count, 1);
+    }
+
+    #[test]
+    fn test_adjust_das_steps_and_clamps() {
+        let mut config = GameConfig::default();
+
+        SettingsItem::DasDelay.adjust(&mut config, 1);
+        assert_eq!(config.das_delay, 260);
+
+        config.das_repeat = 15;
+        SettingsItem::DasRepeat.adjust(&mut config, -1);
+        assert_eq!(config.das_repeat, DAS_MIN_MS);
+    }
+
+    #[test]
+    fn test_value_formatting() {
+        let config = GameConfig::default();
+        assert_eq!(SettingsItem::Hold.value(&config), "On");
+        assert_eq!(SettingsItem::PreviewCount.value(&config), "3");
+        assert_eq!(SettingsItem::DasRepeat.value(&config), "50 ms");
+    }
+}
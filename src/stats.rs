@@ -0,0 +1,92 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Totals kept across every game played, saved between runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifetimeStats {
+    pub games_played: u32,
+    pub total_lines: u64,
+    pub total_score: u64,
+    pub best_score: u64,
+}
+
+impl LifetimeStats {
+    /// Reads saved totals; a missing file means no games have been played yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Adds a finished game to the totals.
+    pub fn update(&mut self, score: u64, lines: u32) {
+        self.games_played += 1;
+        self.total_lines += lines as u64;
+        self.total_score += score;
+        self.best_score = self.best_score.max(score);
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_accumulates() {
+        let mut stats = LifetimeStats::default();
+        stats.update(1_200, 10);
+        stats.update(800, 6);
+        stats.update(3_000, 25);
+
+        assert_eq!(
+            stats,
+            LifetimeStats {
+                games_played: 3,
+                total_lines: 41,
+                total_score: 5_000,
+                best_score: 3_000,
+            }
+        );
+    }
+
+    #[test]
+    fn test_reset_clears_everything() {
+        let mut stats = LifetimeStats::default();
+        stats.update(1_200, 10);
+        stats.reset();
+
+        assert_eq!(stats, LifetimeStats::default());
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("tetris-rs-stats-{}.json", std::process::id()));
+        let mut stats = LifetimeStats::default();
+        stats.update(4_500, 30);
+        stats.save(&path).unwrap();
+
+        let loaded = LifetimeStats::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, stats);
+        assert_eq!(
+            LifetimeStats::load(&path).unwrap(),
+            LifetimeStats::default()
+        );
+    }
+}
@@ -1,4 +1,7 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use serde::{Deserialize, Serialize};
+
+/// A piece shape. Serializes as its letter, e.g. `"T"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TetriminoType {
     I,
     O,
@@ -9,6 +12,39 @@ pub enum TetriminoType {
     L,
 }
 
+impl TetriminoType {
+    /// All seven piece types, in guideline order.
+    pub const ALL: [TetriminoType; 7] = [
+        TetriminoType::I,
+        TetriminoType::O,
+        TetriminoType::T,
+        TetriminoType::S,
+        TetriminoType::Z,
+        TetriminoType::J,
+        TetriminoType::L,
+    ];
+
+    /// The piece's letter, as used in board dumps and puzzle files.
+    pub fn to_char(self) -> char {
+        match self {
+            TetriminoType::I => 'I',
+            TetriminoType::O => 'O',
+            TetriminoType::T => 'T',
+            TetriminoType::S => 'S',
+            TetriminoType::Z => 'Z',
+            TetriminoType::J => 'J',
+            TetriminoType::L => 'L',
+        }
+    }
+
+    /// The piece named by a letter, in either case; `None` for anything else.
+    pub fn from_char(c: char) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.to_char() == c.to_ascii_uppercase())
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Tetrimino {
     pub kind: TetriminoType,
@@ -27,6 +63,16 @@ impl Tetrimino {
         }
     }
 
+    /// A piece placed at `(x, y)` in a given rotation state, 0 being spawn orientation.
+    pub fn from_spec(kind: TetriminoType, x: i32, y: i32, rotation: usize) -> Self {
+        Self {
+            kind,
+            x,
+            y,
+            rotation: rotation % 4,
+        }
+    }
+
     /// Returns relative block positions for the current rotation
     pub fn get_blocks(&self) -> Vec<(i32, i32)> {
         match (self.kind, self.rotation % 4) {
@@ -97,6 +143,37 @@ mod tests {
         assert_ne!(TetriminoType::L, TetriminoType::I);
     }
 
+    #[test]
+    fn test_char_round_trip() {
+        for kind in TetriminoType::ALL {
+            assert_eq!(TetriminoType::from_char(kind.to_char()), Some(kind));
+            let lower = kind.to_char().to_ascii_lowercase();
+            assert_eq!(TetriminoType::from_char(lower), Some(kind));
+        }
+    }
+
+    #[test]
+    fn test_from_char_invalid() {
+        for c in ['.', '#', 'X', ' ', '1'] {
+            assert_eq!(TetriminoType::from_char(c), None, "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn test_from_spec() {
+        let piece = Tetrimino::from_spec(TetriminoType::T, -1, 5, 1);
+        assert_eq!(
+            piece,
+            Tetrimino {
+                kind: TetriminoType::T,
+                x: -1,
+                y: 5,
+                rotation: 1,
+            }
+        );
+        assert_eq!(Tetrimino::from_spec(TetriminoType::J, 0, 0, 6).rotation, 2);
+    }
+
     #[test]
     fn test_tetrimino_new_i() {
         let piece = Tetrimino::new(TetriminoType::I);
@@ -0,0 +1,82 @@
+use crate::game_state::GameMode;
+use crate::input::MenuInput;
+
+/// What the player picked on the title screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleChoice {
+    Start(GameMode),
+    Quit,
+}
+
+/// Cursor state for the title screen's mode list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TitleMenu {
+    selected: usize,
+}
+
+impl TitleMenu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn selected(&self) -> GameMode {
+        GameMode::ALL[self.selected]
+    }
+
+    /// Moves the cursor or makes a choice. Up and down wrap around the list; Enter
+    /// starts the highlighted mode and Esc or Q quits.
+    pub fn handle(&mut self, input: MenuInput) -> Option<TitleChoice> {
+        let count = GameMode::ALL.len();
+        match input {
+            MenuInput::Up => self.selected = (self.selected + count - 1) % count,
+            MenuInput::Down => self.selected = (self.selected + 1) % count,
+            MenuInput::Increase => return Some(TitleChoice::Start(self.selected())),
+            MenuInput::Close => return Some(TitleChoice::Quit),
+            MenuInput::Decrease => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_starts_on_marathon() {
+        assert_eq!(TitleMenu::new().selected(), GameMode::Marathon);
+    }
+
+    #[test]
+    fn test_navigation_wraps() {
+        let mut menu = TitleMenu::new();
+
+        assert_eq!(menu.handle(MenuInput::Up), None);
+        assert_eq!(menu.selected(), GameMode::Zen);
+
+        assert_eq!(menu.handle(MenuInput::Down), None);
+        assert_eq!(menu.handle(MenuInput::Down), None);
+        assert_eq!(menu.selected(), GameMode::Sprint);
+    }
+
+    #[test]
+    fn test_enter_starts_selected_mode() {
+        let mut menu = TitleMenu::new();
+        menu.handle(MenuInput::Down);
+        menu.handle(MenuInput::Down);
+
+        assert_eq!(
+            menu.handle(MenuInput::Increase),
+            Some(TitleChoice::Start(GameMode::Ultra))
+        );
+    }
+
+    #[test]
+    fn test_close_quits_and_left_does_nothing() {
+        let mut menu = TitleMenu::new();
+
+        assert_eq!(menu.handle(MenuInput::Decrease), None);
+        assert_eq!(menu.selected(), GameMode::Marathon);
+        assert_eq!(menu.handle(MenuInput::Close), Some(TitleChoice::Quit));
+    }
+}
@@ -1,21 +1,142 @@
-use crate::game_state::GameState;
-use crate::tetrimino::TetriminoType;
+use crate::config::{BorderStyle, GameConfig, GhostStyle, RotationSystem};
+use crate::game_state::{GameMode, GameState};
+use crate::replay::ReplayViewer;
+use crate::rotation;
+use crate::settings::{SettingsItem, SettingsMenu};
+use crate::stats::LifetimeStats;
+use crate::tetrimino::{Tetrimino, TetriminoType};
+use crate::title::TitleMenu;
 use anyhow::Result;
 use ratatui::layout::Alignment;
 use ratatui::{
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Flex, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Clear, Paragraph},
     Frame, Terminal,
 };
+use std::collections::VecDeque;
 use std::io::Stdout;
+use std::time::{Duration, Instant};
+
+/// Narrowest the HOLD/INFO and NEXT panels get; fits the INFO labels and progress bar.
+const SIDE_PANEL_MIN_WIDTH: u16 = 14;
+/// Cells across a piece preview: the widest piece (I) plus the one-cell left margin.
+const PREVIEW_COLUMNS: usize = 5;
+/// Rows the HOLD panel takes at the top of the left column.
+const HOLD_PANEL_HEIGHT: u16 = 10;
+/// Colors the kill screen scatters over the stack.
+const KILL_SCREEN_PALETTE: [Color; 6] = [
+    Color::Magenta,
+    Color::LightRed,
+    Color::DarkGray,
+    Color::LightGreen,
+    Color::Blue,
+    Color::White,
+];
+
+/// Sliding window of recent frame times, for the FPS overlay.
+#[derive(Debug, Clone)]
+pub struct FrameStats {
+    samples: VecDeque<Duration>,
+    capacity: usize,
+}
+
+impl FrameStats {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Adds a frame time, dropping the oldest once the window is full.
+    pub fn record(&mut self, frame_time: Duration) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(frame_time);
+    }
+
+    pub fn min(&self) -> Option<Duration> {
+        self.samples.iter().min().copied()
+    }
+
+    pub fn max(&self) -> Option<Duration> {
+        self.samples.iter().max().copied()
+    }
+
+    pub fn average(&self) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        Some(self.samples.iter().sum::<Duration>() / self.samples.len() as u32)
+    }
+
+    /// Frames per second implied by the average frame time.
+    pub fn fps(&self) -> Option<f64> {
+        self.average()
+            .filter(|avg| !avg.is_zero())
+            .map(|avg| 1.0 / avg.as_secs_f64())
+    }
+}
 
 pub struct Renderer {
     terminal: Terminal<CrosstermBackend<Stdout>>,
 }
 
+/// Corner and edge glyphs for drawing the playfield border.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BorderGlyphs {
+    pub top_left: &'static str,
+    pub top_right: &'static str,
+    pub bottom_left: &'static str,
+    pub bottom_right: &'static str,
+    pub horizontal: &'static str,
+    pub vertical: &'static str,
+}
+
+impl BorderGlyphs {
+    pub fn for_style(style: BorderStyle) -> Self {
+        match style {
+            BorderStyle::Rounded => Self {
+                top_left: "╭",
+                top_right: "╮",
+                bottom_left: "╰",
+                bottom_right: "╯",
+                horizontal: "─",
+                vertical: "│",
+            },
+            BorderStyle::Square => Self {
+                top_left: "┌",
+                top_right: "┐",
+                bottom_left: "└",
+                bottom_right: "┘",
+                horizontal: "─",
+                vertical: "│",
+            },
+            BorderStyle::Double => Self {
+                top_left: "╔",
+                top_right: "╗",
+                bottom_left: "╚",
+                bottom_right: "╝",
+                horizontal: "═",
+                vertical: "║",
+            },
+            // Blank glyphs keep the board the same size as the bordered styles
+            BorderStyle::None => Self {
+                top_left: " ",
+                top_right: " ",
+                bottom_left: " ",
+                bottom_right: " ",
+                horizontal: " ",
+                vertical: " ",
+            },
+        }
+    }
+}
+
 impl Renderer {
     pub fn new() -> Result<Self> {
         let backend = CrosstermBackend::new(std::io::stdout());
@@ -33,12 +154,18 @@ impl Renderer {
         }
     }
 
-    pub fn render(&mut self, state: &GameState) -> Result<()> {
+    pub fn render(&mut self, state: &GameState, frame_stats: Option<&FrameStats>) -> Result<()> {
         self.terminal.draw(|f| {
             Self::draw_game(f, state);
             if state.show_help {
                 Self::draw_help_overlay(f);
             }
+            if cfg!(debug_assertions) && state.show_debug {
+                Self::draw_debug_overlay(f, state);
+            }
+            if let Some(stats) = frame_stats {
+                Self::draw_fps_overlay(f, stats);
+            }
         })?;
         Ok(())
     }
@@ -46,37 +173,85 @@ impl Renderer {
     fn draw_game(f: &mut Frame, state: &GameState) {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Length(14),
-                Constraint::Min(state.board.get_width() as u16 * 2 + 2),
-                Constraint::Length(14),
-            ])
+            .flex(Flex::Center)
+            .constraints(layout_constraints(state, f.area()))
             .split(f.area());
 
         let left_chunk = chunks[0];
         let board_chunk = chunks[1];
         let right_chunk = chunks[2];
 
+        Self::draw_board(f, board_chunk, state);
+        if let Some(level) = state.level_up_banner(Instant::now()) {
+            Self::draw_banner(f, board_chunk, &format!("LEVEL {}", level), Color::Yellow);
+        }
+        if state.hard_drop_warning(Instant::now()) {
+            Self::draw_banner(f, board_chunk, "DROP AGAIN?", Color::Red);
+        }
+        if !state.side_panels_visible(Instant::now()) {
+            return;
+        }
+
         let left_chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(10), Constraint::Min(0)])
+            .constraints(left_column_constraints(state.config.enable_hold))
             .split(left_chunk);
 
         let hold_chunk = left_chunks[0];
         let info_chunk = left_chunks[1];
 
-        Self::draw_held_piece(f, hold_chunk, state);
+        if state.config.enable_hold {
+            Self::draw_held_piece(f, hold_chunk, state);
+        }
         Self::draw_info(f, info_chunk, state);
-        Self::draw_board(f, board_chunk, state);
         Self::draw_next_pieces(f, right_chunk, state);
     }
 
+    /// Draws a small titled box centered in `area`, sized to fit `lines`, with each
+    /// line centered inside it. Used for the pause, ready and game-over messages.
+    fn draw_overlay_text(f: &mut Frame, area: Rect, title: &str, lines: Vec<Line>, style: Style) {
+        let overlay_area = overlay_rect(area, OVERLAY_WIDTH, lines.len() as u16 + 2);
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray))
+            .style(style);
+        let lines: Vec<Line> = lines
+            .into_iter()
+            .map(|line| line.alignment(Alignment::Center))
+            .collect();
+
+        f.render_widget(Clear, overlay_area);
+        f.render_widget(Paragraph::new(lines).block(block), overlay_area);
+    }
+
+    /// A bordered line of text across the middle of the board.
+    fn draw_banner(f: &mut Frame, board_area: Rect, text: &str, color: Color) {
+        let banner_area = Rect {
+            x: board_area.x,
+            y: board_area.y + board_area.height.saturating_sub(3) / 2,
+            width: board_area.width,
+            height: 3.min(board_area.height),
+        };
+
+        let banner = Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(color).add_modifier(Modifier::BOLD))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(color)),
+            );
+
+        f.render_widget(Clear, banner_area);
+        f.render_widget(banner, banner_area);
+    }
+
     fn draw_info(f: &mut Frame, area: Rect, state: &GameState) {
-        let lines_cleared_in_level = state.lines_cleared % state.config.lines_per_level;
-        let progress = lines_cleared_in_level as f64 / state.config.lines_per_level as f64;
-        let progress_bar = Self::create_progress_bar(progress);
+        let level_goal = state.lines_this_level + state.lines_until_next_level;
+        let progress_bar = Self::create_progress_bar(state.level_progress());
 
-        let lines = vec![
+        let mut lines = vec![
             Line::from(vec![Span::styled(
                 "SCORE",
                 Style::default()
@@ -110,6 +285,26 @@ impl Renderer {
                 Style::default().fg(Color::White),
             )]),
             Line::from(""),
+        ];
+
+        // Lives only matter when there is more than one; show them after LINES
+        if state.config.lives > 1 {
+            lines.extend([
+                Line::from(vec![Span::styled(
+                    "LIVES",
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                )]),
+                Line::from(vec![Span::styled(
+                    "♥ ".repeat(state.lives as usize),
+                    Style::default().fg(Color::Red),
+                )]),
+                Line::from(""),
+            ]);
+        }
+
+        lines.extend([
             Line::from(vec![Span::styled(
                 "PROGRESS",
                 Style::default()
@@ -117,10 +312,7 @@ impl Renderer {
                     .add_modifier(Modifier::BOLD),
             )]),
             Line::from(vec![Span::styled(
-                format!(
-                    "{}/{}",
-                    lines_cleared_in_level, state.config.lines_per_level
-                ),
+                format!("{}/{}", state.lines_this_level, level_goal),
                 Style::default().fg(Color::White),
             )]),
             Line::from(vec![Span::styled(
@@ -128,6 +320,16 @@ impl Renderer {
                 Style::default().fg(Color::Green),
             )]),
             Line::from(""),
+            Line::from(vec![Span::styled(
+                "RANK",
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )]),
+            Line::from(vec![Span::styled(
+                state.config.rank_for(state.score, state.lines_cleared),
+                Style::default().fg(Color::White),
+            )]),
             Line::from(""),
             Line::from(vec![Span::styled(
                 "[H] Help",
@@ -135,7 +337,7 @@ impl Renderer {
                     .fg(Color::Gray)
                     .add_modifier(Modifier::BOLD),
             )]),
-        ];
+        ]);
 
         let paragraph = Paragraph::new(lines).block(
             Block::default()
@@ -150,11 +352,19 @@ impl Renderer {
     fn draw_board(f: &mut Frame, area: Rect, state: &GameState) {
         let board_width = state.board.get_width();
         let board_height = state.board.get_height();
+        let columns = cell_columns(state.config.cell_width);
 
         let ghost_y = state
-            .config
-            .enable_ghost_piece
-            .then(|| Self::calculate_ghost_y(state));
+            .ghost_piece()
+            .zip(state.current_piece)
+            .filter(|(ghost, piece)| ghost_in_range(piece.y, ghost.y, state.config.ghost_proximity))
+            .map(|(ghost, _)| ghost.y);
+        let held_ghost = (state.config.enable_hold && state.config.assist_hold_ghost)
+            .then(|| state.held_piece_landing())
+            .flatten();
+        let planning_ghosts = state.planning_ghosts();
+        let new_holes = state.highlighted_holes(Instant::now());
+        let spawn_fading = state.spawn_fading(Instant::now());
 
         let show_cleared_animation = state.should_show_cleared_rows();
         let cleared_rows: Vec<usize> = state
@@ -163,103 +373,181 @@ impl Renderer {
             .map(|anim| anim.cleared_rows.clone())
             .unwrap_or_default();
 
+        let glyphs = BorderGlyphs::for_style(state.config.border_style);
         let mut board_lines = Vec::with_capacity(board_height);
 
         for y in 0..board_height {
-            let mut line_spans = Vec::with_capacity(board_width * 2 + 2);
+            let mut line_spans = Vec::with_capacity(board_width + 2);
 
-            line_spans.push(Span::styled("│", Style::default().fg(Color::White)));
+            line_spans.push(Span::styled(
+                glyphs.vertical,
+                Style::default().fg(Color::White),
+            ));
 
             let is_cleared_row = show_cleared_animation && cleared_rows.contains(&y);
 
             for x in 0..board_width {
                 let (cell_content, is_ghost) = Self::get_combined_cell(state, ghost_y, x, y);
-                let color = Self::get_piece_color(cell_content);
+                let locked = state.board.get_cell(x, y).is_some();
+                let color = if state.config.height_gradient && locked {
+                    Self::height_color(y, board_height)
+                } else {
+                    Self::get_piece_color(cell_content)
+                };
+                let color = if locked {
+                    kill_screen_color(color, &state.config, state.level, x, y)
+                } else {
+                    color
+                };
 
-                let block_str = "██";
-                let style = if is_cleared_row {
-                    Style::default()
-                        .fg(Color::White)
-                        .add_modifier(Modifier::REVERSED)
+                if cell_content.is_none() && !is_cleared_row {
+                    if let Some(held) = held_ghost.filter(|p| Self::occupies(p, x, y)) {
+                        let style = Style::default()
+                            .fg(Self::get_piece_color(Some(held.kind)))
+                            .add_modifier(Modifier::DIM);
+                        line_spans.push(Span::styled(fit_glyph("::", columns), style));
+                        continue;
+                    }
+
+                    if let Some(plan) = planning_ghosts.iter().find(|p| Self::occupies(p, x, y)) {
+                        let style = Style::default()
+                            .fg(Self::get_piece_color(Some(plan.kind)))
+                            .add_modifier(Modifier::DIM);
+                        line_spans.push(Span::styled(fit_glyph("··", columns), style));
+                        continue;
+                    }
+
+                    if new_holes.contains(&(x, y)) {
+                        line_spans.push(Span::styled(
+                            fit_glyph("░░", columns),
+                            Style::default().fg(Color::Red),
+                        ));
+                        continue;
+                    }
+
+                    let glyph = fit_glyph(Self::empty_cell_glyph(state.config.show_grid), columns);
+                    let style = Style::default()
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::DIM);
+                    line_spans.push(Span::styled(glyph, style));
+                    continue;
+                }
+
+                let (block_str, style) = if is_cleared_row {
+                    (
+                        "██",
+                        Style::default()
+                            .fg(Color::White)
+                            .add_modifier(Modifier::REVERSED),
+                    )
                 } else if is_ghost {
-                    Style::default().fg(color).add_modifier(Modifier::DIM)
+                    Self::ghost_cell(state.config.ghost_style, color)
+                } else if spawn_fading && !locked {
+                    ("██", Style::default().fg(color).add_modifier(Modifier::DIM))
                 } else {
-                    Style::default().fg(color)
+                    ("██", Style::default().fg(color))
                 };
-                line_spans.push(Span::styled(block_str, style));
+                line_spans.push(Span::styled(fit_glyph(block_str, columns), style));
             }
 
-            line_spans.push(Span::styled("│", Style::default().fg(Color::White)));
+            line_spans.push(Span::styled(
+                glyphs.vertical,
+                Style::default().fg(Color::White),
+            ));
             board_lines.push(Line::from(line_spans));
         }
 
-        let top_border = "┌".to_string() + &"─".repeat(board_width * 2) + "┐";
-        let bottom_border = "└".to_string() + &"─".repeat(board_width * 2) + "┘";
-
-        let mut full_lines = vec![Line::from(vec![Span::styled(
+        let top_border = glyphs.top_left.to_string()
+            + &glyphs.horizontal.repeat(board_width * columns)
+            + glyphs.top_right;
+        let bottom_border = glyphs.bottom_left.to_string()
+            + &glyphs.horizontal.repeat(board_width * columns)
+            + glyphs.bottom_right;
+
+        let mut full_lines = Vec::with_capacity(board_height + 3);
+        if state.config.show_heights {
+            let markers: String = state
+                .board
+                .column_heights()
+                .into_iter()
+                .map(|height| Self::height_marker(height, columns))
+                .collect();
+            full_lines.push(Line::from(Span::styled(
+                format!(" {} ", markers),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        full_lines.push(Line::from(vec![Span::styled(
             top_border,
             Style::default().fg(Color::White),
-        )])];
+        )]));
         full_lines.extend(board_lines);
         full_lines.push(Line::from(vec![Span::styled(
             bottom_border,
             Style::default().fg(Color::White),
         )]));
 
-        let paragraph = Paragraph::new(full_lines).alignment(Alignment::Center);
+        // A board taller than the terminal keeps its floor in view and loses the top
+        let hidden_rows = full_lines.len().saturating_sub(area.height as usize) as u16;
+        let paragraph = Paragraph::new(full_lines)
+            .alignment(Alignment::Center)
+            .scroll((hidden_rows, 0));
 
         f.render_widget(paragraph, area);
     }
 
-    fn calculate_ghost_y(state: &GameState) -> i32 {
-        if let Some(ref piece) = state.current_piece {
-            let mut ghost_y = piece.y;
-            loop {
-                let test_piece = crate::tetrimino::Tetrimino {
-                    x: piece.x,
-                    y: ghost_y + 1,
-                    kind: piece.kind,
-                    rotation: piece.rotation,
-                };
+    /// Glyph and style for a ghost cell of the given piece color.
+    fn ghost_cell(style: GhostStyle, color: Color) -> (&'static str, Style) {
+        match style {
+            GhostStyle::Dimmed => ("██", Style::default().fg(color).add_modifier(Modifier::DIM)),
+            GhostStyle::Outline => ("▒▒", Style::default().fg(color)),
+            GhostStyle::Hollow => ("[]", Style::default().fg(color)),
+        }
+    }
 
-                if !state.board.is_valid_position(&test_piece) {
-                    break;
-                }
-                ghost_y += 1;
-            }
-            ghost_y
-        } else {
-            0
+    /// Marker `columns` wide for a column's stack height; empty columns stay blank
+    /// and heights too wide to fit show as "+".
+    fn height_marker(height: usize, columns: usize) -> String {
+        let limit = 10_usize.pow(columns as u32);
+        match height {
+            0 => " ".repeat(columns),
+            h if h < limit => format!("{:>width$}", h, width = columns),
+            _ => "+".repeat(columns),
         }
     }
 
+    /// Glyph for an empty playfield cell; purely cosmetic, collision reads the board.
+    fn empty_cell_glyph(show_grid: bool) -> &'static str {
+        if show_grid { " ·" } else { "  " }
+    }
+
+    fn occupies(piece: &Tetrimino, x: usize, y: usize) -> bool {
+        piece
+            .get_blocks()
+            .into_iter()
+            .any(|(dx, dy)| piece.x + dx == x as i32 && piece.y + dy == y as i32)
+    }
+
     fn get_combined_cell(
         state: &GameState,
         ghost_y: Option<i32>,
         x: usize,
         y: usize,
     ) -> (Option<TetriminoType>, bool) {
-        let xi = x as i32;
-        let yi = y as i32;
-
         if let Some(ref piece) = state.current_piece {
-            for (dx, dy) in piece.get_blocks() {
-                let piece_x = piece.x + dx;
-                let piece_y = piece.y + dy;
-
-                if piece_x == xi && piece_y == yi {
-                    return (Some(piece.kind), false);
-                }
+            // The real piece always wins over its own ghost
+            if Self::occupies(piece, x, y) {
+                return (Some(piece.kind), false);
             }
 
-            if let Some(ghost_pos) = ghost_y {
-                for (dx, dy) in piece.get_blocks() {
-                    let ghost_piece_x = piece.x + dx;
-                    let ghost_piece_y = ghost_pos + dy;
-
-                    if ghost_piece_x == xi && ghost_piece_y == yi {
-                        return (Some(piece.kind), true);
-                    }
+            // A landed piece hides its ghost entirely rather than leaving stray cells
+            if let Some(ghost_pos) = ghost_y.filter(|&ghost_pos| ghost_pos != piece.y) {
+                let ghost = Tetrimino {
+                    y: ghost_pos,
+                    ..*piece
+                };
+                if Self::occupies(&ghost, x, y) {
+                    return (Some(piece.kind), true);
                 }
             }
         }
@@ -280,7 +568,42 @@ impl Renderer {
         }
     }
 
+    /// Danger gradient for `height_gradient`: blue on the bottom row through green and
+    /// yellow to red on the top row.
+    fn height_color(row: usize, board_height: usize) -> Color {
+        const STOPS: [(u8, u8, u8); 5] = [
+            (40, 90, 255),
+            (0, 200, 200),
+            (60, 200, 60),
+            (240, 220, 0),
+            (255, 40, 40),
+        ];
+
+        let height = board_height.saturating_sub(1).max(1) as f64;
+        let t = (height - row.min(board_height.saturating_sub(1)) as f64) / height;
+        let scaled = t * (STOPS.len() - 1) as f64;
+        let index = (scaled.floor() as usize).min(STOPS.len() - 2);
+        let frac = scaled - index as f64;
+
+        let lerp = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * frac).round() as u8;
+        let (from, to) = (STOPS[index], STOPS[index + 1]);
+        Color::Rgb(lerp(from.0, to.0), lerp(from.1, to.1), lerp(from.2, to.2))
+    }
+
     fn draw_next_pieces(f: &mut Frame, area: Rect, state: &GameState) {
+        let columns = preview_cell_columns(area.width, cell_columns(state.config.cell_width));
+        let paragraph = Paragraph::new(Self::next_pieces_lines(state, columns)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        );
+
+        f.render_widget(paragraph, area);
+    }
+
+    /// The NEXT panel's heading and previews, `preview_spacing` blank lines apart,
+    /// drawn `columns` terminal columns per cell.
+    fn next_pieces_lines(state: &GameState, columns: usize) -> Vec<Line<'static>> {
         let preview_count = state.config.preview_count.clamp(1, 6);
         let display_count = preview_count.min(state.next_pieces.len());
 
@@ -296,19 +619,15 @@ impl Renderer {
 
         for (i, &piece_type) in state.next_pieces.iter().enumerate().take(display_count) {
             if i > 0 {
-                lines.push(Line::from(""));
+                lines.extend((0..state.config.preview_spacing).map(|_| Line::from("")));
             }
-            let piece_lines = Self::get_piece_display(piece_type);
-            lines.extend(piece_lines);
+            lines.extend(Self::get_piece_display(
+                piece_type,
+                state.config.rotation_system,
+                columns,
+            ));
         }
-
-        let paragraph = Paragraph::new(lines).block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Cyan)),
-        );
-
-        f.render_widget(paragraph, area);
+        lines
     }
 
     fn draw_held_piece(f: &mut Frame, area: Rect, state: &GameState) {
@@ -323,7 +642,9 @@ impl Renderer {
         ];
 
         if let Some(piece_type) = state.held_piece {
-            let piece_lines = Self::get_piece_display(piece_type);
+            let columns = preview_cell_columns(area.width, cell_columns(state.config.cell_width));
+            let piece_lines =
+                Self::get_piece_display(piece_type, state.config.rotation_system, columns);
             lines.extend(piece_lines);
         } else {
             lines.push(Line::from(""));
@@ -341,12 +662,20 @@ impl Renderer {
         f.render_widget(paragraph, area);
     }
 
-    fn get_piece_display(piece_type: TetriminoType) -> Vec<Line<'static>> {
-        let piece = crate::tetrimino::Tetrimino::new(piece_type);
+    /// A preview of `piece_type` in the orientation it spawns in under `system`.
+    fn get_piece_display(
+        piece_type: TetriminoType,
+        system: RotationSystem,
+        columns: usize,
+    ) -> Vec<Line<'static>> {
+        let piece = Tetrimino {
+            rotation: rotation::spawn_rotation(system, piece_type),
+            ..Tetrimino::new(piece_type)
+        };
         let blocks = piece.get_blocks();
         let color = Self::get_piece_color(Some(piece_type));
 
-        let mut display = vec!["        ".to_string(); 4];
+        let mut display = vec![" ".repeat(PREVIEW_COLUMNS * columns); 4];
 
         for (dx, dy) in blocks {
             let x = (dx + 1) as usize;
@@ -354,9 +683,8 @@ impl Renderer {
             if y < 4 {
                 let row = display.get_mut(y).unwrap();
                 let mut chars: Vec<char> = row.chars().collect();
-                if x * 2 < chars.len() {
-                    chars[x * 2] = '█';
-                    chars[x * 2 + 1] = '█';
+                if (x + 1) * columns <= chars.len() {
+                    chars[x * columns..(x + 1) * columns].fill('█');
                     *row = chars.into_iter().collect();
                 }
             }
@@ -375,6 +703,114 @@ impl Renderer {
         "█".repeat(filled) + &"░".repeat(empty)
     }
 
+    fn draw_fps_overlay(f: &mut Frame, stats: &FrameStats) {
+        let ms = |d: Option<std::time::Duration>| {
+            d.map_or_else(
+                || "-".to_string(),
+                |d| format!("{:.1}", d.as_secs_f64() * 1000.0),
+            )
+        };
+
+        let lines = vec![
+            Line::from(vec![
+                Span::styled("FPS: ", Style::default().fg(Color::Yellow)),
+                Span::styled(
+                    stats
+                        .fps()
+                        .map_or_else(|| "-".to_string(), |fps| format!("{:.1}", fps)),
+                    Style::default().fg(Color::White),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("ms:  ", Style::default().fg(Color::Yellow)),
+                Span::styled(
+                    format!(
+                        "{}/{}/{}",
+                        ms(stats.min()),
+                        ms(stats.average()),
+                        ms(stats.max())
+                    ),
+                    Style::default().fg(Color::White),
+                ),
+            ]),
+        ];
+
+        let width = 28.min(f.area().width);
+        let fps_area = Rect {
+            x: f.area().width.saturating_sub(width),
+            y: 0,
+            width,
+            height: 4.min(f.area().height),
+        };
+
+        let paragraph = Paragraph::new(lines).block(
+            Block::default()
+                .title(" FRAMES (min/avg/max ms) ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Magenta))
+                .style(Style::default().bg(Color::Black)),
+        );
+
+        f.render_widget(Clear, fps_area);
+        f.render_widget(paragraph, fps_area);
+    }
+
+    fn draw_debug_overlay(f: &mut Frame, state: &GameState) {
+        let format_pieces = |pieces: &[TetriminoType]| {
+            pieces
+                .iter()
+                .map(|kind| format!("{:?}", kind))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        let lines = vec![
+            Line::from(vec![
+                Span::styled("Bag:   ", Style::default().fg(Color::Yellow)),
+                Span::styled(
+                    format_pieces(&state.remaining_bag()),
+                    Style::default().fg(Color::White),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("Queue: ", Style::default().fg(Color::Yellow)),
+                Span::styled(
+                    format_pieces(&state.next_pieces),
+                    Style::default().fg(Color::White),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("Seed:  ", Style::default().fg(Color::Yellow)),
+                Span::styled(state.seed.to_string(), Style::default().fg(Color::White)),
+            ]),
+            Line::from(vec![
+                Span::styled("Bumps: ", Style::default().fg(Color::Yellow)),
+                Span::styled(
+                    state.board.bumpiness().to_string(),
+                    Style::default().fg(Color::White),
+                ),
+            ]),
+        ];
+
+        let debug_area = Rect {
+            x: 0,
+            y: f.area().height.saturating_sub(6),
+            width: 36.min(f.area().width),
+            height: 6.min(f.area().height),
+        };
+
+        let paragraph = Paragraph::new(lines).block(
+            Block::default()
+                .title(" DEBUG ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Magenta))
+                .style(Style::default().bg(Color::Black)),
+        );
+
+        f.render_widget(Clear, debug_area);
+        f.render_widget(paragraph, debug_area);
+    }
+
     fn draw_help_overlay(f: &mut Frame) {
         let help_block = Block::default()
             .title(" CONTROLS ")
@@ -384,9 +820,9 @@ impl Renderer {
 
         let help_area = Rect {
             x: (f.area().width.saturating_sub(36)) / 2,
-            y: (f.area().height.saturating_sub(18)) / 2,
+            y: (f.area().height.saturating_sub(20)) / 2,
             width: 36.min(f.area().width),
-            height: 18.min(f.area().height),
+            height: 20.min(f.area().height),
         };
 
         f.render_widget(Clear, help_area);
@@ -404,6 +840,10 @@ impl Renderer {
                 Span::styled("Move:  ", Style::default().fg(Color::Yellow)),
                 Span::styled("← →", Style::default().fg(Color::White)),
             ]),
+            Line::from(vec![
+                Span::styled("Wall:   ", Style::default().fg(Color::Yellow)),
+                Span::styled("HOME / END", Style::default().fg(Color::White)),
+            ]),
             Line::from(vec![
                 Span::styled("Drop:   ", Style::default().fg(Color::Yellow)),
                 Span::styled("↓", Style::default().fg(Color::White)),
@@ -412,6 +852,10 @@ impl Renderer {
                 Span::styled("Hard:   ", Style::default().fg(Color::Yellow)),
                 Span::styled("SPACE", Style::default().fg(Color::White)),
             ]),
+            Line::from(vec![
+                Span::styled("Sonic:  ", Style::default().fg(Color::Yellow)),
+                Span::styled("↑", Style::default().fg(Color::White)),
+            ]),
             Line::from(vec![
                 Span::styled("Rotate: ", Style::default().fg(Color::Yellow)),
                 Span::styled("X / Z", Style::default().fg(Color::White)),
@@ -424,6 +868,22 @@ impl Renderer {
                 Span::styled("Pause:  ", Style::default().fg(Color::Yellow)),
                 Span::styled("P / ESC", Style::default().fg(Color::White)),
             ]),
+            Line::from(vec![
+                Span::styled("Pick:   ", Style::default().fg(Color::Yellow)),
+                Span::styled("1-7 (practice)", Style::default().fg(Color::White)),
+            ]),
+            Line::from(vec![
+                Span::styled("Ghost:  ", Style::default().fg(Color::Yellow)),
+                Span::styled("G", Style::default().fg(Color::White)),
+            ]),
+            Line::from(vec![
+                Span::styled("Plan:   ", Style::default().fg(Color::Yellow)),
+                Span::styled("N (planning)", Style::default().fg(Color::White)),
+            ]),
+            Line::from(vec![
+                Span::styled("Setup:  ", Style::default().fg(Color::Yellow)),
+                Span::styled("O", Style::default().fg(Color::White)),
+            ]),
             Line::from(""),
             Line::from(vec![
                 Span::styled("Help:   ", Style::default().fg(Color::Yellow)),
@@ -441,82 +901,928 @@ impl Renderer {
         f.render_widget(help_text, inner_area);
     }
 
-    pub fn render_pause(&mut self, state: &GameState) -> Result<()> {
+    /// Draws the attract-mode demo game with a prompt to start.
+    pub fn render_attract(&mut self, state: &GameState) -> Result<()> {
         self.terminal.draw(|f| {
             Self::draw_game(f, state);
 
-            let pause_block = Block::default()
-                .title(" PAUSED ")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::DarkGray))
-                .style(Style::default().bg(Color::DarkGray).fg(Color::White));
-
-            let pause_area = Rect {
-                x: (f.area().width.saturating_sub(30)) / 2,
-                y: (f.area().height.saturating_sub(6)) / 2,
-                width: 30.min(f.area().width),
-                height: 6.min(f.area().height),
+            let prompt_area = Rect {
+                x: 0,
+                y: f.area().height.saturating_sub(1),
+                width: f.area().width,
+                height: 1.min(f.area().height),
             };
+            let prompt = Paragraph::new("DEMO - press any key to play")
+                .alignment(Alignment::Center)
+                .style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                );
 
-            f.render_widget(Clear, pause_area);
-            f.render_widget(pause_block, pause_area);
+            f.render_widget(Clear, prompt_area);
+            f.render_widget(prompt, prompt_area);
+        })?;
+        Ok(())
+    }
 
-            let inner_area = Rect {
-                x: pause_area.x + 1,
-                y: pause_area.y + 1,
-                width: pause_area.width.saturating_sub(2),
-                height: pause_area.height.saturating_sub(2),
+    /// Draws the replay viewer's board with its position and controls.
+    pub fn render_replay(&mut self, viewer: &ReplayViewer) -> Result<()> {
+        self.terminal.draw(|f| {
+            Self::draw_game(f, viewer.state());
+
+            let status_area = Rect {
+                x: 0,
+                y: f.area().height.saturating_sub(1),
+                width: f.area().width,
+                height: 1.min(f.area().height),
+            };
+            let (placed, total) = viewer.position();
+            let mode = if viewer.is_playing() {
+                "PLAYING"
+            } else {
+                "PAUSED"
             };
+            let status = Paragraph::new(format!(
+                "REPLAY {} - piece {}/{}  Space: play  →: step  N: next clear  Q: back",
+                mode, placed, total
+            ))
+            .alignment(Alignment::Center)
+            .style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            );
+
+            f.render_widget(Clear, status_area);
+            f.render_widget(status, status_area);
+        })?;
+        Ok(())
+    }
 
-            let pause_text = Paragraph::new(vec![
-                Line::from("Press PAUSE again to resume").alignment(Alignment::Center),
-                Line::from("Press QUIT to exit game").alignment(Alignment::Center),
-            ])
-            .alignment(Alignment::Center);
+    pub fn render_pause(&mut self, state: &GameState) -> Result<()> {
+        self.terminal.draw(|f| {
+            Self::draw_game(f, state);
 
-            f.render_widget(pause_text, inner_area);
+            Self::draw_overlay_text(
+                f,
+                f.area(),
+                " PAUSED ",
+                vec![
+                    Line::from("Press PAUSE again to resume"),
+                    Line::from("Press QUIT to exit game"),
+                ],
+                Style::default().bg(Color::DarkGray).fg(Color::White),
+            );
         })?;
         Ok(())
     }
 
-    pub fn render_game_over(&mut self, state: &GameState) -> Result<()> {
+    pub fn render_ready(&mut self, state: &GameState) -> Result<()> {
         self.terminal.draw(|f| {
             Self::draw_game(f, state);
 
-            let over_block = Block::default()
-                .title(" GAME OVER ")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::DarkGray))
-                .style(Style::default().bg(Color::DarkGray).fg(Color::White));
-
-            let over_area = Rect {
-                x: (f.area().width.saturating_sub(30)) / 2,
-                y: (f.area().height.saturating_sub(8)) / 2,
-                width: 30.min(f.area().width),
-                height: 8.min(f.area().height),
+            Self::draw_overlay_text(
+                f,
+                f.area(),
+                " READY ",
+                vec![
+                    Line::from(state.mode.label()),
+                    Line::from("Press any key to start"),
+                ],
+                Style::default().bg(Color::DarkGray).fg(Color::White),
+            );
+        })?;
+        Ok(())
+    }
+
+    pub fn render_settings(&mut self, state: &GameState, menu: &SettingsMenu) -> Result<()> {
+        self.terminal.draw(|f| {
+            Self::draw_game(f, state);
+
+            let height = SettingsItem::ALL.len() as u16 + 5;
+            let settings_area = Rect {
+                x: (f.area().width.saturating_sub(34)) / 2,
+                y: (f.area().height.saturating_sub(height)) / 2,
+                width: 34.min(f.area().width),
+                height: height.min(f.area().height),
             };
 
-            f.render_widget(Clear, over_area);
-            f.render_widget(over_block, over_area);
+            let mut lines: Vec<Line> = SettingsItem::ALL
+                .iter()
+                .map(|&item| {
+                    let selected = item == menu.selected();
+                    let marker = if selected { "> " } else { "  " };
+                    let style = if selected {
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(Color::White)
+                    };
+                    Line::from(vec![
+                        Span::styled(format!("{}{:<15}", marker, item.label()), style),
+                        Span::styled(item.value(&state.config), style),
+                    ])
+                })
+                .collect();
+            lines.push(Line::from(""));
+            lines.push(Line::from("↑↓ select  ←→ change  ESC close").alignment(Alignment::Center));
+
+            let paragraph = Paragraph::new(lines).block(
+                Block::default()
+                    .title(" SETTINGS ")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan))
+                    .style(Style::default().bg(Color::DarkGray).fg(Color::White)),
+            );
+
+            f.render_widget(Clear, settings_area);
+            f.render_widget(paragraph, settings_area);
+        })?;
+        Ok(())
+    }
+
+    pub fn render_title(&mut self, menu: &TitleMenu, stats: &LifetimeStats) -> Result<()> {
+        self.terminal.draw(|f| Self::draw_title(f, menu, stats))?;
+        Ok(())
+    }
 
-            let inner_area = Rect {
-                x: over_area.x + 1,
-                y: over_area.y + 1,
-                width: over_area.width.saturating_sub(2),
-                height: over_area.height.saturating_sub(2),
+    fn draw_title(f: &mut Frame, menu: &TitleMenu, stats: &LifetimeStats) {
+        let height = GameMode::ALL.len() as u16 + 10;
+        let title_area = Rect {
+            x: (f.area().width.saturating_sub(34)) / 2,
+            y: (f.area().height.saturating_sub(height)) / 2,
+            width: 34.min(f.area().width),
+            height: height.min(f.area().height),
+        };
+
+        let mut lines = vec![
+            Line::from(Span::styled(
+                "T E T R I S",
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .alignment(Alignment::Center),
+            Line::from(""),
+        ];
+        lines.extend(GameMode::ALL.iter().map(|&mode| {
+            let selected = mode == menu.selected();
+            let marker = if selected { "> " } else { "  " };
+            let style = if selected {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
             };
+            Line::from(Span::styled(format!("{}{}", marker, mode.label()), style))
+        }));
+        lines.push(Line::from(""));
+        lines.push(
+            Line::from(Span::styled(
+                menu.selected().description(),
+                Style::default().fg(Color::Gray),
+            ))
+            .alignment(Alignment::Center),
+        );
+        lines.push(Line::from(""));
+        lines.push(
+            Line::from(Span::styled(
+                format!("Games {}  Best {}", stats.games_played, stats.best_score),
+                Style::default().fg(Color::DarkGray),
+            ))
+            .alignment(Alignment::Center),
+        );
+        lines.push(Line::from(""));
+        lines.push(Line::from("↑↓ select  ENTER play  Q quit").alignment(Alignment::Center));
 
-            let over_text = Paragraph::new(vec![
-                Line::from(format!("Final Score: {}", state.score)).alignment(Alignment::Center),
-                Line::from(format!("Level Reached: {}", state.level)).alignment(Alignment::Center),
-                Line::from(format!("Lines Cleared: {}", state.lines_cleared))
-                    .alignment(Alignment::Center),
-                Line::from("Press any key to exit").alignment(Alignment::Center),
-            ])
-            .alignment(Alignment::Center);
+        let paragraph = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        );
+
+        f.render_widget(Clear, f.area());
+        f.render_widget(paragraph, title_area);
+    }
+
+    pub fn render_game_over(&mut self, state: &GameState) -> Result<()> {
+        self.terminal.draw(|f| {
+            Self::draw_game(f, state);
 
-            f.render_widget(over_text, inner_area);
+            let lines = vec![
+                Line::from(format!("Final Score: {}", state.score)),
+                Line::from(format!("Level Reached: {}", state.level)),
+                Line::from(format!("Lines Cleared: {}", state.lines_cleared)),
+                Line::from(format!(
+                    "Rank: {}",
+                    state.config.rank_for(state.score, state.lines_cleared)
+                )),
+                Line::from(format!("Best Combo: {}", state.max_combo())),
+                Line::from(format!("Best B2B Chain: {}", state.max_b2b_chain())),
+                blocked_line(&state.blocking_cells),
+                Line::from("R: restart  S: same seed  V: review"),
+                Line::from("Any other key: title"),
+            ];
+
+            Self::draw_overlay_text(
+                f,
+                f.area(),
+                " GAME OVER ",
+                lines,
+                Style::default().bg(Color::DarkGray).fg(Color::White),
+            );
         })?;
         Ok(())
     }
 }
+
+/// Width of the boxes drawn by `Renderer::draw_overlay_text`.
+const OVERLAY_WIDTH: u16 = 30;
+
+/// A `width` x `height` rect centered in `area`, shrunk to fit when `area` is smaller.
+fn overlay_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Names the cells that blocked the final spawn, or a blank line if there were none.
+fn blocked_line(cells: &[(usize, usize)]) -> Line<'static> {
+    if cells.is_empty() {
+        return Line::from("");
+    }
+    let coords: Vec<String> = cells.iter().map(|(x, y)| format!("({x},{y})")).collect();
+    Line::from(format!("Blocked: {}", coords.join(" "))).style(Style::default().fg(Color::Red))
+}
+
+/// Column widths for the hold/info panel, the board, and the next panel within
+/// `area`. The side panels grow with the board, to half its width, but give way to
+/// it when the terminal is narrow; a board wider than `area` is clamped to it. The
+/// side columns collapse to nothing while `minimal_ui` hides them.
+pub fn layout_constraints(state: &GameState, area: Rect) -> [Constraint; 3] {
+    let columns = cell_columns(state.config.cell_width);
+    let board_width = (state.board.get_width() as u16 * columns as u16 + 2).min(area.width);
+    let side_width = if state.side_panels_visible(Instant::now()) {
+        let preferred = SIDE_PANEL_MIN_WIDTH
+            .max(preview_width(columns))
+            .max(board_width / 2);
+        preferred.min((area.width - board_width) / 2)
+    } else {
+        0
+    };
+
+    [
+        Constraint::Length(side_width),
+        Constraint::Length(board_width),
+        Constraint::Length(side_width),
+    ]
+}
+
+/// Terminal columns a piece preview needs, borders included, at `columns` per cell.
+fn preview_width(columns: usize) -> u16 {
+    (PREVIEW_COLUMNS * columns) as u16 + 2
+}
+
+/// Columns per cell for the previews in a side panel `panel_width` wide: the board's
+/// `columns` when a preview fits at that size, otherwise single-column cells.
+fn preview_cell_columns(panel_width: u16, columns: usize) -> usize {
+    if panel_width >= preview_width(columns) {
+        columns
+    } else {
+        1
+    }
+}
+
+/// A locked cell's color under the kill screen: `color` below `kill_screen_level` or
+/// with `kill_screen` off, otherwise a pick from `KILL_SCREEN_PALETTE` that varies by
+/// cell and shifts each level, so the stack looks corrupted.
+fn kill_screen_color(color: Color, config: &GameConfig, level: u32, x: usize, y: usize) -> Color {
+    if !config.kill_screen || level < config.kill_screen_level {
+        return color;
+    }
+    let index = (x * 7 + y * 3 + level as usize) % KILL_SCREEN_PALETTE.len();
+    KILL_SCREEN_PALETTE[index]
+}
+
+/// Whether the ghost should show for a piece at row `piece_y` that lands at `ghost_y`,
+/// given the `ghost_proximity` setting.
+fn ghost_in_range(piece_y: i32, ghost_y: i32, proximity: Option<usize>) -> bool {
+    proximity.is_none_or(|rows| (ghost_y - piece_y) as i64 <= rows as i64)
+}
+
+/// Terminal columns each cell takes for a `cell_width` setting.
+pub fn cell_columns(cell_width: u8) -> usize {
+    if cell_width == 1 { 1 } else { 2 }
+}
+
+/// A two-column cell glyph cut down to `columns`. Hollow ghosts get a box, since
+/// half of "[]" wouldn't read as a cell.
+fn fit_glyph(glyph: &'static str, columns: usize) -> &'static str {
+    match (glyph, columns) {
+        (_, 2..) => glyph,
+        ("[]", _) => "□",
+        _ => glyph
+            .char_indices()
+            .last()
+            .map_or(glyph, |(i, _)| &glyph[i..]),
+    }
+}
+
+/// Heights of the HOLD panel and the INFO panel below it. With hold disabled the
+/// HOLD panel collapses and INFO takes the whole column.
+pub fn left_column_constraints(enable_hold: bool) -> [Constraint; 2] {
+    let hold_height = if enable_hold { HOLD_PANEL_HEIGHT } else { 0 };
+    [Constraint::Length(hold_height), Constraint::Min(0)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameConfig;
+
+    /// A terminal roomy enough that nothing in the layout has to give way.
+    const WIDE_AREA: Rect = Rect {
+        x: 0,
+        y: 0,
+        width: 120,
+        height: 40,
+    };
+
+    fn state_with_width(board_width: usize) -> GameState {
+        GameState::new(GameConfig {
+            board_width,
+            ..GameConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_overlay_rect_is_centered_and_clamped() {
+        let area = Rect::new(0, 0, 80, 24);
+        assert_eq!(overlay_rect(area, 30, 6), Rect::new(25, 9, 30, 6));
+
+        // Odd leftovers round toward the top-left.
+        assert_eq!(overlay_rect(area, 31, 5), Rect::new(24, 9, 31, 5));
+
+        // Offsets follow the area's origin.
+        let offset = Rect::new(10, 4, 40, 10);
+        assert_eq!(overlay_rect(offset, 30, 6), Rect::new(15, 6, 30, 6));
+
+        // A box larger than the area shrinks to fill it.
+        let small = Rect::new(2, 3, 20, 4);
+        assert_eq!(overlay_rect(small, 30, 11), small);
+    }
+
+    #[test]
+    fn test_height_marker() {
+        assert_eq!(Renderer::height_marker(0, 2), "  ");
+        assert_eq!(Renderer::height_marker(3, 2), " 3");
+        assert_eq!(Renderer::height_marker(20, 2), "20");
+        assert_eq!(Renderer::height_marker(120, 2), "++");
+        assert_eq!(Renderer::height_marker(0, 1), " ");
+        assert_eq!(Renderer::height_marker(3, 1), "3");
+        assert_eq!(Renderer::height_marker(12, 1), "+");
+    }
+
+    #[test]
+    fn test_layout_constraints_standard_board() {
+        let constraints = layout_constraints(&state_with_width(10), WIDE_AREA);
+        assert_eq!(
+            constraints,
+            [
+                Constraint::Length(14),
+                Constraint::Length(22),
+                Constraint::Length(14),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layout_constraints_single_width_cells() {
+        let mut state = state_with_width(10);
+        state.config.cell_width = 1;
+        assert_eq!(
+            layout_constraints(&state, WIDE_AREA),
+            [
+                Constraint::Length(14),
+                Constraint::Length(12),
+                Constraint::Length(14),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_board_border_matches_cell_width() {
+        for (cell_width, inner) in [(1, 10), (2, 20)] {
+            let mut state = state_with_width(10);
+            state.config.cell_width = cell_width;
+            let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(30, 24)).unwrap();
+            terminal
+                .draw(|f| Renderer::draw_board(f, f.area(), &state))
+                .unwrap();
+            let top_row: String = terminal.backend().buffer().content()[..30]
+                .iter()
+                .map(|cell| cell.symbol())
+                .collect();
+            assert_eq!(top_row.trim().chars().count(), inner + 2, "{}", cell_width);
+        }
+    }
+
+    #[test]
+    fn test_piece_display_single_width() {
+        let lines = Renderer::get_piece_display(TetriminoType::I, RotationSystem::Srs, 1);
+        assert!(lines.iter().all(|line| line.width() == PREVIEW_COLUMNS));
+        let blocks: usize = lines
+            .iter()
+            .map(|line| line.to_string().matches('█').count())
+            .sum();
+        assert_eq!(blocks, 4);
+    }
+
+    #[test]
+    fn test_fit_glyph_narrows_cells() {
+        assert_eq!(fit_glyph("██", 2), "██");
+        assert_eq!(fit_glyph("██", 1), "█");
+        assert_eq!(fit_glyph(" ·", 1), "·");
+        assert_eq!(fit_glyph("  ", 1), " ");
+        assert_eq!(fit_glyph("[]", 1), "□");
+    }
+
+    #[test]
+    fn test_layout_constraints_wide_board() {
+        let constraints = layout_constraints(&state_with_width(20), WIDE_AREA);
+        assert_eq!(
+            constraints,
+            [
+                Constraint::Length(21),
+                Constraint::Length(42),
+                Constraint::Length(21),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layout_constraints_narrow_terminal() {
+        // The panels shrink to the room left beside the board
+        let constraints = layout_constraints(&state_with_width(10), Rect::new(0, 0, 40, 24));
+        assert_eq!(
+            constraints,
+            [
+                Constraint::Length(9),
+                Constraint::Length(22),
+                Constraint::Length(9),
+            ]
+        );
+
+        // A board wider than the terminal takes all of it
+        let constraints = layout_constraints(&state_with_width(20), Rect::new(0, 0, 30, 24));
+        assert_eq!(
+            constraints,
+            [
+                Constraint::Length(0),
+                Constraint::Length(30),
+                Constraint::Length(0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_preview_cell_columns_shrink_in_narrow_panels() {
+        assert_eq!(preview_cell_columns(14, 2), 2);
+        assert_eq!(preview_cell_columns(12, 2), 2);
+        assert_eq!(preview_cell_columns(9, 2), 1);
+        assert_eq!(preview_cell_columns(9, 1), 1);
+    }
+
+    #[test]
+    fn test_tall_board_keeps_its_floor_in_view() {
+        let state = GameState::new(GameConfig {
+            board_height: 40,
+            ..GameConfig::default()
+        });
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(30, 24)).unwrap();
+        terminal
+            .draw(|f| Renderer::draw_board(f, f.area(), &state))
+            .unwrap();
+        let bottom_row: String = terminal.backend().buffer().content()[23 * 30..]
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        let glyphs = BorderGlyphs::for_style(state.config.border_style);
+        assert!(bottom_row.contains(glyphs.bottom_left), "{}", bottom_row);
+    }
+
+    fn minimal_state() -> GameState {
+        GameState::new(GameConfig {
+            minimal_ui: true,
+            ..GameConfig::default()
+        })
+    }
+
+    fn rendered_text(state: &GameState) -> String {
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(80, 30)).unwrap();
+        terminal.draw(|f| Renderer::draw_game(f, state)).unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn test_lives_show_between_lines_and_progress() {
+        let state = GameState::new(GameConfig {
+            lives: 3,
+            ..GameConfig::default()
+        });
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(20, 30)).unwrap();
+        terminal
+            .draw(|f| Renderer::draw_info(f, f.area(), &state))
+            .unwrap();
+        let rows: Vec<String> = terminal
+            .backend()
+            .buffer()
+            .content()
+            .chunks(20)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+            .collect();
+        let row_of = |label: &str| rows.iter().position(|row| row.contains(label)).unwrap();
+
+        assert!(row_of("LINES") < row_of("LIVES"));
+        assert!(row_of("LIVES") < row_of("PROGRESS"));
+        assert!(rows[row_of("LIVES") + 1].contains("♥ ♥"));
+    }
+
+    #[test]
+    fn test_kill_screen_color_only_at_threshold() {
+        let config = GameConfig {
+            kill_screen: true,
+            kill_screen_level: 29,
+            ..GameConfig::default()
+        };
+
+        for level in [1, 15, 28] {
+            for (x, y) in [(0, 0), (3, 17), (9, 19)] {
+                assert_eq!(
+                    kill_screen_color(Color::Cyan, &config, level, x, y),
+                    Color::Cyan
+                );
+            }
+        }
+
+        let scrambled: Vec<_> = (0..10)
+            .map(|x| kill_screen_color(Color::Cyan, &config, 29, x, 19))
+            .collect();
+        assert!(scrambled.iter().all(|c| KILL_SCREEN_PALETTE.contains(c)));
+        assert!(scrambled.iter().any(|&c| c != scrambled[0]));
+    }
+
+    #[test]
+    fn test_kill_screen_color_off_by_default() {
+        let config = GameConfig::default();
+        assert_eq!(
+            kill_screen_color(Color::Yellow, &config, 99, 4, 10),
+            Color::Yellow
+        );
+    }
+
+    /// A board with an S piece in play at row `piece_y`.
+    fn state_with_s_at(piece_y: i32) -> GameState {
+        let mut state = GameState::new(GameConfig::default());
+        state.current_piece = Some(Tetrimino::from_spec(TetriminoType::S, 3, piece_y, 0));
+        state
+    }
+
+    /// Cells of the 10x20 board drawn as ghost, given a ghost at `ghost_y`.
+    fn ghost_cells(state: &GameState, ghost_y: Option<i32>) -> Vec<(usize, usize)> {
+        (0..20)
+            .flat_map(|y| (0..10).map(move |x| (x, y)))
+            .filter(|&(x, y)| Renderer::get_combined_cell(state, ghost_y, x, y).1)
+            .collect()
+    }
+
+    #[test]
+    fn test_combined_cell_piece_beats_overlapping_ghost() {
+        // A ghost one row below an S overlaps one of the piece's own cells
+        let state = state_with_s_at(10);
+        let piece = state.current_piece.unwrap();
+        let ghost = Tetrimino { y: 11, ..piece };
+
+        let ghost_cells = ghost_cells(&state, Some(11));
+        assert!(!ghost_cells.is_empty());
+        for (x, y) in ghost_cells {
+            assert!(!Renderer::occupies(&piece, x, y));
+            assert!(Renderer::occupies(&ghost, x, y));
+        }
+        for (dx, dy) in piece.get_blocks() {
+            let (x, y) = ((piece.x + dx) as usize, (piece.y + dy) as usize);
+            assert_eq!(
+                Renderer::get_combined_cell(&state, Some(11), x, y),
+                (Some(TetriminoType::S), false)
+            );
+        }
+    }
+
+    #[test]
+    fn test_combined_cell_hides_ghost_of_landed_piece() {
+        let state = state_with_s_at(10);
+        assert!(ghost_cells(&state, Some(10)).is_empty());
+        assert!(ghost_cells(&state, None).is_empty());
+        assert_eq!(ghost_cells(&state, Some(15)).len(), 4);
+    }
+
+    #[test]
+    fn test_next_pieces_lines_follow_spacing() {
+        for spacing in [0u8, 1, 2] {
+            let state = GameState::new(GameConfig {
+                preview_count: 5,
+                preview_spacing: spacing,
+                ..GameConfig::default()
+            });
+            let display_count = 5.min(state.next_pieces.len());
+            // Heading and its blank line, 4 rows per piece, and the gaps between them
+            let expected = 2 + display_count * 4 + (display_count - 1) * spacing as usize;
+            assert_eq!(
+                Renderer::next_pieces_lines(&state, 2).len(),
+                expected,
+                "spacing {}",
+                spacing
+            );
+        }
+    }
+
+    #[test]
+    fn test_ghost_in_range() {
+        assert!(ghost_in_range(0, 18, None));
+
+        assert!(!ghost_in_range(0, 18, Some(4)));
+        assert!(!ghost_in_range(13, 18, Some(4)));
+        assert!(ghost_in_range(14, 18, Some(4)));
+        assert!(ghost_in_range(18, 18, Some(4)));
+        assert!(ghost_in_range(18, 18, Some(0)));
+        assert!(!ghost_in_range(17, 18, Some(0)));
+    }
+
+    #[test]
+    fn test_left_column_constraints_follow_hold() {
+        assert_eq!(
+            left_column_constraints(true),
+            [Constraint::Length(HOLD_PANEL_HEIGHT), Constraint::Min(0)]
+        );
+        assert_eq!(
+            left_column_constraints(false),
+            [Constraint::Length(0), Constraint::Min(0)]
+        );
+    }
+
+    #[test]
+    fn test_hold_panel_hidden_when_hold_disabled() {
+        let with_hold = GameState::new(GameConfig::default());
+        assert!(rendered_text(&with_hold).contains("HOLD"));
+
+        let without_hold = GameState::new(GameConfig {
+            enable_hold: false,
+            ..GameConfig::default()
+        });
+        let text = rendered_text(&without_hold);
+        assert!(!text.contains("HOLD"));
+        assert!(text.contains("SCORE"));
+    }
+
+    #[test]
+    fn test_layout_constraints_minimal_ui() {
+        let constraints = layout_constraints(&minimal_state(), WIDE_AREA);
+        assert_eq!(
+            constraints,
+            [
+                Constraint::Length(0),
+                Constraint::Length(22),
+                Constraint::Length(0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_draw_game_minimal_ui_skips_side_panels() {
+        let text = rendered_text(&minimal_state());
+        assert!(!text.contains("NEXT"));
+        assert!(!text.contains("SCORE"));
+
+        let text = rendered_text(&state_with_width(10));
+        assert!(text.contains("NEXT"));
+        assert!(text.contains("SCORE"));
+    }
+
+    #[test]
+    fn test_draw_game_shows_level_up_banner() {
+        let mut state = state_with_width(10);
+        assert!(!rendered_text(&state).contains("LEVEL 2"));
+
+        state.lines_until_next_level = 1;
+        for x in 0..6 {
+            state.board.cells_mut()[19][x] = Some(TetriminoType::O);
+        }
+        state.current_piece = Some(Tetrimino {
+            kind: TetriminoType::I,
+            x: 6,
+            y: 19,
+            rotation: 0,
+        });
+        state.lock_current_piece();
+        state.complete_line_clear();
+        assert!(rendered_text(&state).contains("LEVEL 2"));
+    }
+
+    #[test]
+    fn test_draw_game_shows_hard_drop_warning() {
+        let mut state = GameState::new(GameConfig {
+            safe_hard_drop: true,
+            ..GameConfig::default()
+        });
+        state.spawn_piece();
+        assert!(!rendered_text(&state).contains("DROP AGAIN?"));
+
+        state.board.fill_region(0, 1, 8, 19, Some(TetriminoType::J));
+        state.request_hard_drop(Instant::now());
+        assert!(rendered_text(&state).contains("DROP AGAIN?"));
+    }
+
+    #[test]
+    fn test_height_color_stops() {
+        // On a five-row board each row lands exactly on a gradient stop
+        assert_eq!(Renderer::height_color(4, 5), Color::Rgb(40, 90, 255));
+        assert_eq!(Renderer::height_color(3, 5), Color::Rgb(0, 200, 200));
+        assert_eq!(Renderer::height_color(2, 5), Color::Rgb(60, 200, 60));
+        assert_eq!(Renderer::height_color(1, 5), Color::Rgb(240, 220, 0));
+        assert_eq!(Renderer::height_color(0, 5), Color::Rgb(255, 40, 40));
+    }
+
+    #[test]
+    fn test_height_color_full_board() {
+        assert_eq!(Renderer::height_color(19, 20), Color::Rgb(40, 90, 255));
+        assert_eq!(Renderer::height_color(0, 20), Color::Rgb(255, 40, 40));
+
+        // Halfway between the green and yellow stops
+        let Color::Rgb(r, g, b) = Renderer::height_color(3, 9) else {
+            panic!("expected an RGB color");
+        };
+        assert_eq!((r, g, b), (150, 210, 30));
+    }
+
+    #[test]
+    fn test_draw_title_lists_modes_and_marks_selection() {
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(80, 30)).unwrap();
+        let mut menu = TitleMenu::new();
+        menu.handle(crate::input::MenuInput::Down);
+
+        let stats = LifetimeStats {
+            games_played: 12,
+            best_score: 34_567,
+            ..LifetimeStats::default()
+        };
+
+        terminal
+            .draw(|f| Renderer::draw_title(f, &menu, &stats))
+            .unwrap();
+        let text: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+
+        for mode in GameMode::ALL {
+            assert!(text.contains(mode.label()), "{:?}", mode);
+        }
+        assert!(text.contains("> Sprint"));
+        assert!(text.contains(GameMode::Sprint.description()));
+        assert!(text.contains("Games 12  Best 34567"));
+    }
+
+    #[test]
+    fn test_piece_display_fits_i_piece() {
+        let lines = Renderer::get_piece_display(TetriminoType::I, RotationSystem::Srs, 2);
+        let blocks: usize = lines
+            .iter()
+            .map(|line| line.to_string().matches('█').count())
+            .sum();
+        assert_eq!(blocks, 8);
+    }
+
+    #[test]
+    fn test_piece_display_uses_spawn_orientation() {
+        let row_blocks = |system| -> Vec<usize> {
+            Renderer::get_piece_display(TetriminoType::T, system, 1)
+                .iter()
+                .map(|line| line.to_string().matches('█').count())
+                .collect()
+        };
+        // SRS spawns the T nub up, ARS flat side up
+        assert_eq!(row_blocks(RotationSystem::Srs), vec![0, 1, 3, 0]);
+        assert_eq!(row_blocks(RotationSystem::Ars), vec![0, 0, 3, 1]);
+    }
+
+    #[test]
+    fn test_empty_cell_glyph_grid_on_differs_from_off() {
+        assert_ne!(
+            Renderer::empty_cell_glyph(true),
+            Renderer::empty_cell_glyph(false)
+        );
+        assert_eq!(Renderer::empty_cell_glyph(false).trim(), "");
+    }
+
+    #[test]
+    fn test_ghost_cell_dimmed_uses_dim_solid_block() {
+        let (glyph, style) = Renderer::ghost_cell(GhostStyle::Dimmed, Color::Cyan);
+        assert_eq!(glyph, "██");
+        assert_eq!(style.fg, Some(Color::Cyan));
+        assert!(style.add_modifier.contains(Modifier::DIM));
+    }
+
+    #[test]
+    fn test_ghost_cell_outline_and_hollow_use_distinct_glyphs() {
+        let (outline, outline_style) = Renderer::ghost_cell(GhostStyle::Outline, Color::Red);
+        let (hollow, hollow_style) = Renderer::ghost_cell(GhostStyle::Hollow, Color::Red);
+
+        assert_eq!(outline, "▒▒");
+        assert_eq!(hollow, "[]");
+        assert_eq!(outline_style.fg, Some(Color::Red));
+        assert_eq!(hollow_style.fg, Some(Color::Red));
+        assert!(!outline_style.add_modifier.contains(Modifier::DIM));
+    }
+
+    #[test]
+    fn test_border_glyphs_corners_per_style() {
+        let cases = [
+            (BorderStyle::Rounded, ["╭", "╮", "╰", "╯"]),
+            (BorderStyle::Square, ["┌", "┐", "└", "┘"]),
+            (BorderStyle::Double, ["╔", "╗", "╚", "╝"]),
+            (BorderStyle::None, [" ", " ", " ", " "]),
+        ];
+
+        for (style, [tl, tr, bl, br]) in cases {
+            let glyphs = BorderGlyphs::for_style(style);
+            assert_eq!(glyphs.top_left, tl, "{:?}", style);
+            assert_eq!(glyphs.top_right, tr, "{:?}", style);
+            assert_eq!(glyphs.bottom_left, bl, "{:?}", style);
+            assert_eq!(glyphs.bottom_right, br, "{:?}", style);
+        }
+    }
+
+    #[test]
+    fn test_border_glyphs_default_is_square() {
+        assert_eq!(
+            BorderGlyphs::for_style(BorderStyle::default()),
+            BorderGlyphs::for_style(BorderStyle::Square)
+        );
+    }
+
+    #[test]
+    fn test_empty_cell_glyph_matches_cell_width() {
+        assert_eq!(Renderer::empty_cell_glyph(true).chars().count(), 2);
+        assert_eq!(Renderer::empty_cell_glyph(false).chars().count(), 2);
+    }
+
+    #[test]
+    fn test_frame_stats_empty_window() {
+        let stats = FrameStats::new(3);
+        assert_eq!(stats.min(), None);
+        assert_eq!(stats.max(), None);
+        assert_eq!(stats.average(), None);
+        assert_eq!(stats.fps(), None);
+    }
+
+    #[test]
+    fn test_frame_stats_min_avg_max() {
+        let mut stats = FrameStats::new(3);
+        for ms in [10, 20, 30] {
+            stats.record(Duration::from_millis(ms));
+        }
+
+        assert_eq!(stats.min(), Some(Duration::from_millis(10)));
+        assert_eq!(stats.max(), Some(Duration::from_millis(30)));
+        assert_eq!(stats.average(), Some(Duration::from_millis(20)));
+        assert_eq!(stats.fps(), Some(50.0));
+    }
+
+    #[test]
+    fn test_frame_stats_window_drops_oldest() {
+        let mut stats = FrameStats::new(3);
+        for ms in [100, 10, 20, 30] {
+            stats.record(Duration::from_millis(ms));
+        }
+
+        assert_eq!(stats.max(), Some(Duration::from_millis(30)));
+        assert_eq!(stats.average(), Some(Duration::from_millis(20)));
+    }
+}